@@ -1,122 +1,3228 @@
-use crate::json_values::GuessValue;
+use crate::json_values::{hash_or_null, GuessConfig, GuessValue, IndexMap};
+use crate::store_helpers::GenericStoreGet;
 use crate::{
     aliases::*,
+    errors::SolError,
     map_literal,
-    prelude::{format_hex, SolidityType},
+    prelude::{block_meta_sol, format_hex, raw_log_to_sol, SolidityType},
     sol_type,
 };
-use alloy_primitives::{FixedBytes, Log};
-use alloy_sol_types::{SolCall, SolEnum, SolEvent};
+use alloy_dyn_abi::{DynSolType, DynSolValue, EventExt};
+use alloy_json_abi::Event;
+use alloy_primitives::{keccak256, FixedBytes, Log, B256, I256};
+use alloy_sol_types::{SolCall, SolEnum, SolEvent, SolType};
+use prost_wkt_types::Struct;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
-use std::collections::HashMap;
-use substreams_ethereum::{block_view::LogView, pb::eth::v2::Block};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+use substreams::{
+    pb::substreams::store_delta::Operation,
+    store::{DeltaProto, Deltas},
+};
+use substreams_ethereum::{
+    block_view::LogView,
+    pb::eth::{
+        rpc::{RpcCall, RpcCalls, RpcResponse},
+        v2::{BigInt as PbBigInt, Block, Log as PbLog, TransactionTrace, TransactionTraceStatus},
+    },
+    rpc::eth_call,
+};
 
+/// The "from", "to", "block_number", "tx_hash" and "tx_index" of the transaction an event came from,
+/// plus the "log_index" within its receipt and the block wide "ordinal" of the log,
+/// so `tx_hash:log_index` is a unique id for the event.
 #[derive(Serialize, Deserialize)]
 pub struct TxMeta(SolidityType);
 impl TxMeta {
-    pub fn new(from: &String, to: &String, block_number: &String) -> TxMeta {
+    pub fn new(
+        from: &String,
+        to: &String,
+        block_number: &String,
+        tx_hash: &String,
+        tx_index: u32,
+        log_index: u32,
+        ordinal: u64,
+    ) -> TxMeta {
         TxMeta(map_literal! {
             "from"; sol_type!(Address, from),
             "to"; sol_type!(Address, to),
-            "block_number"; sol_type!(Uint, block_number)
+            "block_number"; sol_type!(Uint, block_number),
+            "tx_hash"; sol_type!(FixedArray, tx_hash),
+            "tx_index"; SolidityType::Uint(U256::from(tx_index)),
+            "log_index"; SolidityType::Uint(U256::from(log_index)),
+            "ordinal"; SolidityType::Uint(U256::from(ordinal))
+        })
+    }
+
+    fn from_log(value: &LogView, block_number: &String) -> Self {
+        Self::from_pb_log(value.receipt.transaction, value.log, block_number)
+    }
+
+    fn from_pb_log(txn: &TransactionTrace, log: &PbLog, block_number: &String) -> Self {
+        let mut meta = Self::from_trace(txn, block_number);
+        meta.0
+            .insert("log_index", SolidityType::Uint(U256::from(log.index)));
+        meta.0
+            .insert("ordinal", SolidityType::Uint(U256::from(log.ordinal)));
+        meta
+    }
+
+    /// Only reads fields of the transaction itself, which every detail level carries.
+    /// A missing sender can only mean the field was stripped, so it's Null rather than the zero address.
+    fn from_trace(txn: &TransactionTrace, block_number: &String) -> Self {
+        let from = if txn.from.is_empty() {
+            SolidityType::Null
+        } else {
+            address_from_bytes(&txn.from)
+        };
+        TxMeta(map_literal! {
+            "from"; from,
+            "to"; address_from_bytes(&txn.to),
+            "block_number"; sol_type!(Uint, block_number),
+            "tx_hash"; hash_or_null(&txn.hash),
+            "tx_index"; SolidityType::Uint(U256::from(txn.index))
+        })
+    }
+
+    /// Same as the meta attached to events, with the transaction's "gas_used" and "gas_price" as well.
+    pub fn with_gas(txn: &TransactionTrace, block_number: &String) -> Self {
+        let mut meta = Self::from_trace(txn, block_number);
+        meta.0
+            .insert("gas_used", SolidityType::Uint(U256::from(txn.gas_used)));
+        meta.0
+            .insert("gas_price", pb_bigint_to_sol(txn.gas_price.as_ref()));
+        meta
+    }
+}
+
+/// The same ids as TxMeta as plain rust types, for `EventHelpers::get_typed_events`.
+/// A missing sender, recipient or hash is zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxMetaTyped {
+    pub from: Address,
+    pub to: Address,
+    pub block_number: u64,
+    pub tx_hash: B256,
+    pub tx_index: u32,
+    pub log_index: u32,
+    pub ordinal: u64,
+}
+
+impl TxMetaTyped {
+    fn from_log(log: &LogView, block_number: u64) -> Self {
+        let txn = log.receipt.transaction;
+        let address =
+            |bytes: &[u8]| <[u8; 20]>::try_from(bytes).map_or(Address::ZERO, Address::from);
+        TxMetaTyped {
+            from: address(&txn.from),
+            to: address(&txn.to),
+            block_number,
+            tx_hash: <[u8; 32]>::try_from(txn.hash.as_slice()).map_or(B256::ZERO, B256::from),
+            tx_index: txn.index,
+            log_index: log.log.index,
+            ordinal: log.log.ordinal,
+        }
+    }
+}
+
+/// The block wide ordinal of the log an event from `get_events` came from, read out of its tx_meta.
+/// Events without one, ie built by hand, are at ordinal 0.
+pub fn event_ordinal(event: &SolidityType) -> u64 {
+    match event.get("tx_meta").get("ordinal") {
+        SolidityType::Uint(ordinal) => u64::try_from(ordinal).unwrap_or(0),
+        _ => 0,
+    }
+}
+
+impl From<TxMeta> for SolidityType {
+    fn from(value: TxMeta) -> Self {
+        value.0
+    }
+}
+
+/// Converts an optional protobuf BigInt, see `From<&PbBigInt>`.
+/// A missing value is zero, since that's how protobuf leaves it out.
+fn pb_bigint_to_sol(value: Option<&PbBigInt>) -> SolidityType {
+    value.map_or(SolidityType::Uint(U256::ZERO), SolidityType::from)
+}
+
+/// Builds an address straight from chain bytes.
+/// Anything that isn't 20 bytes long, like the empty `to` of a contract creation, becomes the zero address.
+fn address_from_bytes(bytes: &[u8]) -> SolidityType {
+    if bytes.len() == 20 {
+        SolidityType::Address(Address::from_slice(bytes))
+    } else {
+        SolidityType::Address(Address::ZERO)
+    }
+}
+
+/// Whether a log emitted from `address` should be kept, an empty `addresses` slice matches everything.
+fn address_matches(addresses: &[&Address], address: &[u8]) -> bool {
+    addresses.is_empty()
+        || (address.len() == 20 && addresses.contains(&&Address::from_slice(address)))
+}
+
+/// Anything that can name an address when building an AddressSet.
+pub trait ToAddress {
+    /// Panics if the value can't name an address, see `try_to_address`.
+    fn to_address(&self) -> Address {
+        self.try_to_address().unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as `to_address`, but errors for a string that isn't a hex address, or a value that isn't an address or string.
+    fn try_to_address(&self) -> Result<Address, SolError>;
+}
+
+impl ToAddress for Address {
+    fn try_to_address(&self) -> Result<Address, SolError> {
+        Ok(*self)
+    }
+}
+
+impl ToAddress for &Address {
+    fn try_to_address(&self) -> Result<Address, SolError> {
+        Ok(**self)
+    }
+}
+
+impl ToAddress for &str {
+    fn try_to_address(&self) -> Result<Address, SolError> {
+        self.parse().map_err(|_| SolError::ParseFailure {
+            input: self.to_string(),
+            expected: "an address",
         })
     }
+}
+
+impl ToAddress for String {
+    fn try_to_address(&self) -> Result<Address, SolError> {
+        self.as_str().try_to_address()
+    }
+}
+
+impl ToAddress for SolidityType {
+    fn try_to_address(&self) -> Result<Address, SolError> {
+        match self {
+            SolidityType::Address(address) => Ok(*address),
+            SolidityType::String(address) => address.try_to_address(),
+            _ => Err(SolError::WrongVariant {
+                operation: "use as an address",
+                found: self.kind(),
+            }),
+        }
+    }
+}
+
+/// A set of addresses to filter logs by, where some addresses can be aliases of a canonical one,
+/// ie the implementation behind an EIP-1967 proxy. Logs from an alias match and are reported under the canonical address.
+/// Addresses are deduplicated, and an empty set matches everything.
+///
+/// ```ignore
+/// let pools = AddressSet::new().add(proxy).alias(proxy, implementation);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AddressSet {
+    canonical: HashMap<Address, Address>,
+}
+
+impl AddressSet {
+    pub fn new() -> Self {
+        AddressSet::default()
+    }
+
+    pub fn add(mut self, address: impl ToAddress) -> Self {
+        let address = address.to_address();
+        self.canonical.insert(address, address);
+        self
+    }
+
+    /// Matches logs from `alias` as well, reporting them under `canonical`.
+    pub fn alias(mut self, canonical: impl ToAddress, alias: impl ToAddress) -> Self {
+        let canonical = canonical.to_address();
+        self.canonical.insert(canonical, canonical);
+        self.canonical.insert(alias.to_address(), canonical);
+        self
+    }
 
-    fn from_log(value: &substreams_ethereum::block_view::LogView, block_number: &String) -> Self {
-        let txn = &value.receipt.transaction;
-        let (from, to) = (&format_hex(&txn.from), &format_hex(&txn.to));
-        TxMeta::new(from, to, block_number)
+    pub fn is_empty(&self) -> bool {
+        self.canonical.is_empty()
     }
+
+    /// The canonical address for `address`, or None if it isn't in the set.
+    /// An empty set matches everything, so every address is its own canonical address.
+    pub fn canonical(&self, address: &Address) -> Option<Address> {
+        if self.is_empty() {
+            Some(*address)
+        } else {
+            self.canonical.get(address).copied()
+        }
+    }
+}
+
+/// A set of addresses checked one address at a time rather than listed up front,
+/// ie the pools a factory created, kept by a store module under keys like `pool:0x...`.
+///
+/// ```ignore
+/// let pools = DynamicAddressSet::from_store(&pool_store, "pool:");
+/// let swaps = Swap::get_events_from_set(&blk, &pools);
+/// ```
+pub struct DynamicAddressSet<'a> {
+    source: AddressSource<'a>,
+}
+
+enum AddressSource<'a> {
+    Store {
+        store: &'a dyn GenericStoreGet<SolidityType>,
+        prefix: String,
+    },
+    Fixed(HashSet<Address>),
+}
+
+impl<'a> DynamicAddressSet<'a> {
+    /// The addresses `store` has a key for, keyed `{prefix}{address}` with the address in lowercase 0x hex,
+    /// the way `generic_set` writes an Address key. Each address is looked up with `generic_has` when it's checked,
+    /// so real stores don't decode the value, and nothing is listed.
+    pub fn from_store(store: &'a dyn GenericStoreGet<SolidityType>, prefix: &str) -> Self {
+        DynamicAddressSet {
+            source: AddressSource::Store {
+                store,
+                prefix: prefix.to_string(),
+            },
+        }
+    }
+
+    /// The addresses in the keys of `deltas` under `prefix`, ie the pools a factory module stored in this block.
+    /// Deleted keys and keys that aren't an address once the prefix is stripped are left out.
+    pub fn from_deltas(deltas: &Deltas<DeltaProto<Struct>>, prefix: &str) -> Self {
+        let addresses = deltas
+            .deltas
+            .iter()
+            .filter(|delta| delta.operation != Operation::Delete)
+            .filter_map(|delta| delta.key.strip_prefix(prefix)?.parse().ok())
+            .collect();
+        DynamicAddressSet {
+            source: AddressSource::Fixed(addresses),
+        }
+    }
+
+    pub fn contains(&self, address: &Address) -> bool {
+        match &self.source {
+            AddressSource::Store { store, prefix } => {
+                let key = format!("{prefix}{}", format_hex(address.as_slice()));
+                store.generic_has(SolidityType::String(key))
+            }
+            AddressSource::Fixed(addresses) => addresses.contains(address),
+        }
+    }
+}
+
+impl From<&[&Address]> for AddressSet {
+    fn from(value: &[&Address]) -> Self {
+        value
+            .iter()
+            .fold(AddressSet::new(), |set, address| set.add(*address))
+    }
+}
+
+/// Checks the standard 2048 bit log bloom for `input`, ie an address or a topic.
+/// False positives are possible, false negatives aren't. A bloom that isn't 256 bytes long can't rule anything out.
+pub fn bloom_contains(bloom: &[u8], input: &[u8]) -> bool {
+    if bloom.len() != 256 {
+        return true;
+    }
+
+    let hash = keccak256(input);
+    [0, 2, 4].iter().all(|&i| {
+        let bit = (hash[i + 1] as usize + ((hash[i] as usize) << 8)) & 0x7FF;
+        bloom[255 - bit / 8] & (1 << (bit % 8)) != 0
+    })
+}
+
+/// keccak256 of the rlp of an empty trie, the transactions root of a block without any transactions.
+const EMPTY_TRIE_ROOT: [u8; 32] =
+    hex_literal::hex!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+
+/// How much of each transaction a block was streamed with, from least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DetailLevel {
+    /// The header says the block has transactions, but none of them are in it.
+    Header,
+    /// Transaction traces without their receipts, so there are no logs.
+    Transactions,
+    /// Transactions with their receipts and logs, but without call traces.
+    Base,
+    /// Everything, including the call traces.
+    Extended,
+}
+
+impl Display for DetailLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DetailLevel::Header => "header",
+            DetailLevel::Transactions => "transactions",
+            DetailLevel::Base => "base",
+            DetailLevel::Extended => "extended",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Errors when `blk` was streamed at a lower detail level than `operation` needs.
+fn require_detail(
+    blk: &Block,
+    operation: &'static str,
+    required: DetailLevel,
+) -> Result<(), SolError> {
+    let found = blk.detail_level();
+    if found < required {
+        Err(SolError::UnsupportedDetailLevel {
+            operation,
+            required,
+            found,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// `Block::logs`, but with a descriptive panic rather than an unwrap deep in the receipts when the block has none.
+fn checked_logs<'a>(blk: &'a Block, operation: &'static str) -> impl Iterator<Item = LogView<'a>> {
+    require_detail(blk, operation, DetailLevel::Base).unwrap_or_else(|err| panic!("{err}"));
+    blk.logs()
 }
 
 pub trait BlockHelpers {
+    /// Detects the detail level the block was streamed at from which of its fields are populated.
+    /// A block without any transactions has nothing missing, so it's Extended.
+    fn detail_level(&self) -> DetailLevel;
+
+    /// Checks the header's log bloom to see if the block may have a log from `address`, with `topic0` if one is given.
+    /// A false means the block definitely doesn't, so it can be skipped without walking the logs.
+    /// Blocks without a header always may.
+    fn may_contain(&self, address: &Address, topic0: Option<&B256>) -> bool;
+
+    /// Logs from successful transactions only, as yielded by `Block::logs`.
+    /// Panics if the block doesn't carry receipts, see `try_alloy_logs`.
     fn alloy_logs(&self, addresses: &[&Address]) -> Vec<(Log, TxMeta)>;
+
+    /// Starts a query over the logs of the block, for filtering by more than the emitting address, see `LogQuery`.
+    fn log_query(&self) -> LogQuery<'_>;
+
+    /// Same as `alloy_logs`, but errors if the block is below the base detail level rather than panicking.
+    fn try_alloy_logs(&self, addresses: &[&Address]) -> Result<Vec<(Log, TxMeta)>, SolError>;
+
+    /// Same as `alloy_logs`, but only the logs whose topic0 is `topic0`, so logs of other events never reach a decoder.
+    /// Anonymous events have no topic0 to match, so a None `topic0` is the same as `alloy_logs`.
+    fn alloy_logs_with_topic(
+        &self,
+        addresses: &[&Address],
+        topic0: Option<&B256>,
+    ) -> Vec<(Log, TxMeta)>;
+
+    /// Same as `alloy_logs`, but filtered by an AddressSet.
+    /// The TxMeta also records the "address" that emitted the log and its "canonical_address".
+    fn alloy_logs_in(&self, addresses: &AddressSet) -> Vec<(Log, TxMeta)>;
+
+    /// Walks every call of every transaction trace, rather than only the successful receipts.
+    /// The bool is true when the call emitting the log was reverted, those logs are skipped unless `include_reverted` is set.
+    /// Panics if the block doesn't carry call traces, see `try_alloy_logs_all_calls`.
+    fn alloy_logs_all_calls(
+        &self,
+        addresses: &[&Address],
+        include_reverted: bool,
+    ) -> Vec<(Log, TxMeta, bool)>;
+
+    /// Same as `alloy_logs_all_calls`, but errors if the block is below the extended detail level rather than panicking.
+    fn try_alloy_logs_all_calls(
+        &self,
+        addresses: &[&Address],
+        include_reverted: bool,
+    ) -> Result<Vec<(Log, TxMeta, bool)>, SolError>;
+
+    /// The block's metadata as a SolidityType struct, see `block_meta_sol`.
+    fn meta_sol(&self) -> SolidityType;
+
+    /// Returns every log whose topic0 isn't one of `known_topic0s` in its raw form, see `raw_log_to_sol`.
+    fn unknown_logs(&self, known_topic0s: &[B256]) -> SolidityType;
+
+    /// Decodes the logs matching `event` straight from the ABI, without going through serde_json and `guess_json_value`.
+    /// Each event is a struct of its named inputs plus `tx_meta`, like `EventHelpers::get_events`, and is the recommended path.
+    /// ie `blk.get_events_dyn(&Event::parse("event Swap(int256 amount0, int256 amount1)").unwrap(), &[&pool])`
+    fn get_events_dyn(&self, event: &Event, addresses: &[&Address]) -> SolidityType;
+
+    /// Every ERC20 Transfer from the tokens in `token_filter`, or from any token if it's empty.
+    /// Each is a struct with the "token" that emitted it, "from", "to", "amount" and "tx_meta".
+    fn erc20_transfers(&self, token_filter: &[&Address]) -> SolidityType;
+
+    /// Same as `erc20_transfers` but for ERC721, with a "token_id" rather than an "amount".
+    /// The two share a signature, ERC721 transfers are told apart by their indexed token id making for 4 topics rather than 3.
+    fn erc721_transfers(&self, token_filter: &[&Address]) -> SolidityType;
+
+    /// Nets the ERC20 transfers of the block per token and holder, ie `{ token: { holder: "-100" } }`.
+    /// The net changes are Ints, and holders whose balance ends up unchanged are left out.
+    fn erc20_balance_changes(&self, token_filter: &[&Address]) -> SolidityType;
+
+    /// The successful transactions sent from or to any of `addresses`, or every successful transaction if it's empty.
+    /// Each is a struct like `TransactionHelpers::transactions_sol` gives, so plain ETH transfers are the ones with a "value".
+    fn transactions(&self, addresses: &[&Address]) -> SolidityType;
+
+    /// Same as `transactions`, but failed and reverted transactions are included, see their "status".
+    fn transactions_including_failed(&self, addresses: &[&Address]) -> SolidityType;
+}
+
+mod erc20 {
+    use alloy_sol_macro::sol;
+
+    crate::loose_sol! {
+        event Transfer(address indexed from, address indexed to, uint256 value);
+    }
+}
+
+mod erc721 {
+    use alloy_sol_macro::sol;
+
+    crate::loose_sol! {
+        event Transfer(address indexed from, address indexed to, uint256 indexed tokenId);
+    }
+}
+
+/// The logs of `blk` from `token_filter` whose topic0 is the shared Transfer signature and that have `topic_count` topics.
+fn transfer_logs<'a>(
+    blk: &'a Block,
+    token_filter: &'a [&Address],
+    topic_count: usize,
+) -> impl Iterator<Item = LogView<'a>> {
+    checked_logs(blk, "read the transfers of").filter(move |log| {
+        address_matches(token_filter, log.address())
+            && log.topics().len() == topic_count
+            && log.topics()[0].as_slice() == erc20::Transfer::SIGNATURE_HASH.as_slice()
+    })
 }
 
 impl BlockHelpers for Block {
+    fn detail_level(&self) -> DetailLevel {
+        if self.transaction_traces.is_empty() {
+            let has_transactions = self.header.as_ref().map_or(false, |header| {
+                !header.transactions_root.is_empty() && header.transactions_root != EMPTY_TRIE_ROOT
+            });
+            return if has_transactions {
+                DetailLevel::Header
+            } else {
+                DetailLevel::Extended
+            };
+        }
+
+        if self
+            .transaction_traces
+            .iter()
+            .any(|txn| !txn.calls.is_empty())
+        {
+            DetailLevel::Extended
+        } else if self
+            .transaction_traces
+            .iter()
+            .all(|txn| txn.receipt.is_some())
+        {
+            DetailLevel::Base
+        } else {
+            DetailLevel::Transactions
+        }
+    }
+
+    fn may_contain(&self, address: &Address, topic0: Option<&B256>) -> bool {
+        let Some(header) = &self.header else {
+            return true;
+        };
+
+        bloom_contains(&header.logs_bloom, address.as_slice())
+            && topic0.map_or(true, |topic0| {
+                bloom_contains(&header.logs_bloom, topic0.as_slice())
+            })
+    }
+
     fn alloy_logs(&self, addresses: &[&Address]) -> Vec<(Log, TxMeta)> {
+        self.try_alloy_logs(addresses)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn log_query(&self) -> LogQuery<'_> {
+        LogQuery::new(self)
+    }
+
+    fn try_alloy_logs(&self, addresses: &[&Address]) -> Result<Vec<(Log, TxMeta)>, SolError> {
+        self.log_query().addresses(addresses).try_collect_alloy()
+    }
+
+    fn alloy_logs_with_topic(
+        &self,
+        addresses: &[&Address],
+        topic0: Option<&B256>,
+    ) -> Vec<(Log, TxMeta)> {
+        let query = self.log_query().addresses(addresses);
+        match topic0 {
+            Some(topic0) => query.topic0(*topic0),
+            None => query,
+        }
+        .collect_alloy()
+    }
+
+    fn alloy_logs_in(&self, addresses: &AddressSet) -> Vec<(Log, TxMeta)> {
         let block_number = self.number.to_string();
-        self.logs()
+        checked_logs(self, "read the logs of")
+            .filter(|log| log.address().len() == 20)
             .filter_map(|log| {
-                if addresses.is_empty() {
-                    Some((log, TxMeta::from_log(&log, &block_number)))
-                } else {
-                    let address = Address::from_slice(log.address());
-                    if addresses.contains(&&address) {
-                        Some((log, TxMeta::from_log(&log, &block_number)))
-                    } else {
-                        None
-                    }
-                }
+                let address = Address::from_slice(log.address());
+                let canonical = addresses.canonical(&address)?;
+                let mut meta = TxMeta::from_log(&log, &block_number);
+                meta.0.insert("address", SolidityType::Address(address));
+                meta.0
+                    .insert("canonical_address", SolidityType::Address(canonical));
+                Some((log.into_log(), meta))
             })
-            .map(|(log, meta)| (log.into_log(), meta))
             .collect()
     }
-}
 
-pub trait EventHelpers {
-    fn get_events(blk: &Block, addresses: &[&Address]) -> SolidityType;
-}
+    fn alloy_logs_all_calls(
+        &self,
+        addresses: &[&Address],
+        include_reverted: bool,
+    ) -> Vec<(Log, TxMeta, bool)> {
+        self.try_alloy_logs_all_calls(addresses, include_reverted)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
 
-impl<T> EventHelpers for T
-where
-    T: SolEvent + Serialize,
-{
-    fn get_events(blk: &Block, addresses: &[&Address]) -> SolidityType {
-        let validate = false;
-        let events: Vec<SolidityType> = blk
-            .alloy_logs(addresses)
+    fn try_alloy_logs_all_calls(
+        &self,
+        addresses: &[&Address],
+        include_reverted: bool,
+    ) -> Result<Vec<(Log, TxMeta, bool)>, SolError> {
+        require_detail(self, "read the calls of", DetailLevel::Extended)?;
+        let block_number = self.number.to_string();
+        let logs = self
+            .transaction_traces
             .iter()
-            .filter_map(|(l, meta)| {
-                let event = T::decode_log_object(l, validate).ok();
-                if let Some(event) = event {
-                    Some((event, meta))
-                } else {
-                    None
-                }
+            .flat_map(|txn| {
+                let block_number = &block_number;
+                txn.calls
+                    .iter()
+                    .filter(move |call| include_reverted || !call.state_reverted)
+                    .flat_map(move |call| {
+                        call.logs
+                            .iter()
+                            .filter(move |log| address_matches(addresses, &log.address))
+                            .map(move |log| {
+                                (
+                                    log.into_log(),
+                                    TxMeta::from_pb_log(txn, log, block_number),
+                                    call.state_reverted,
+                                )
+                            })
+                    })
             })
-            .map(|(event, meta)| {
-                let map = serde_json::to_value(event).unwrap();
-                let mut event_guess = SolidityType::guess_json_value(&map).unwrap();
-                if let SolidityType::Struct(ref mut map) = &mut event_guess {
-                    let key = String::from("tx_meta");
+            .collect();
+        Ok(logs)
+    }
 
-                    map.insert(key, meta.0.clone());
-                } else {
-                    panic!("Event wasn't found to be an Object!?");
+    fn meta_sol(&self) -> SolidityType {
+        block_meta_sol(self)
+    }
+
+    fn erc20_transfers(&self, token_filter: &[&Address]) -> SolidityType {
+        let validate = false;
+        let block_number = self.number.to_string();
+        let transfers: Vec<SolidityType> = transfer_logs(self, token_filter, 3)
+            .filter_map(|log| {
+                let event = erc20::Transfer::decode_log_object(&log.into_log(), validate).ok()?;
+                Some(map_literal! {
+                    "token"; SolidityType::Address(Address::from_slice(log.address())),
+                    "from"; SolidityType::Address(event.from),
+                    "to"; SolidityType::Address(event.to),
+                    "amount"; SolidityType::Uint(event.value),
+                    "tx_meta"; TxMeta::from_log(&log, &block_number).0
+                })
+            })
+            .collect();
+
+        if transfers.is_empty() {
+            SolidityType::Null
+        } else {
+            SolidityType::List(transfers)
+        }
+    }
+
+    fn erc721_transfers(&self, token_filter: &[&Address]) -> SolidityType {
+        let validate = false;
+        let block_number = self.number.to_string();
+        let transfers: Vec<SolidityType> = transfer_logs(self, token_filter, 4)
+            .filter_map(|log| {
+                let event = erc721::Transfer::decode_log_object(&log.into_log(), validate).ok()?;
+                Some(map_literal! {
+                    "token"; SolidityType::Address(Address::from_slice(log.address())),
+                    "from"; SolidityType::Address(event.from),
+                    "to"; SolidityType::Address(event.to),
+                    "token_id"; SolidityType::Uint(event.tokenId),
+                    "tx_meta"; TxMeta::from_log(&log, &block_number).0
+                })
+            })
+            .collect();
+
+        if transfers.is_empty() {
+            SolidityType::Null
+        } else {
+            SolidityType::List(transfers)
+        }
+    }
+
+    fn erc20_balance_changes(&self, token_filter: &[&Address]) -> SolidityType {
+        let validate = false;
+        // (received, sent) per token and holder, kept apart so nothing underflows
+        let mut totals: IndexMap<(Address, Address), (U256, U256)> = IndexMap::new();
+        for log in transfer_logs(self, token_filter, 3) {
+            let Ok(event) = erc20::Transfer::decode_log_object(&log.into_log(), validate) else {
+                continue;
+            };
+            let token = Address::from_slice(log.address());
+            let to = totals.entry((token, event.to)).or_default();
+            to.0 = to.0.saturating_add(event.value);
+            let from = totals.entry((token, event.from)).or_default();
+            from.1 = from.1.saturating_add(event.value);
+        }
+
+        let mut changes = SolidityType::Null;
+        for ((token, holder), (received, sent)) in totals {
+            let net = match received.cmp(&sent) {
+                std::cmp::Ordering::Equal => continue,
+                std::cmp::Ordering::Greater => I256::try_from(received - sent).unwrap_or(I256::MAX),
+                std::cmp::Ordering::Less => {
+                    I256::try_from(sent - received).map_or(I256::MIN, |net| -net)
                 }
+            };
+            let token = SolidityType::Address(token).to_string();
+            let holder = SolidityType::Address(holder).to_string();
+            changes[token.as_str()][holder.as_str()] = SolidityType::Int(net);
+        }
+        changes
+    }
+
+    fn get_events_dyn(&self, event: &Event, addresses: &[&Address]) -> SolidityType {
+        let topic0 = (!event.anonymous).then(|| event.selector());
+        if !may_have_log(self, addresses, topic0.as_ref()) {
+            return SolidityType::Null;
+        }
+
+        let validate = false;
+        let events: Vec<SolidityType> = self
+            .alloy_logs(addresses)
+            .iter()
+            .filter_map(|(log, meta)| {
+                let decoded = event
+                    .decode_log_parts(log.topics().iter().copied(), &log.data, validate)
+                    .ok()?;
+                let mut indexed = decoded.indexed.into_iter();
+                let mut body = decoded.body.into_iter();
 
-                event_guess
+                let mut fields: IndexMap<String, SolidityType> = event
+                    .inputs
+                    .iter()
+                    .map(|input| {
+                        let value = if input.indexed {
+                            indexed.next()
+                        } else {
+                            body.next()
+                        };
+                        (
+                            input.name.clone(),
+                            value.map(SolidityType::from).unwrap_or(SolidityType::Null),
+                        )
+                    })
+                    .collect();
+                fields.insert("tx_meta".to_string(), meta.0.clone());
+                Some(SolidityType::Struct(fields))
             })
             .collect();
 
-        if events.len() == 0 {
+        if events.is_empty() {
             SolidityType::Null
         } else {
             SolidityType::List(events)
         }
     }
+
+    fn unknown_logs(&self, known_topic0s: &[B256]) -> SolidityType {
+        let logs: Vec<SolidityType> = checked_logs(self, "read the logs of")
+            .filter(|log| match log.topics().first() {
+                Some(topic0) => !known_topic0s
+                    .iter()
+                    .any(|known| known.as_slice() == topic0.as_slice()),
+                None => true,
+            })
+            .map(|log| raw_log_to_sol(log.log))
+            .collect();
+
+        if logs.is_empty() {
+            SolidityType::Null
+        } else {
+            SolidityType::List(logs)
+        }
+    }
+
+    fn transactions(&self, addresses: &[&Address]) -> SolidityType {
+        self.transactions_sol(|txn| {
+            txn.status() == TransactionTraceStatus::Succeeded && touches(txn, addresses)
+        })
+    }
+
+    fn transactions_including_failed(&self, addresses: &[&Address]) -> SolidityType {
+        self.transactions_sol(|txn| touches(txn, addresses))
+    }
 }
 
-pub trait FunctionHelpers {
-    fn rpc_call(&self) -> prost_wkt_types::Struct;
+/// Whether the transaction was sent from or to one of `addresses`, an empty slice matches everything.
+fn touches(txn: &TransactionTrace, addresses: &[&Address]) -> bool {
+    address_matches(addresses, &txn.from) || address_matches(addresses, &txn.to)
 }
 
-pub trait AlloyLog {
-    fn into_log(&self) -> Log;
+pub trait TransactionHelpers {
+    /// Every transaction trace that passes `filter` as a list of structs with
+    /// "hash", "from", "to", "value", "gas_used", "gas_price", "nonce", "status" and "block_number".
+    /// Panics if the block doesn't carry its transactions, see `try_transactions_sol`.
+    fn transactions_sol(&self, filter: impl Fn(&TransactionTrace) -> bool) -> SolidityType;
+
+    /// Same as `transactions_sol`, but errors for a block streamed with only its header rather than panicking.
+    fn try_transactions_sol(
+        &self,
+        filter: impl Fn(&TransactionTrace) -> bool,
+    ) -> Result<SolidityType, SolError>;
 }
 
-impl AlloyLog for LogView<'_> {
-    fn into_log(&self) -> Log {
-        let topics = self
-            .topics()
+impl TransactionHelpers for Block {
+    fn transactions_sol(&self, filter: impl Fn(&TransactionTrace) -> bool) -> SolidityType {
+        self.try_transactions_sol(filter)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_transactions_sol(
+        &self,
+        filter: impl Fn(&TransactionTrace) -> bool,
+    ) -> Result<SolidityType, SolError> {
+        require_detail(self, "read the transactions of", DetailLevel::Transactions)?;
+        let transactions: Vec<SolidityType> = self
+            .transaction_traces
             .iter()
-            .map(|t| FixedBytes::try_from(&t.as_slice()[..]).unwrap())
+            .filter(|txn| filter(txn))
+            .map(|txn| {
+                map_literal! {
+                    "hash"; hash_or_null(&txn.hash),
+                    "from"; address_from_bytes(&txn.from),
+                    "to"; address_from_bytes(&txn.to),
+                    "value"; pb_bigint_to_sol(txn.value.as_ref()),
+                    "gas_used"; SolidityType::Uint(U256::from(txn.gas_used)),
+                    "gas_price"; pb_bigint_to_sol(txn.gas_price.as_ref()),
+                    "nonce"; SolidityType::Uint(U256::from(txn.nonce)),
+                    "status"; SolidityType::String(txn.status().as_str_name().to_string()),
+                    "block_number"; SolidityType::Uint(U256::from(self.number))
+                }
+            })
             .collect();
 
-        let data = self.data().to_vec().into();
+        if transactions.is_empty() {
+            Ok(SolidityType::Null)
+        } else {
+            Ok(SolidityType::List(transactions))
+        }
+    }
+}
 
-        Log::new(topics, data).expect("Couldn't create a AlloyLog from a LogView")
+/// The logs of a block indexed by their (address, topic0), so decoding several event types out of the same block
+/// only walks its logs once. Building it is a single pass that doesn't decode anything, see `EventHelpers::get_events_cached`.
+pub struct BlockEventCache<'a> {
+    block_number: String,
+    /// Every log in block order, along with the transaction it came from.
+    logs: Vec<(&'a TransactionTrace, &'a PbLog)>,
+    /// Positions in `logs`, keyed by the raw 32 byte topic0.
+    index: HashMap<(Address, B256), Vec<usize>>,
+}
+
+impl<'a> BlockEventCache<'a> {
+    pub fn new(blk: &'a Block) -> Self {
+        let mut logs = Vec::new();
+        let mut index: HashMap<(Address, B256), Vec<usize>> = HashMap::new();
+        for log in checked_logs(blk, "cache the logs of") {
+            let position = logs.len();
+            logs.push((log.receipt.transaction, log.log));
+
+            let (Ok(address), Some(topic0)) = (
+                <[u8; 20]>::try_from(log.address()),
+                log.topics()
+                    .first()
+                    .and_then(|topic| <[u8; 32]>::try_from(&topic[..]).ok()),
+            ) else {
+                continue;
+            };
+            index
+                .entry((Address::from(address), B256::from(topic0)))
+                .or_default()
+                .push(position);
+        }
+
+        BlockEventCache {
+            block_number: blk.number.to_string(),
+            logs,
+            index,
+        }
+    }
+
+    /// The number of logs in the block.
+    pub fn len(&self) -> usize {
+        self.logs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.logs.is_empty()
+    }
+
+    /// Same as `BlockHelpers::alloy_logs`, but only the logs whose topic0 is `topic0`, in block order.
+    /// Anonymous events have no topic0 to look up, so a None `topic0` falls back to filtering every log by address.
+    pub fn alloy_logs(&self, addresses: &[&Address], topic0: Option<&B256>) -> Vec<(Log, TxMeta)> {
+        let mut positions: Vec<usize> = match topic0 {
+            Some(topic0) if !addresses.is_empty() => addresses
+                .iter()
+                .filter_map(|address| self.index.get(&(**address, *topic0)))
+                .flatten()
+                .copied()
+                .collect(),
+            Some(topic0) => self
+                .index
+                .iter()
+                .filter(|((_, topic), _)| topic == topic0)
+                .flat_map(|(_, positions)| positions)
+                .copied()
+                .collect(),
+            None => (0..self.logs.len())
+                .filter(|position| address_matches(addresses, &self.logs[*position].1.address))
+                .collect(),
+        };
+        // the same address can be passed twice, and lookups across addresses come back out of order
+        positions.sort_unstable();
+        positions.dedup();
+
+        positions
+            .into_iter()
+            .map(|position| {
+                let (txn, log) = self.logs[position];
+                (
+                    log.into_log(),
+                    TxMeta::from_pb_log(txn, log, &self.block_number),
+                )
+            })
+            .collect()
+    }
+}
+
+pub trait EventHelpers {
+    /// The events emitted by `addresses`, or by any contract if it's empty, or Null if there aren't any.
+    /// Fields are typed by the event signature, so an `int24` is an Int and a `bytes4` is FixedBytes, like `get_events_dyn` gives.
+    fn get_events(blk: &Block, addresses: &[&Address]) -> SolidityType;
+
+    /// Same as `get_events`, but the candidate logs are looked up from a BlockEventCache instead of walking the block again.
+    fn get_events_cached(cache: &BlockEventCache, addresses: &[&Address]) -> SolidityType;
+
+    /// Same as `get_events`, but filtered by an AddressSet, see `BlockHelpers::alloy_logs_in`.
+    fn get_events_in(blk: &Block, addresses: &AddressSet) -> SolidityType;
+
+    /// Same as `get_events`, but each event also carries its "event_name" and the "address" of the contract that emitted it.
+    /// Both are strings, so they pass through stores and entity sinks as is.
+    fn get_events_tagged(blk: &Block, addresses: &[&Address]) -> SolidityType;
+
+    /// Same as `get_events`, but logs whose topic0 matches the event and still fail to decode are reported rather than dropped,
+    /// which is usually an abi mismatch like the wrong parameters being indexed. Logs with any other topic0 are skipped silently.
+    fn get_events_with_errors(
+        blk: &Block,
+        addresses: &[&Address],
+    ) -> (SolidityType, Vec<DecodeFailure>);
+
+    /// Same as `get_events`, but only the indexed parameters are read out of the topics and the data is never decoded,
+    /// which is much cheaper for high volume events. They're named "topic1" to "topic3" after the topic they came from.
+    /// Addresses are narrowed to 20 bytes, uints and bools are read as such, and dynamic types stay as the hash in the topic.
+    fn get_event_topics(blk: &Block, addresses: &[&Address]) -> SolidityType;
+
+    /// Same as `get_events`, but the events stay the types `sol!` generated, next to their meta as plain rust types.
+    /// Nothing goes through SolidityType, so it's the fast path when the output is a hand written protobuf anyway.
+    fn get_typed_events(blk: &Block, addresses: &[&Address]) -> Vec<(Self, TxMetaTyped)>
+    where
+        Self: Sized;
+
+    /// Same as `get_events`, but from the addresses in a DynamicAddressSet, only checking the addresses of logs with the event's topic0.
+    fn get_events_from_set(blk: &Block, addresses: &DynamicAddressSet) -> SolidityType;
+}
+
+/// A log that looked like the event being decoded, but couldn't be decoded as it.
+#[derive(Debug, Clone)]
+pub struct DecodeFailure {
+    pub address: Address,
+    pub topic0: B256,
+    pub tx_hash: Vec<u8>,
+    pub error: String,
+}
+
+/// Converts the decoded event, or call, into a struct and inserts its tx meta.
+/// Each field is read by its type in `signature`, ie `Swap(address,int256,bytes32)`, so ints, fixed bytes and fixed arrays
+/// come out the same as `get_events_dyn` gives them. Only what the type doesn't pin down, like a struct parameter, is guessed.
+fn event_to_sol<T: Serialize>(event: T, signature: &str, meta: &TxMeta) -> SolidityType {
+    // the json text keeps the fields in declaration order, which is the order of the types in the signature
+    let json = serde_json::to_string(&event).unwrap();
+    let fields: IndexMap<String, Value> =
+        serde_json::from_str(&json).expect("Event wasn't found to be an Object!?");
+    let types = signature
+        .find('(')
+        .and_then(|start| DynSolType::parse(&signature[start..]).ok())
+        .and_then(|ty| match ty {
+            DynSolType::Tuple(types) if types.len() == fields.len() => Some(types),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    let mut map: IndexMap<String, SolidityType> = fields
+        .into_iter()
+        .enumerate()
+        .map(|(position, (name, value))| {
+            let value = types
+                .get(position)
+                .and_then(|ty| typed_json_to_sol(ty, &value))
+                .or_else(|| SolidityType::guess_json_value(&value))
+                .unwrap_or(SolidityType::Null);
+            (name, value)
+        })
+        .collect();
+    map.insert("tx_meta".to_string(), meta.0.clone());
+    SolidityType::Struct(map)
+}
+
+/// Reads a field of a decoded event or call out of its json as `ty`, see `event_to_sol`.
+/// None for the types left to the guesser, or json that isn't what `ty` serializes to.
+fn typed_json_to_sol(ty: &DynSolType, value: &Value) -> Option<SolidityType> {
+    // small ints serialize as json numbers, the rest as strings
+    let text = || match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    };
+    let value = match (ty, value) {
+        (DynSolType::Bool, Value::Bool(val)) => SolidityType::from(*val),
+        (DynSolType::Int(_), _) => SolidityType::Int(text()?.parse().ok()?),
+        (DynSolType::Uint(_), _) => SolidityType::Uint(text()?.parse().ok()?),
+        (DynSolType::Address, Value::String(hex)) => SolidityType::Address(hex.parse().ok()?),
+        (DynSolType::FixedBytes(32), Value::String(hex)) => {
+            SolidityType::FixedArray(hex.parse().ok()?)
+        }
+        (DynSolType::FixedBytes(size), Value::String(hex)) => {
+            SolidityType::FixedBytes(*size, hex.parse().ok()?)
+        }
+        (DynSolType::Bytes, Value::String(hex)) => SolidityType::ByteArray(hex.parse().ok()?),
+        (DynSolType::String, Value::String(text)) => SolidityType::String(text.clone()),
+        (DynSolType::Array(inner), Value::Array(vals)) => SolidityType::List(
+            vals.iter()
+                .map(|val| typed_json_to_sol(inner, val))
+                .collect::<Option<_>>()?,
+        ),
+        (DynSolType::FixedArray(inner, size), Value::Array(vals)) => SolidityType::SizedList(
+            *size,
+            vals.iter()
+                .map(|val| typed_json_to_sol(inner, val))
+                .collect::<Option<_>>()?,
+        ),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Filters the logs of a block by their address, topic0 and the transaction they came from, see `BlockHelpers::log_query`.
+/// Every filter that's set has to match, so `topic0` and `addresses` together are the logs of that event from those contracts.
+/// Like `alloy_logs`, only the logs of successful transactions are walked.
+///
+/// ```ignore
+/// let pairs: Vec<Address> = ...; // learned from a store at runtime
+/// let swaps = blk.log_query().address_set(pairs).topic0(Swap::SIGNATURE_HASH).collect_alloy();
+/// ```
+#[derive(Debug, Clone)]
+pub struct LogQuery<'a> {
+    blk: &'a Block,
+    addresses: Vec<Address>,
+    topic0: Option<B256>,
+    tx_from: Option<Address>,
+    tx_to: Option<Address>,
+}
+
+impl<'a> LogQuery<'a> {
+    pub fn new(blk: &'a Block) -> Self {
+        LogQuery {
+            blk,
+            addresses: Vec::new(),
+            topic0: None,
+            tx_from: None,
+            tx_to: None,
+        }
+    }
+
+    /// Only the logs emitted by one of `addresses`, an empty slice matches every address.
+    pub fn addresses(mut self, addresses: &[&Address]) -> Self {
+        self.addresses = addresses.iter().map(|address| **address).collect();
+        self
+    }
+
+    /// Same as `addresses`, for anything that can name an address, ie the strings kept in a store.
+    /// Panics if one of them isn't an address, see `try_address_set`.
+    pub fn address_set<T: ToAddress>(self, addresses: impl IntoIterator<Item = T>) -> Self {
+        self.try_address_set(addresses)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as `address_set`, but errors on the first value that isn't an address rather than panicking.
+    /// Nothing is skipped, since leaving out every address would match the logs of every contract.
+    pub fn try_address_set<T: ToAddress>(
+        mut self,
+        addresses: impl IntoIterator<Item = T>,
+    ) -> Result<Self, SolError> {
+        self.addresses = addresses
+            .into_iter()
+            .map(|address| address.try_to_address())
+            .collect::<Result<_, _>>()?;
+        Ok(self)
+    }
+
+    /// Only the logs whose topic0 is `topic0`, which leaves out anonymous events.
+    pub fn topic0(mut self, topic0: B256) -> Self {
+        self.topic0 = Some(topic0);
+        self
+    }
+
+    /// Only the logs of transactions sent by `from`.
+    pub fn tx_from(mut self, from: impl ToAddress) -> Self {
+        self.tx_from = Some(from.to_address());
+        self
+    }
+
+    /// Only the logs of transactions sent to `to`, contract creations have no `to` and never match.
+    pub fn tx_to(mut self, to: impl ToAddress) -> Self {
+        self.tx_to = Some(to.to_address());
+        self
+    }
+
+    /// The matching logs, like `alloy_logs` gives. Panics if the block doesn't carry receipts, see `try_collect_alloy`.
+    pub fn collect_alloy(&self) -> Vec<(Log, TxMeta)> {
+        self.try_collect_alloy()
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as `collect_alloy`, but errors if the block is below the base detail level rather than panicking.
+    /// Every filter runs before the TxMeta is built, since most logs of a block are skipped.
+    pub fn try_collect_alloy(&self) -> Result<Vec<(Log, TxMeta)>, SolError> {
+        require_detail(self.blk, "read the logs of", DetailLevel::Base)?;
+        let addresses: Vec<&Address> = self.addresses.iter().collect();
+        let block_number = self.blk.number.to_string();
+        let logs = matching_logs(self.blk, &addresses, self.topic0.as_ref())
+            .filter(|log| {
+                let txn = log.receipt.transaction;
+                self.tx_from
+                    .map_or(true, |from| txn.from.as_slice() == from.as_slice())
+                    && self
+                        .tx_to
+                        .map_or(true, |to| txn.to.as_slice() == to.as_slice())
+            })
+            .map(|log| (log.into_log(), TxMeta::from_log(&log, &block_number)))
+            .collect();
+        Ok(logs)
+    }
+}
+
+/// The logs from `addresses` whose topic0 is `topic0`, if one is given, without checking the detail level.
+fn matching_logs<'a>(
+    blk: &'a Block,
+    addresses: &'a [&Address],
+    topic0: Option<&'a B256>,
+) -> impl Iterator<Item = LogView<'a>> {
+    blk.logs()
+        .filter(move |log| {
+            topic0.map_or(true, |topic0| {
+                log.topics()
+                    .first()
+                    .map_or(false, |topic| topic.as_slice() == topic0.as_slice())
+            })
+        })
+        .filter(move |log| address_matches(addresses, log.address()))
+}
+
+/// Every log whose topic0 is `topic0` in its raw form, see `raw_log_to_sol`, or Null if there aren't any.
+/// Pairs with `type_casts::keccak` for events only known by their signature string.
+pub fn raw_logs(blk: &Block, topic0: &B256) -> SolidityType {
+    if !may_have_log(blk, &[], Some(topic0)) {
+        return SolidityType::Null;
+    }
+
+    let logs: Vec<SolidityType> = checked_logs(blk, "read the logs of")
+        .filter(|log| {
+            log.topics()
+                .first()
+                .map_or(false, |topic| topic.as_slice() == topic0.as_slice())
+        })
+        .map(|log| raw_log_to_sol(log.log))
+        .collect();
+
+    if logs.is_empty() {
+        SolidityType::Null
+    } else {
+        SolidityType::List(logs)
+    }
+}
+
+/// Checks the log bloom before walking any logs, see `BlockHelpers::may_contain`.
+/// `topic0` is None for anonymous events.
+fn may_have_log(blk: &Block, addresses: &[&Address], topic0: Option<&B256>) -> bool {
+    match &blk.header {
+        Some(header) if addresses.is_empty() => topic0.map_or(true, |topic0| {
+            bloom_contains(&header.logs_bloom, topic0.as_slice())
+        }),
+        _ => {
+            addresses.is_empty()
+                || addresses
+                    .iter()
+                    .any(|address| blk.may_contain(address, topic0))
+        }
+    }
+}
+
+/// Reads a single indexed parameter of type `ty` out of its topic.
+fn topic_to_sol(ty: &DynSolType, topic: B256) -> SolidityType {
+    let value = match ty {
+        DynSolType::Address => DynSolValue::Address(Address::from_word(topic)),
+        DynSolType::Bool => DynSolValue::Bool(!topic.is_zero()),
+        DynSolType::Uint(size) => DynSolValue::Uint(U256::from_be_bytes(topic.0), *size),
+        DynSolType::Int(size) => {
+            DynSolValue::Int(I256::from_raw(U256::from_be_bytes(topic.0)), *size)
+        }
+        DynSolType::FixedBytes(size) => DynSolValue::FixedBytes(topic, *size),
+        _ => DynSolValue::FixedBytes(topic, 32),
+    };
+    value.into()
+}
+
+fn decode_events<T: SolEvent + Serialize>(logs: Vec<(Log, TxMeta)>) -> SolidityType {
+    let validate = false;
+    let events: Vec<SolidityType> = logs
+        .iter()
+        .filter_map(|(l, meta)| {
+            let event = T::decode_log_object(l, validate).ok();
+            if let Some(event) = event {
+                Some((event, meta))
+            } else {
+                None
+            }
+        })
+        .map(|(event, meta)| event_to_sol(event, T::SIGNATURE, meta))
+        .collect();
+
+    if events.len() == 0 {
+        SolidityType::Null
+    } else {
+        SolidityType::List(events)
+    }
+}
+
+impl<T> EventHelpers for T
+where
+    T: SolEvent + Serialize,
+{
+    fn get_events(blk: &Block, addresses: &[&Address]) -> SolidityType {
+        let topic0 = (!T::ANONYMOUS).then_some(&T::SIGNATURE_HASH);
+        if !may_have_log(blk, addresses, topic0) {
+            return SolidityType::Null;
+        }
+        decode_events::<T>(blk.alloy_logs_with_topic(addresses, topic0))
+    }
+
+    fn get_events_cached(cache: &BlockEventCache, addresses: &[&Address]) -> SolidityType {
+        let topic0 = (!T::ANONYMOUS).then_some(&T::SIGNATURE_HASH);
+        decode_events::<T>(cache.alloy_logs(addresses, topic0))
+    }
+
+    fn get_events_in(blk: &Block, addresses: &AddressSet) -> SolidityType {
+        decode_events::<T>(blk.alloy_logs_in(addresses))
+    }
+
+    fn get_events_from_set(blk: &Block, addresses: &DynamicAddressSet) -> SolidityType {
+        let topic0 = (!T::ANONYMOUS).then_some(&T::SIGNATURE_HASH);
+        if !may_have_log(blk, &[], topic0) {
+            return SolidityType::Null;
+        }
+
+        let block_number = blk.number.to_string();
+        let logs = checked_logs(blk, "read the logs of")
+            .filter(|log| {
+                topic0.map_or(true, |topic0| {
+                    log.topics()
+                        .first()
+                        .map_or(false, |topic| topic.as_slice() == topic0.as_slice())
+                })
+            })
+            .filter(|log| {
+                log.address().len() == 20 && addresses.contains(&Address::from_slice(log.address()))
+            })
+            .map(|log| (log.into_log(), TxMeta::from_log(&log, &block_number)))
+            .collect();
+        decode_events::<T>(logs)
+    }
+
+    fn get_events_tagged(blk: &Block, addresses: &[&Address]) -> SolidityType {
+        let validate = false;
+        let block_number = blk.number.to_string();
+        let event_name = T::SIGNATURE.split('(').next().unwrap_or(T::SIGNATURE);
+        let events: Vec<SolidityType> = checked_logs(blk, "read the logs of")
+            .filter(|log| address_matches(addresses, log.address()))
+            .filter_map(|log| {
+                let event = T::decode_log_object(&log.into_log(), validate).ok()?;
+                let meta = TxMeta::from_log(&log, &block_number);
+                let mut event_guess = event_to_sol(event, T::SIGNATURE, &meta);
+                event_guess.insert("event_name", SolidityType::String(event_name.to_string()));
+                event_guess.insert("address", SolidityType::String(format_hex(log.address())));
+                Some(event_guess)
+            })
+            .collect();
+
+        if events.is_empty() {
+            SolidityType::Null
+        } else {
+            SolidityType::List(events)
+        }
+    }
+
+    fn get_events_with_errors(
+        blk: &Block,
+        addresses: &[&Address],
+    ) -> (SolidityType, Vec<DecodeFailure>) {
+        let validate = false;
+        let block_number = blk.number.to_string();
+        let mut events = Vec::new();
+        let mut failures = Vec::new();
+
+        let logs = checked_logs(blk, "read the logs of").filter(|log| {
+            address_matches(addresses, log.address())
+                && log.topics().first().map_or(false, |topic0| {
+                    topic0.as_slice() == T::SIGNATURE_HASH.as_slice()
+                })
+        });
+        for log in logs {
+            match T::decode_log_object(&log.into_log(), validate) {
+                Ok(event) => {
+                    let meta = TxMeta::from_log(&log, &block_number);
+                    events.push(event_to_sol(event, T::SIGNATURE, &meta))
+                }
+                Err(err) => failures.push(DecodeFailure {
+                    address: Address::from_slice(log.address()),
+                    topic0: T::SIGNATURE_HASH,
+                    tx_hash: log.receipt.transaction.hash.clone(),
+                    error: err.to_string(),
+                }),
+            }
+        }
+
+        let events = if events.is_empty() {
+            SolidityType::Null
+        } else {
+            SolidityType::List(events)
+        };
+        (events, failures)
+    }
+
+    fn get_event_topics(blk: &Block, addresses: &[&Address]) -> SolidityType {
+        let topic0 = (!T::ANONYMOUS).then_some(&T::SIGNATURE_HASH);
+        if !may_have_log(blk, addresses, topic0) {
+            return SolidityType::Null;
+        }
+
+        // the topic list includes topic0 for everything but anonymous events
+        let types = match DynSolType::parse(&T::TopicList::sol_type_name()) {
+            Ok(DynSolType::Tuple(types)) => types,
+            _ => return SolidityType::Null,
+        };
+        let skip = topic0.is_some() as usize;
+
+        let events: Vec<SolidityType> = blk
+            .alloy_logs_with_topic(addresses, topic0)
+            .into_iter()
+            .filter(|(log, _)| {
+                let topics = log.topics();
+                topics.len() == types.len()
+                    && topic0.map_or(true, |topic0| topics.first() == Some(topic0))
+            })
+            .map(|(log, meta)| {
+                let mut fields: IndexMap<String, SolidityType> = types
+                    .iter()
+                    .zip(log.topics())
+                    .enumerate()
+                    .skip(skip)
+                    .map(|(index, (ty, topic))| {
+                        let index = index + 1 - skip;
+                        (format!("topic{index}"), topic_to_sol(ty, *topic))
+                    })
+                    .collect();
+                fields.insert("tx_meta".to_string(), meta.0);
+                SolidityType::Struct(fields)
+            })
+            .collect();
+
+        if events.is_empty() {
+            SolidityType::Null
+        } else {
+            SolidityType::List(events)
+        }
+    }
+
+    fn get_typed_events(blk: &Block, addresses: &[&Address]) -> Vec<(Self, TxMetaTyped)> {
+        let signature_hash = T::SIGNATURE_HASH;
+        let topic0 = (!T::ANONYMOUS).then_some(&signature_hash);
+        if !may_have_log(blk, addresses, topic0) {
+            return Vec::new();
+        }
+        require_detail(blk, "read the logs of", DetailLevel::Base)
+            .unwrap_or_else(|err| panic!("{err}"));
+
+        let validate = false;
+        matching_logs(blk, addresses, topic0)
+            .filter_map(|log| {
+                let event = T::decode_log_object(&log.into_log(), validate).ok()?;
+                Some((event, TxMetaTyped::from_log(&log, blk.number)))
+            })
+            .collect()
+    }
+}
+
+/// Decodes several event types in a single pass over the logs of a block, dispatching each log on its topic0.
+/// ie `EventSet::new().with::<Transfer>().with::<Approval>().get_events(&blk, &[&token])`, or see `events_from_block!`.
+#[derive(Default)]
+pub struct EventSet {
+    decoders: Vec<EventDecoder>,
+    by_topic0: HashMap<B256, Vec<usize>>,
+}
+
+struct EventDecoder {
+    name: &'static str,
+    anonymous: bool,
+    decode: fn(&Log, &TxMeta) -> Option<SolidityType>,
+}
+
+fn decode_event_sol<T: SolEvent + Serialize>(log: &Log, meta: &TxMeta) -> Option<SolidityType> {
+    let validate = false;
+    let event = T::decode_log_object(log, validate).ok()?;
+    Some(event_to_sol(event, T::SIGNATURE, meta))
+}
+
+impl EventSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the event type `T`, keyed by its name, ie "Transfer".
+    /// Events sharing a topic0, like the ERC20 and ERC721 Transfer, are tried in the order they were added.
+    /// Anonymous events have no topic0, so they're tried on every log none of the other events decoded.
+    pub fn with<T: SolEvent + Serialize>(mut self) -> Self {
+        let index = self.decoders.len();
+        self.decoders.push(EventDecoder {
+            name: T::SIGNATURE.split('(').next().unwrap_or(T::SIGNATURE),
+            anonymous: T::ANONYMOUS,
+            decode: decode_event_sol::<T>,
+        });
+        if !T::ANONYMOUS {
+            self.by_topic0
+                .entry(T::SIGNATURE_HASH)
+                .or_default()
+                .push(index);
+        }
+        self
+    }
+
+    /// A struct keyed by event name, each a list of events shaped like `EventHelpers::get_events`.
+    /// Every list is in block order and each event's tx_meta carries its log "ordinal", so the lists can be interleaved again.
+    /// Event types without any events in the block are left out, the rest are in the order their first event appears.
+    pub fn get_events(&self, blk: &Block, addresses: &[&Address]) -> SolidityType {
+        let block_number = blk.number.to_string();
+        let mut events: IndexMap<String, Vec<SolidityType>> = IndexMap::new();
+
+        for log in checked_logs(blk, "read the logs of")
+            .filter(|log| address_matches(addresses, log.address()))
+        {
+            let named = log
+                .topics()
+                .first()
+                .filter(|topic0| topic0.len() == 32)
+                .and_then(|topic0| self.by_topic0.get(&B256::from_slice(topic0)))
+                .into_iter()
+                .flatten()
+                .map(|index| &self.decoders[*index]);
+            let anonymous = self.decoders.iter().filter(|decoder| decoder.anonymous);
+            let mut candidates = named.chain(anonymous).peekable();
+            if candidates.peek().is_none() {
+                continue;
+            }
+
+            let alloy_log = log.into_log();
+            let meta = TxMeta::from_log(&log, &block_number);
+            if let Some((name, event)) = candidates
+                .find_map(|decoder| Some((decoder.name, (decoder.decode)(&alloy_log, &meta)?)))
+            {
+                events.entry(name.to_string()).or_default().push(event);
+            }
+        }
+
+        SolidityType::Struct(
+            events
+                .into_iter()
+                .map(|(name, events)| (name, SolidityType::List(events)))
+                .collect(),
+        )
+    }
+}
+
+/// Groups a list of structs, ie the output of `get_events`, by the value at `path`, like `["address"]` or `["tx_meta", "to"]`.
+/// Returns a struct mapping the string form of each distinct value to a list of the matching elements, in their original order.
+/// The groups are in the order their first element appears.
+/// Elements where the path is missing are grouped under "null".
+pub fn group_by_key(list: &SolidityType, path: &[&str]) -> SolidityType {
+    group_by(list, path, false)
+}
+
+/// Same as `group_by_key`, but elements where the path is missing are skipped.
+pub fn group_by_key_skip_missing(list: &SolidityType, path: &[&str]) -> SolidityType {
+    group_by(list, path, true)
+}
+
+fn group_by(list: &SolidityType, path: &[&str], skip_missing: bool) -> SolidityType {
+    let items = match list {
+        SolidityType::List(items) | SolidityType::Tuple(items) => items,
+        SolidityType::Null => return SolidityType::Null,
+        _ => panic!("Tried to group a value that isn't a list!"),
+    };
+
+    let mut groups: IndexMap<String, Vec<SolidityType>> = IndexMap::new();
+    for item in items {
+        let key = path.iter().fold(item.clone(), |value, key| value.get(key));
+        if skip_missing && matches!(key, SolidityType::Null) {
+            continue;
+        }
+        groups
+            .entry(key.to_string())
+            .or_default()
+            .push(item.clone());
+    }
+
+    if groups.is_empty() {
+        SolidityType::Null
+    } else {
+        SolidityType::Struct(
+            groups
+                .into_iter()
+                .map(|(key, items)| (key, SolidityType::List(items)))
+                .collect(),
+        )
+    }
+}
+
+pub trait CallHelpers {
+    /// Decodes the calls to `addresses` whose input is this call, internal calls included, skipping the reverted ones.
+    /// Each is a struct of the call's arguments with a "tx_meta" like events get, its "ordinal" being where the call began
+    /// and its "call_index" the index of the call within the transaction. Null if there aren't any.
+    /// The call's return data is decoded into "outputs" like `FunctionHelpers::rpc_call` does.
+    /// Calls whose return data is empty or doesn't decode, ie void functions, have no "outputs", so they can still be stored.
+    /// Panics if the block doesn't carry calls, see `DetailLevel::Extended`.
+    fn get_calls(blk: &Block, addresses: &[&Address]) -> SolidityType;
+
+    /// Same as `get_calls`, but reverted calls are decoded too, and every tx_meta carries whether the call was "reverted".
+    fn get_calls_including_reverted(blk: &Block, addresses: &[&Address]) -> SolidityType;
+}
+
+fn decode_calls<T>(blk: &Block, addresses: &[&Address], include_reverted: bool) -> SolidityType
+where
+    T: SolCall + Serialize,
+    T::Return: Serialize,
+{
+    require_detail(blk, "read the calls of", DetailLevel::Extended)
+        .unwrap_or_else(|err| panic!("{err}"));
+    let validate = false;
+    let block_number = blk.number.to_string();
+    let calls: Vec<SolidityType> = blk
+        .transaction_traces
+        .iter()
+        .flat_map(|txn| txn.calls.iter().map(move |call| (txn, call)))
+        .filter(|(_, call)| include_reverted || !call.state_reverted)
+        .filter(|(_, call)| address_matches(addresses, &call.address))
+        .filter_map(|(txn, call)| {
+            let decoded = T::abi_decode(&call.input, validate).ok()?;
+            let mut meta = TxMeta::from_trace(txn, &block_number);
+            meta.0
+                .insert("call_index", SolidityType::Uint(U256::from(call.index)));
+            meta.0.insert(
+                "ordinal",
+                SolidityType::Uint(U256::from(call.begin_ordinal)),
+            );
+            if include_reverted {
+                meta.0.insert("reverted", call.state_reverted.into());
+            }
+            let mut decoded = event_to_sol(decoded, T::SIGNATURE, &meta);
+            if let Some(outputs) = decode_returns::<T>(&call.return_data) {
+                decoded.insert("outputs", outputs);
+            }
+            Some(decoded)
+        })
+        .collect();
+
+    if calls.is_empty() {
+        SolidityType::Null
+    } else {
+        SolidityType::List(calls)
+    }
+}
+
+impl<T> CallHelpers for T
+where
+    T: SolCall + Serialize,
+    T::Return: Serialize,
+{
+    fn get_calls(blk: &Block, addresses: &[&Address]) -> SolidityType {
+        decode_calls::<T>(blk, addresses, false)
+    }
+
+    fn get_calls_including_reverted(blk: &Block, addresses: &[&Address]) -> SolidityType {
+        decode_calls::<T>(blk, addresses, true)
+    }
+}
+
+pub trait FunctionHelpers {
+    /// Calls the contract at `to` with this call over eth_call, at the block being processed.
+    /// A single return value is unwrapped, ie `token0Call {}.rpc_call(&pool)` is an Address, several are a Tuple.
+    /// Reverts, empty returns and returns that don't decode are None.
+    fn rpc_call(&self, to: &Address) -> Option<SolidityType>;
+}
+
+impl<T> FunctionHelpers for T
+where
+    T: SolCall,
+    T::Return: Serialize,
+{
+    fn rpc_call(&self, to: &Address) -> Option<SolidityType> {
+        let calls = RpcCalls {
+            calls: vec![RpcCall {
+                to_addr: to.to_vec(),
+                data: self.abi_encode(),
+            }],
+        };
+        let responses = eth_call(&calls);
+        decode_return::<T>(responses.responses.first()?)
+    }
+}
+
+/// Issues calls to several contracts as a single eth_call batch, ie a new pool's token0, token1 and fee.
+/// Calls can be of different types, each is decoded like `FunctionHelpers::rpc_call`. See also `rpc_batch!`.
+#[derive(Default)]
+pub struct RpcBatchHelper {
+    calls: Vec<RpcCall>,
+    decoders: Vec<fn(&RpcResponse) -> Option<SolidityType>>,
+}
+
+impl RpcBatchHelper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add<T>(mut self, to: &Address, call: &T) -> Self
+    where
+        T: SolCall,
+        T::Return: Serialize,
+    {
+        self.calls.push(RpcCall {
+            to_addr: to.to_vec(),
+            data: call.abi_encode(),
+        });
+        self.decoders.push(decode_return::<T>);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// A List of the decoded returns in the order the calls were added, with Null for each call that failed.
+    /// An empty batch is Null without making any call.
+    pub fn execute(mut self) -> SolidityType {
+        if self.is_empty() {
+            return SolidityType::Null;
+        }
+
+        let responses = eth_call(&RpcCalls {
+            calls: std::mem::take(&mut self.calls),
+        });
+        self.decode(&responses.responses)
+    }
+
+    fn decode(&self, responses: &[RpcResponse]) -> SolidityType {
+        SolidityType::List(
+            self.decoders
+                .iter()
+                .enumerate()
+                .map(|(index, decode)| {
+                    responses
+                        .get(index)
+                        .and_then(decode)
+                        .unwrap_or(SolidityType::Null)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Decodes the response to a call of `T` through `guess_json_value`, None if the call failed or returned nothing.
+fn decode_return<T>(response: &RpcResponse) -> Option<SolidityType>
+where
+    T: SolCall,
+    T::Return: Serialize,
+{
+    if response.failed {
+        return None;
+    }
+    decode_returns::<T>(&response.raw)
+}
+
+/// Decodes the return data of a call of `T`, a single return value is unwrapped. None if it's empty or doesn't decode.
+fn decode_returns<T>(raw: &[u8]) -> Option<SolidityType>
+where
+    T: SolCall,
+    T::Return: Serialize,
+{
+    if raw.is_empty() {
+        return None;
+    }
+
+    let validate = false;
+    let decoded = T::abi_decode_returns(raw, validate).ok()?;
+    let config = GuessConfig {
+        collapse_single_tuples: true,
+        ..Default::default()
+    };
+    SolidityType::guess_json_value_with(serde_json::to_value(decoded).ok()?, &config)
+}
+
+pub trait AlloyLog {
+    fn into_log(&self) -> Log;
+}
+
+impl AlloyLog for LogView<'_> {
+    fn into_log(&self) -> Log {
+        let topics = self
+            .topics()
+            .iter()
+            .map(|t| FixedBytes::try_from(&t.as_slice()[..]).unwrap())
+            .collect();
+
+        let data = self.data().to_vec().into();
+
+        Log::new(topics, data).expect("Couldn't create a AlloyLog from a LogView")
+    }
+}
+
+impl AlloyLog for PbLog {
+    fn into_log(&self) -> Log {
+        let topics = self
+            .topics
+            .iter()
+            .map(|t| FixedBytes::try_from(&t[..]).unwrap())
+            .collect();
+
+        let data = self.data.to_vec().into();
+
+        Log::new(topics, data).expect("Couldn't create a AlloyLog from a raw Log")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loose_sol;
+    use crate::store_helpers::{GenericStore, MockStore, StoredValue};
+    use alloy_sol_macro::sol;
+    use substreams::store::{StoreGetBigInt, StoreGetProto, StoreGetRaw, StoreGetString};
+    use substreams_ethereum::pb::eth::v2::{BlockHeader, Call, TransactionReceipt};
+
+    fn block_with_logs(logs: Vec<PbLog>) -> Block {
+        let trace = TransactionTrace {
+            from: vec![1; 20],
+            to: vec![2; 20],
+            status: TransactionTraceStatus::Succeeded as i32,
+            receipt: Some(TransactionReceipt {
+                logs,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        Block {
+            number: 42,
+            transaction_traces: vec![trace],
+            ..Default::default()
+        }
+    }
+
+    fn contract_creation_block() -> Block {
+        let log = PbLog {
+            address: vec![2; 20],
+            topics: vec![vec![3; 32]],
+            ..Default::default()
+        };
+        let trace = TransactionTrace {
+            from: vec![1; 20],
+            to: vec![],
+            status: TransactionTraceStatus::Succeeded as i32,
+            receipt: Some(TransactionReceipt {
+                logs: vec![log],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        Block {
+            number: 42,
+            transaction_traces: vec![trace],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_contract_creation_tx_meta() {
+        let block = contract_creation_block();
+        let logs = block.alloy_logs(&[]);
+        assert_eq!(logs.len(), 1);
+
+        let meta = &logs[0].1 .0;
+        assert_eq!(meta.get("to"), Address::ZERO);
+        assert_eq!(meta.get("from"), Address::from_slice(&[1; 20]));
+    }
+
+    fn header_block(base_fee_per_gas: Option<PbBigInt>) -> Block {
+        Block {
+            number: 17_000_000,
+            hash: vec![1; 32],
+            header: Some(BlockHeader {
+                parent_hash: vec![2; 32],
+                timestamp: Some(prost_types::Timestamp {
+                    seconds: 1_681_338_455,
+                    nanos: 0,
+                }),
+                base_fee_per_gas,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_meta_sol() {
+        let base_fee = PbBigInt {
+            bytes: vec![0x3b, 0x9a, 0xca, 0x00],
+        };
+        let meta = header_block(Some(base_fee)).meta_sol();
+        assert_eq!(meta.get("number"), U256::from(17_000_000));
+        assert_eq!(meta.get("timestamp"), U256::from(1_681_338_455));
+        assert_eq!(meta.get("base_fee_per_gas"), U256::from(1_000_000_000));
+        assert!(matches!(meta.get("hash"), SolidityType::FixedArray(_)));
+        assert!(matches!(
+            meta.get("parent_hash"),
+            SolidityType::FixedArray(_)
+        ));
+
+        let meta = header_block(None).meta_sol();
+        assert!(matches!(meta.get("base_fee_per_gas"), SolidityType::Null));
+        assert_eq!(meta.get("number"), U256::from(17_000_000));
+
+        let meta = Block::default().meta_sol();
+        assert!(matches!(meta.get("timestamp"), SolidityType::Null));
+        assert!(matches!(meta.get("hash"), SolidityType::Null));
+    }
+
+    #[test]
+    fn test_unknown_logs() {
+        let known = B256::from_slice(&[3; 32]);
+        let known_log = PbLog {
+            address: vec![2; 20],
+            topics: vec![known.to_vec()],
+            ..Default::default()
+        };
+        let unknown_log = PbLog {
+            address: vec![2; 20],
+            topics: vec![vec![4; 32]],
+            data: vec![1, 2, 3],
+            ..Default::default()
+        };
+
+        let block = block_with_logs(vec![known_log.clone(), unknown_log]);
+        let unknown = block.unknown_logs(&[known]);
+        assert_eq!(unknown.len(), 1);
+        assert!(matches!(
+            unknown.first().get("topics").first(),
+            SolidityType::FixedArray(topic) if topic == B256::from_slice(&[4; 32])
+        ));
+
+        let block = block_with_logs(vec![known_log]);
+        assert!(matches!(block.unknown_logs(&[known]), SolidityType::Null));
+    }
+
+    #[test]
+    fn test_raw_logs() {
+        let topic0 = crate::type_casts::keccak("Transfer(address,address,uint256)".to_string());
+        let SolidityType::FixedArray(topic0) = topic0 else {
+            panic!("Expected the hash to be a bytes32");
+        };
+        let transfer = PbLog {
+            address: vec![2; 20],
+            topics: vec![topic0.to_vec(), vec![5; 32]],
+            data: vec![1, 2, 3],
+            ..Default::default()
+        };
+        let other = PbLog {
+            address: vec![2; 20],
+            topics: vec![vec![4; 32]],
+            ..Default::default()
+        };
+
+        let block = block_with_logs(vec![other.clone(), transfer]);
+        let logs = raw_logs(&block, &topic0);
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs.first().get("topics").len(), 2);
+        assert!(matches!(
+            logs.first().get("topics").last(),
+            SolidityType::FixedArray(topic) if topic == B256::from_slice(&[5; 32])
+        ));
+        assert!(matches!(
+            logs.first().get("data"),
+            SolidityType::ByteArray(data) if data[..] == [1, 2, 3]
+        ));
+
+        let block = block_with_logs(vec![other]);
+        assert!(matches!(raw_logs(&block, &topic0), SolidityType::Null));
+    }
+
+    fn reverted_call_block() -> Block {
+        let log = |topic: u8| PbLog {
+            address: vec![2; 20],
+            topics: vec![vec![topic; 32]],
+            ..Default::default()
+        };
+        let root = Call {
+            index: 1,
+            logs: vec![log(3)],
+            ..Default::default()
+        };
+        let reverted = Call {
+            index: 2,
+            parent_index: 1,
+            state_reverted: true,
+            logs: vec![log(4)],
+            ..Default::default()
+        };
+        let trace = TransactionTrace {
+            from: vec![1; 20],
+            to: vec![2; 20],
+            status: TransactionTraceStatus::Succeeded as i32,
+            calls: vec![root, reverted],
+            ..Default::default()
+        };
+
+        Block {
+            number: 42,
+            transaction_traces: vec![trace],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_alloy_logs_all_calls() {
+        let block = reverted_call_block();
+
+        let logs = block.alloy_logs_all_calls(&[], false);
+        assert_eq!(logs.len(), 1);
+        assert!(!logs[0].2);
+        assert_eq!(logs[0].0.topics()[0], B256::from_slice(&[3; 32]));
+
+        let logs = block.alloy_logs_all_calls(&[], true);
+        assert_eq!(logs.len(), 2);
+        assert!(logs[1].2);
+        assert_eq!(logs[1].0.topics()[0], B256::from_slice(&[4; 32]));
+        assert_eq!(logs[1].1 .0.get("from"), Address::from_slice(&[1; 20]));
+        assert_eq!(logs[1].1 .0.get("block_number"), U256::from(42));
+
+        let other = Address::from_slice(&[9; 20]);
+        assert!(block.alloy_logs_all_calls(&[&other], true).is_empty());
+    }
+
+    loose_sol! {
+        event Transfer(address indexed from, address indexed to, uint256 value);
+    }
+
+    loose_sol! {
+        event Approval(address indexed owner, address indexed spender, uint256 value);
+    }
+
+    #[test]
+    fn test_get_events_tagged() {
+        let emitter = vec![5; 20];
+        let transfer = PbLog {
+            address: emitter.clone(),
+            topics: vec![
+                Transfer::SIGNATURE_HASH.to_vec(),
+                Address::from_slice(&[6; 20]).into_word().to_vec(),
+                Address::from_slice(&[7; 20]).into_word().to_vec(),
+            ],
+            data: U256::from(1000).to_be_bytes::<32>().to_vec(),
+            ..Default::default()
+        };
+        let unrelated = PbLog {
+            address: emitter.clone(),
+            topics: vec![vec![3; 32]],
+            ..Default::default()
+        };
+        let block = block_with_logs(vec![transfer, unrelated]);
+
+        let events = Transfer::get_events_tagged(&block, &[]);
+        assert_eq!(events.len(), 1);
+        let event = events.first();
+        assert!(matches!(
+            event.get("event_name"),
+            SolidityType::String(name) if name == "Transfer"
+        ));
+        assert!(matches!(
+            event.get("address"),
+            SolidityType::String(address) if address == format_hex(&emitter)
+        ));
+        // the emitter isn't the transaction target
+        assert_eq!(
+            event.get("tx_meta").get("to"),
+            Address::from_slice(&[2; 20])
+        );
+
+        let other = Address::from_slice(&[9; 20]);
+        assert!(matches!(
+            Transfer::get_events_tagged(&block, &[&other]),
+            SolidityType::Null
+        ));
+    }
+
+    fn value_block() -> Block {
+        let zero_value = TransactionTrace {
+            hash: vec![1; 32],
+            from: vec![1; 20],
+            to: vec![2; 20],
+            gas_used: 21_000,
+            nonce: 7,
+            gas_price: Some(PbBigInt {
+                bytes: vec![0x3b, 0x9a, 0xca, 0x00],
+            }),
+            status: TransactionTraceStatus::Succeeded as i32,
+            ..Default::default()
+        };
+        // 2^64 wei, one past u64::MAX
+        let large_value = TransactionTrace {
+            hash: vec![2; 32],
+            from: vec![3; 20],
+            to: vec![2; 20],
+            value: Some(PbBigInt {
+                bytes: vec![1, 0, 0, 0, 0, 0, 0, 0, 0],
+            }),
+            status: TransactionTraceStatus::Reverted as i32,
+            ..Default::default()
+        };
+
+        Block {
+            number: 42,
+            transaction_traces: vec![zero_value, large_value],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_get_typed_events() {
+        let emitter = Address::from_slice(&[5; 20]);
+        let transfer = PbLog {
+            address: emitter.to_vec(),
+            topics: vec![
+                Transfer::SIGNATURE_HASH.to_vec(),
+                Address::from_slice(&[6; 20]).into_word().to_vec(),
+                Address::from_slice(&[7; 20]).into_word().to_vec(),
+            ],
+            data: U256::from(1000).to_be_bytes::<32>().to_vec(),
+            index: 3,
+            ordinal: 12,
+            ..Default::default()
+        };
+        let unrelated = PbLog {
+            address: emitter.to_vec(),
+            topics: vec![vec![3; 32]],
+            ..Default::default()
+        };
+        let mut block = block_with_logs(vec![unrelated, transfer]);
+        block.transaction_traces[0].hash = vec![8; 32];
+        block.transaction_traces[0].index = 4;
+
+        let events = Transfer::get_typed_events(&block, &[&emitter]);
+        assert_eq!(events.len(), 1);
+        let (event, meta) = &events[0];
+        assert_eq!(event.from, Address::from_slice(&[6; 20]));
+        assert_eq!(event.value, U256::from(1000));
+        assert_eq!(
+            *meta,
+            TxMetaTyped {
+                from: Address::from_slice(&[1; 20]),
+                to: Address::from_slice(&[2; 20]),
+                block_number: 42,
+                tx_hash: B256::from([8; 32]),
+                tx_index: 4,
+                log_index: 3,
+                ordinal: 12,
+            }
+        );
+
+        assert!(Transfer::get_typed_events(&block, &[&Address::ZERO]).is_empty());
+    }
+
+    #[test]
+    fn test_transactions_sol() {
+        let block = value_block();
+        let transactions = block.transactions_sol(|_| true);
+        assert_eq!(transactions.len(), 2);
+
+        let zero_value = transactions.first();
+        assert_eq!(zero_value.get("value"), U256::ZERO);
+        assert_eq!(zero_value.get("gas_used"), U256::from(21_000));
+        assert_eq!(zero_value.get("gas_price"), U256::from(1_000_000_000));
+        assert_eq!(zero_value.get("block_number"), U256::from(42));
+        assert!(matches!(
+            zero_value.get("status"),
+            SolidityType::String(status) if status == "SUCCEEDED"
+        ));
+
+        let large_value = transactions.last();
+        assert_eq!(
+            large_value.get("value"),
+            U256::from(u64::MAX) + U256::from(1)
+        );
+        assert_eq!(large_value.get("from"), Address::from_slice(&[3; 20]));
+
+        let filtered = block.transactions_sol(|txn| txn.from == vec![3; 20]);
+        assert_eq!(filtered.len(), 1);
+        assert!(matches!(
+            block.transactions_sol(|_| false),
+            SolidityType::Null
+        ));
+    }
+
+    #[test]
+    fn test_transactions() {
+        let block = value_block();
+        let sender = Address::from_slice(&[3; 20]);
+        let receiver = Address::from_slice(&[2; 20]);
+
+        let transactions = block.transactions(&[&receiver]);
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions.first().get("nonce"), U256::from(7));
+        assert_eq!(
+            transactions.first().get("from"),
+            Address::from_slice(&[1; 20])
+        );
+
+        // the large value transfer reverted
+        assert!(matches!(block.transactions(&[&sender]), SolidityType::Null));
+        let failed = block.transactions_including_failed(&[&sender]);
+        assert_eq!(failed.len(), 1);
+        assert_eq!(
+            failed.first().get("value"),
+            U256::from(u64::MAX) + U256::from(1)
+        );
+        assert!(matches!(
+            failed.first().get("status"),
+            SolidityType::String(status) if status == "REVERTED"
+        ));
+
+        assert_eq!(block.transactions_including_failed(&[]).len(), 2);
+        assert!(matches!(
+            block.transactions(&[&Address::ZERO]),
+            SolidityType::Null
+        ));
+    }
+
+    #[test]
+    fn test_tx_meta_with_gas() {
+        let block = value_block();
+        let meta: SolidityType =
+            TxMeta::with_gas(&block.transaction_traces[0], &"42".to_string()).into();
+        assert_eq!(meta.get("gas_used"), U256::from(21_000));
+        assert_eq!(meta.get("gas_price"), U256::from(1_000_000_000));
+        assert_eq!(meta.get("to"), Address::from_slice(&[2; 20]));
+    }
+
+    #[test]
+    fn test_get_events_with_errors() {
+        let transfer = |data: Vec<u8>| PbLog {
+            address: vec![5; 20],
+            topics: vec![
+                Transfer::SIGNATURE_HASH.to_vec(),
+                Address::from_slice(&[6; 20]).into_word().to_vec(),
+                Address::from_slice(&[7; 20]).into_word().to_vec(),
+            ],
+            data,
+            ..Default::default()
+        };
+        let valid = transfer(U256::from(1000).to_be_bytes::<32>().to_vec());
+        let truncated = transfer(vec![0; 16]);
+        let unrelated = PbLog {
+            address: vec![5; 20],
+            topics: vec![vec![3; 32]],
+            ..Default::default()
+        };
+        let block = block_with_logs(vec![valid, truncated, unrelated]);
+
+        let (events, failures) = Transfer::get_events_with_errors(&block, &[]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].address, Address::from_slice(&[5; 20]));
+        assert_eq!(failures[0].topic0, Transfer::SIGNATURE_HASH);
+        assert!(!failures[0].error.is_empty());
+
+        // the plain variant still drops it without a word
+        assert_eq!(Transfer::get_events(&block, &[]).len(), 1);
+    }
+
+    fn synthetic_events() -> SolidityType {
+        let event = |pool: u8, to: Option<u8>, amount: u64| {
+            let tx_meta = match to {
+                Some(to) => map_literal! { "to"; Address::from_slice(&[to; 20]) },
+                None => SolidityType::Null,
+            };
+            map_literal! {
+                "address"; Address::from_slice(&[pool; 20]),
+                "amount"; U256::from(amount),
+                "tx_meta"; tx_meta
+            }
+        };
+
+        SolidityType::List(vec![
+            event(1, Some(9), 1),
+            event(2, Some(9), 2),
+            event(1, Some(8), 3),
+            event(1, None, 4),
+        ])
+    }
+
+    #[test]
+    fn test_group_by_address() {
+        let groups = group_by_key(&synthetic_events(), &["address"]);
+        assert_eq!(groups.len(), 2);
+
+        let pool = format_hex(&[1; 20]);
+        assert_eq!(groups.get(&pool).len(), 3);
+        // keeps the original order within each group
+        assert_eq!(groups.get(&pool).first().get("amount"), U256::from(1));
+        assert_eq!(groups.get(&pool).last().get("amount"), U256::from(4));
+        assert_eq!(groups.get(&format_hex(&[2; 20])).len(), 1);
+    }
+
+    #[test]
+    fn test_group_by_nested_key() {
+        let groups = group_by_key(&synthetic_events(), &["tx_meta", "to"]);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups.get(&format_hex(&[9; 20])).len(), 2);
+        assert_eq!(groups.get("null").len(), 1);
+
+        let groups = group_by_key_skip_missing(&synthetic_events(), &["tx_meta", "to"]);
+        assert_eq!(groups.len(), 2);
+        assert!(matches!(groups.get("null"), SolidityType::Null));
+    }
+
+    #[test]
+    fn test_address_set_aliases() {
+        let proxy = Address::from_slice(&[5; 20]);
+        let implementation = Address::from_slice(&[6; 20]);
+        let log = |address: Address| PbLog {
+            address: address.to_vec(),
+            topics: vec![
+                Transfer::SIGNATURE_HASH.to_vec(),
+                Address::from_slice(&[7; 20]).into_word().to_vec(),
+                Address::from_slice(&[8; 20]).into_word().to_vec(),
+            ],
+            data: U256::from(1000).to_be_bytes::<32>().to_vec(),
+            ..Default::default()
+        };
+        let block = block_with_logs(vec![
+            log(implementation),
+            log(Address::from_slice(&[9; 20])),
+        ]);
+
+        // hex strings and SolidityType addresses work too, and duplicates are ignored
+        let set = AddressSet::new()
+            .add(format_hex(proxy.as_slice()))
+            .add(SolidityType::Address(proxy))
+            .alias(proxy, implementation);
+
+        let logs = block.alloy_logs_in(&set);
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].1 .0.get("address"), implementation);
+        assert_eq!(logs[0].1 .0.get("canonical_address"), proxy);
+
+        let events = Transfer::get_events_in(&block, &set);
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events.first().get("tx_meta").get("canonical_address"),
+            proxy
+        );
+
+        // without the alias the implementation's logs are missed
+        let set = AddressSet::new().add(proxy);
+        assert!(block.alloy_logs_in(&set).is_empty());
+        assert_eq!(block.alloy_logs_in(&AddressSet::new()).len(), 2);
+    }
+
+    /// The log bloom of a real block, holding a single log from 0xef2d6d194084c2de36e0dabfce45d046b37d1106
+    /// with the topic 0x02c69be41d0b7e40352fc85be1cd65eb03d40ef8427a0ca4596b1ead9a00e9fc.
+    fn bloom_block() -> Block {
+        let bloom = "00000000000000000000000000000000\
+                     00000000100000000000000000000000\
+                     00000000000000000000000000000000\
+                     00000000000000000000000000000000\
+                     00000000000000000000000000000000\
+                     00000000000000000000000000000000\
+                     00000002020000000000000000000000\
+                     00000000000000000000000800000000\
+                     10000000000000000000000000000000\
+                     00000000000000000000001000000000\
+                     00000000000000000000000000000000\
+                     00000000000000000000000000000000\
+                     00000000000000000000000000000000\
+                     00000000000000000000000000000000\
+                     00000000000000000000000000000000\
+                     00000000000000000000000000000000";
+
+        Block {
+            header: Some(BlockHeader {
+                logs_bloom: alloy_primitives::hex::decode(bloom).unwrap(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_may_contain() {
+        let block = bloom_block();
+        let address: Address = "0xef2d6d194084c2de36e0dabfce45d046b37d1106"
+            .parse()
+            .unwrap();
+        let topic: B256 = "0x02c69be41d0b7e40352fc85be1cd65eb03d40ef8427a0ca4596b1ead9a00e9fc"
+            .parse()
+            .unwrap();
+
+        assert!(block.may_contain(&address, None));
+        assert!(block.may_contain(&address, Some(&topic)));
+        assert!(!block.may_contain(&Address::from_slice(&[1; 20]), None));
+        assert!(!block.may_contain(&address, Some(&Transfer::SIGNATURE_HASH)));
+
+        // no header, no way to rule anything out
+        assert!(Block::default().may_contain(&Address::from_slice(&[1; 20]), None));
+    }
+
+    #[test]
+    fn test_get_events_skips_on_bloom() {
+        // the transfer log is really there, but the bloom says the block has no transfers
+        let mut block = block_with_logs(vec![PbLog {
+            address: vec![5; 20],
+            topics: vec![
+                Transfer::SIGNATURE_HASH.to_vec(),
+                Address::from_slice(&[6; 20]).into_word().to_vec(),
+                Address::from_slice(&[7; 20]).into_word().to_vec(),
+            ],
+            data: U256::from(1000).to_be_bytes::<32>().to_vec(),
+            ..Default::default()
+        }]);
+        assert_eq!(Transfer::get_events(&block, &[]).len(), 1);
+
+        block.header = bloom_block().header;
+        assert!(matches!(
+            Transfer::get_events(&block, &[]),
+            SolidityType::Null
+        ));
+    }
+
+    #[test]
+    fn test_get_events_dyn() {
+        let transfer = PbLog {
+            address: vec![5; 20],
+            topics: vec![
+                Transfer::SIGNATURE_HASH.to_vec(),
+                Address::from_slice(&[6; 20]).into_word().to_vec(),
+                Address::from_slice(&[7; 20]).into_word().to_vec(),
+            ],
+            data: U256::from(1000).to_be_bytes::<32>().to_vec(),
+            ..Default::default()
+        };
+        let block = block_with_logs(vec![transfer]);
+
+        // a simple event comes out the same as the serde path
+        let event =
+            Event::parse("event Transfer(address indexed from, address indexed to, uint256 value)")
+                .unwrap();
+        assert_eq!(
+            serde_json::to_value(block.get_events_dyn(&event, &[])).unwrap(),
+            serde_json::to_value(Transfer::get_events(&block, &[])).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_get_events_dyn_fidelity() {
+        let event = Event::parse("event Tick(int24 tick, bytes32 id)").unwrap();
+        let mut data = I256::try_from(-5i64).unwrap().to_be_bytes::<32>().to_vec();
+        data.extend_from_slice(&[0; 31]);
+        data.push(1);
+
+        let block = block_with_logs(vec![PbLog {
+            address: vec![5; 20],
+            topics: vec![event.selector().to_vec()],
+            data,
+            ..Default::default()
+        }]);
+
+        let events = block.get_events_dyn(&event, &[]);
+        let tick = events.get("0").get("tick");
+        assert_eq!(tick, I256::try_from(-5i64).unwrap());
+
+        // the guesser would have read this as the uint 1
+        let id = events.get("0").get("id");
+        assert!(matches!(id, SolidityType::FixedArray(val) if val == B256::with_last_byte(1)));
+    }
+
+    loose_sol! {
+        event Ticked(int24 indexed tick, int256 amount, bytes4 selector, bytes32 id, uint256[2] reserves);
+    }
+
+    #[test]
+    fn test_get_events_typed_fields() {
+        let word = |value: I256| value.to_be_bytes::<32>().to_vec();
+        let mut data = word(I256::try_from(-7i64).unwrap());
+        data.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        data.extend_from_slice(&[0; 28]);
+        data.extend_from_slice(B256::with_last_byte(1).as_slice());
+        data.extend(U256::from(1).to_be_bytes::<32>());
+        data.extend(U256::from(2).to_be_bytes::<32>());
+
+        let block = block_with_logs(vec![PbLog {
+            address: vec![5; 20],
+            topics: vec![
+                Ticked::SIGNATURE_HASH.to_vec(),
+                word(I256::try_from(-5i64).unwrap()),
+            ],
+            data,
+            ..Default::default()
+        }]);
+
+        // each field is what get_events_dyn gives, rather than what the guesser makes of its json
+        let event = Ticked::get_events(&block, &[]).get("0");
+        assert_eq!(event.get("tick"), I256::try_from(-5i64).unwrap());
+        assert_eq!(event.get("amount"), I256::try_from(-7i64).unwrap());
+        assert!(
+            matches!(event.get("selector"), SolidityType::FixedBytes(4, ref val) if val[..] == [0xde, 0xad, 0xbe, 0xef])
+        );
+        assert!(
+            matches!(event.get("id"), SolidityType::FixedArray(val) if val == B256::with_last_byte(1))
+        );
+        assert!(
+            matches!(event.get("reserves"), SolidityType::SizedList(2, ref vals) if vals[1] == U256::from(2))
+        );
+        assert!(matches!(event.get("tx_meta"), SolidityType::Struct(_)));
+    }
+
+    #[test]
+    fn test_get_event_topics() {
+        let block = block_with_logs(vec![
+            PbLog {
+                address: vec![5; 20],
+                topics: vec![
+                    Transfer::SIGNATURE_HASH.to_vec(),
+                    Address::from_slice(&[6; 20]).into_word().to_vec(),
+                    Address::from_slice(&[7; 20]).into_word().to_vec(),
+                ],
+                data: U256::from(1000).to_be_bytes::<32>().to_vec(),
+                ..Default::default()
+            },
+            // some other event with the same number of topics
+            PbLog {
+                address: vec![5; 20],
+                topics: vec![vec![3; 32], vec![0; 32], vec![0; 32]],
+                ..Default::default()
+            },
+        ]);
+
+        let topics = Transfer::get_event_topics(&block, &[]);
+        let full = Transfer::get_events(&block, &[]);
+        assert_eq!(topics.len(), 1);
+        assert_eq!(topics.first().get("topic1"), full.first().get("from"));
+        assert_eq!(topics.first().get("topic2"), full.first().get("to"));
+        assert!(matches!(
+            topics.first().get("topic2"),
+            SolidityType::Address(_)
+        ));
+        assert!(matches!(topics.first().get("value"), SolidityType::Null));
+        assert_eq!(
+            serde_json::to_value(topics.first().get("tx_meta")).unwrap(),
+            serde_json::to_value(full.first().get("tx_meta")).unwrap()
+        );
+    }
+
+    fn transfer_log(token: u8, from: u8, to: u8, value: u64, erc721: bool) -> PbLog {
+        let mut topics = vec![
+            erc20::Transfer::SIGNATURE_HASH.to_vec(),
+            Address::from_slice(&[from; 20]).into_word().to_vec(),
+            Address::from_slice(&[to; 20]).into_word().to_vec(),
+        ];
+        let mut data = U256::from(value).to_be_bytes::<32>().to_vec();
+        if erc721 {
+            topics.push(data);
+            data = vec![];
+        }
+
+        PbLog {
+            address: vec![token; 20],
+            topics,
+            data,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_erc20_erc721_transfers() {
+        let block = block_with_logs(vec![
+            transfer_log(5, 1, 2, 100, false),
+            transfer_log(6, 1, 2, 7, true),
+        ]);
+
+        let erc20 = block.erc20_transfers(&[]);
+        assert_eq!(erc20.len(), 1);
+        assert_eq!(erc20.first().get("amount"), U256::from(100));
+        assert_eq!(erc20.first().get("token"), Address::from_slice(&[5; 20]));
+        assert!(matches!(erc20.first().get("token_id"), SolidityType::Null));
+
+        let erc721 = block.erc721_transfers(&[]);
+        assert_eq!(erc721.len(), 1);
+        assert_eq!(erc721.first().get("token_id"), U256::from(7));
+        assert_eq!(erc721.first().get("token"), Address::from_slice(&[6; 20]));
+
+        let other = Address::from_slice(&[9; 20]);
+        assert!(matches!(
+            block.erc20_transfers(&[&other]),
+            SolidityType::Null
+        ));
+    }
+
+    #[test]
+    fn test_erc20_balance_changes() {
+        let block = block_with_logs(vec![
+            transfer_log(5, 1, 2, 100, false),
+            transfer_log(5, 2, 3, 30, false),
+            // nets to nothing for 4
+            transfer_log(5, 4, 4, 50, false),
+            transfer_log(6, 3, 1, 10, false),
+            // erc721 transfers aren't balances
+            transfer_log(5, 1, 2, 1, true),
+        ]);
+
+        let changes = block.erc20_balance_changes(&[]);
+        let holder = |byte: u8| SolidityType::Address(Address::from_slice(&[byte; 20])).to_string();
+        let token = &changes[holder(5).as_str()];
+        let int = |net: i64| I256::try_from(net).unwrap();
+        assert_eq!(token[holder(1).as_str()], int(-100));
+        assert_eq!(token[holder(2).as_str()], int(70));
+        assert_eq!(token[holder(3).as_str()], int(30));
+        assert!(matches!(&token[holder(4).as_str()], SolidityType::Null));
+
+        let token = &changes[holder(6).as_str()];
+        assert_eq!(token[holder(1).as_str()], int(10));
+    }
+
+    /// A block with `count` logs spread over 10 tokens, a third of them erc20 transfers and the rest noise.
+    fn busy_block(count: usize) -> Block {
+        let logs = (0..count)
+            .map(|i| {
+                let token = (i % 10) as u8 + 10;
+                if i % 3 == 0 {
+                    transfer_log(token, 1, 2, i as u64, false)
+                } else {
+                    PbLog {
+                        address: vec![token; 20],
+                        topics: vec![vec![(i % 7) as u8; 32]],
+                        data: vec![0; 64],
+                        ..Default::default()
+                    }
+                }
+            })
+            .collect();
+        block_with_logs(logs)
+    }
+
+    loose_sol! {
+        function token0() external returns (address);
+    }
+
+    loose_sol! {
+        function getReserves() external returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast);
+    }
+
+    loose_sol! {
+        function transferFrom(address from, address to, uint256 amount) external returns (bool);
+    }
+
+    #[test]
+    fn test_get_calls() {
+        let token = Address::from_slice(&[2; 20]);
+        let transfer = |amount: u64| transferFromCall {
+            from: Address::from_slice(&[1; 20]),
+            to: Address::from_slice(&[3; 20]),
+            amount: U256::from(amount),
+        };
+        let call = |index: u32, address: Vec<u8>, amount: u64, state_reverted: bool| Call {
+            index,
+            address,
+            input: transfer(amount).abi_encode(),
+            begin_ordinal: index as u64 * 10,
+            state_reverted,
+            ..Default::default()
+        };
+        let trace = TransactionTrace {
+            from: vec![1; 20],
+            to: vec![2; 20],
+            hash: vec![9; 32],
+            status: TransactionTraceStatus::Succeeded as i32,
+            calls: vec![
+                call(1, vec![2; 20], 100, false),
+                // same selector on another contract
+                call(2, vec![4; 20], 200, false),
+                call(3, vec![2; 20], 300, true),
+                Call {
+                    index: 4,
+                    address: vec![2; 20],
+                    input: vec![0xa9, 0x05, 0x9c, 0xbb],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let block = Block {
+            number: 42,
+            transaction_traces: vec![trace],
+            ..Default::default()
+        };
+
+        let calls = transferFromCall::get_calls(&block, &[&token]);
+        assert_eq!(calls.len(), 1);
+        let first = calls.first();
+        assert_eq!(first.get("amount"), U256::from(100));
+        assert_eq!(first.get("to"), Address::from_slice(&[3; 20]));
+        assert_eq!(first.get("tx_meta").get("call_index"), U256::from(1));
+        assert_eq!(event_ordinal(&first), 10);
+
+        assert!(matches!(first.get("outputs"), SolidityType::Null));
+
+        let calls = transferFromCall::get_calls_including_reverted(&block, &[&token]);
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls.last().get("amount"), U256::from(300));
+        assert!(matches!(
+            calls.last().get("tx_meta").get("reverted"),
+            SolidityType::Boolean(val) if val == U1::from(1)
+        ));
+
+        assert_eq!(transferFromCall::get_calls(&block, &[]).len(), 2);
+        assert!(matches!(
+            transferFromCall::get_calls(&block, &[&Address::ZERO]),
+            SolidityType::Null
+        ));
+    }
+
+    loose_sol! {
+        function createPair(address tokenA, address tokenB) external returns (address pair);
+    }
+
+    #[test]
+    fn test_get_calls_outputs() {
+        let factory = Address::from_slice(&[2; 20]);
+        let pair = Address::from_slice(&[7; 20]);
+        // createPair(0x0101..., 0x0303...) returning 0x0707...
+        let mut input = vec![0xc9, 0xc6, 0x53, 0x96];
+        input.extend([[0; 12].as_slice(), &[1; 20], &[0; 12], &[3; 20]].concat());
+        let call = |return_data: Vec<u8>| Call {
+            index: 1,
+            address: vec![2; 20],
+            input: input.clone(),
+            return_data,
+            ..Default::default()
+        };
+        let trace = TransactionTrace {
+            from: vec![1; 20],
+            to: vec![2; 20],
+            status: TransactionTraceStatus::Succeeded as i32,
+            calls: vec![
+                call([[0; 12].as_slice(), &[7; 20]].concat()),
+                call(vec![]),
+                call(vec![7; 3]),
+            ],
+            ..Default::default()
+        };
+        let block = Block {
+            number: 42,
+            transaction_traces: vec![trace],
+            ..Default::default()
+        };
+
+        let calls = createPairCall::get_calls(&block, &[&factory]);
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls.first().get("tokenA"), Address::from_slice(&[1; 20]));
+        assert_eq!(calls.first().get("tokenB"), Address::from_slice(&[3; 20]));
+        assert_eq!(calls.first().get("outputs"), pair);
+        assert!(matches!(calls.get("1").get("outputs"), SolidityType::Null));
+        assert!(matches!(calls.last().get("outputs"), SolidityType::Null));
+        // calls without outputs leave the key out, so they can still be stored
+        assert!(
+            matches!(calls.get("1"), SolidityType::Struct(map) if !map.contains_key("outputs"))
+        );
+        assert!(calls.to_stored_struct().is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "read the calls of")]
+    fn test_get_calls_needs_extended_blocks() {
+        transferFromCall::get_calls(&block_with_logs(vec![]), &[]);
+    }
+
+    #[test]
+    fn test_decode_return() {
+        let response = |raw: Vec<u8>, failed: bool| RpcResponse { raw, failed };
+        let token = Address::from_slice(&[5; 20]);
+
+        let address = token.into_word().to_vec();
+        assert_eq!(
+            decode_return::<token0Call>(&response(address.clone(), false)).unwrap(),
+            token
+        );
+        assert!(decode_return::<token0Call>(&response(address, true)).is_none());
+        assert!(decode_return::<token0Call>(&response(vec![], false)).is_none());
+        // too short to be an address
+        assert!(decode_return::<token0Call>(&response(vec![1; 4], false)).is_none());
+
+        let raw: Vec<u8> = [1u64, 2, 3]
+            .iter()
+            .flat_map(|value| U256::from(*value).to_be_bytes::<32>())
+            .collect();
+        let reserves = decode_return::<getReservesCall>(&response(raw, false)).unwrap();
+        assert_eq!(reserves.get("reserve1"), U256::from(2));
+        assert_eq!(reserves.get("blockTimestampLast"), U256::from(3));
+    }
+
+    #[test]
+    fn test_rpc_batch_decode() {
+        let pool = Address::from_slice(&[1; 20]);
+        let token = Address::from_slice(&[5; 20]);
+        let batch = RpcBatchHelper::new()
+            .add(&pool, &token0Call {})
+            .add(&pool, &getReservesCall {})
+            .add(&pool, &token0Call {});
+        assert_eq!(batch.len(), 3);
+
+        let responses = [
+            RpcResponse {
+                raw: token.into_word().to_vec(),
+                failed: false,
+            },
+            RpcResponse {
+                raw: vec![],
+                failed: true,
+            },
+        ];
+        let results = batch.decode(&responses);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results.get("0"), token);
+        // a failed call, and a call the host never answered
+        assert!(matches!(results.get("1"), SolidityType::Null));
+        assert!(matches!(results.get("2"), SolidityType::Null));
+
+        assert!(matches!(
+            RpcBatchHelper::new().execute(),
+            SolidityType::Null
+        ));
+    }
+
+    #[test]
+    fn test_events_from_block() {
+        let approval = PbLog {
+            address: vec![5; 20],
+            topics: vec![
+                Approval::SIGNATURE_HASH.to_vec(),
+                Address::from_slice(&[1; 20]).into_word().to_vec(),
+                Address::from_slice(&[3; 20]).into_word().to_vec(),
+            ],
+            data: U256::from(50).to_be_bytes::<32>().to_vec(),
+            ..Default::default()
+        };
+        let logs = vec![
+            transfer_log(5, 1, 2, 100, false),
+            approval,
+            // the erc721 transfer shares the topic0, but doesn't decode as an erc20 one
+            transfer_log(6, 1, 2, 7, true),
+            PbLog {
+                address: vec![5; 20],
+                topics: vec![vec![9; 32]],
+                ..Default::default()
+            },
+            transfer_log(6, 2, 3, 30, false),
+        ];
+        let logs = logs
+            .into_iter()
+            .enumerate()
+            .map(|(ordinal, log)| PbLog {
+                ordinal: ordinal as u64,
+                ..log
+            })
+            .collect();
+        let block = block_with_logs(logs);
+
+        let events = crate::events_from_block!(block, [], Transfer, Approval);
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            serde_json::to_value(events.get("Transfer")).unwrap(),
+            serde_json::to_value(Transfer::get_events(&block, &[])).unwrap()
+        );
+        let approvals = events.get("Approval");
+        assert_eq!(approvals.len(), 1);
+        assert_eq!(approvals.first().get("value"), U256::from(50));
+        // the ordinals interleave the two lists back into block order
+        assert_eq!(
+            events
+                .get("Transfer")
+                .get("0")
+                .get("tx_meta")
+                .get("ordinal"),
+            U256::from(0)
+        );
+        assert_eq!(
+            approvals.first().get("tx_meta").get("ordinal"),
+            U256::from(1)
+        );
+        assert_eq!(
+            events
+                .get("Transfer")
+                .get("1")
+                .get("tx_meta")
+                .get("ordinal"),
+            U256::from(4)
+        );
+
+        let token = Address::from_slice(&[6; 20]);
+        let events = crate::events_from_block!(block, [token], Transfer, Approval);
+        assert_eq!(events.get("Transfer").len(), 1);
+        assert!(matches!(events.get("Approval"), SolidityType::Null));
+    }
+
+    #[test]
+    fn test_alloy_logs_with_topic() {
+        let mut block = busy_block(300);
+        // a log without any topics, and a transfer too short to decode
+        block.transaction_traces[0]
+            .receipt
+            .as_mut()
+            .unwrap()
+            .logs
+            .extend([
+                PbLog {
+                    address: vec![10; 20],
+                    ..Default::default()
+                },
+                PbLog {
+                    data: vec![],
+                    ..transfer_log(10, 1, 2, 0, false)
+                },
+            ]);
+        let topic0 = Transfer::SIGNATURE_HASH;
+
+        // only these logs are handed to the decoder by get_events
+        let candidates = block.alloy_logs_with_topic(&[], Some(&topic0));
+        assert_eq!(block.alloy_logs(&[]).len(), 302);
+        assert_eq!(candidates.len(), 101);
+        assert!(candidates
+            .iter()
+            .all(|(log, _)| log.topics().first() == Some(&topic0)));
+        assert_eq!(Transfer::get_events(&block, &[]).len(), 100);
+
+        let token = Address::from_slice(&[10; 20]);
+        assert_eq!(
+            block.alloy_logs_with_topic(&[&token], Some(&topic0)).len(),
+            11
+        );
+        assert_eq!(block.alloy_logs_with_topic(&[&token], None).len(), 32);
+    }
+
+    #[test]
+    fn test_get_events_from_set() {
+        let block = block_with_logs(vec![
+            transfer_log(5, 1, 2, 100, false),
+            transfer_log(6, 1, 2, 200, false),
+            transfer_log(7, 1, 2, 300, false),
+        ]);
+        let pool = |byte: u8| Address::from_slice(&[byte; 20]);
+
+        // the factory module stored the pools it saw created, keyed like `generic_set` writes an address
+        let store = MockStore::default();
+        for byte in [5, 7] {
+            let key = SolidityType::Address(pool(byte)).to_string();
+            store.generic_set(format!("pool:{key}"), SolidityType::Address(pool(byte)));
+        }
+        let pools = DynamicAddressSet::from_store(&store, "pool:");
+        assert!(pools.contains(&pool(5)));
+        assert!(!pools.contains(&pool(6)));
+
+        let transfers = Transfer::get_events_from_set(&block, &pools);
+        assert_eq!(transfers.len(), 2);
+        assert_eq!(transfers.get("0").get("value"), U256::from(100));
+        assert_eq!(transfers.get("1").get("value"), U256::from(300));
+
+        // or the pools created in this block, from the store's deltas
+        let delta = |operation, byte: u8| DeltaProto {
+            operation,
+            ordinal: 0,
+            key: format!("pool:{}", SolidityType::Address(pool(byte)).to_string()),
+            old_value: Struct::default(),
+            new_value: Struct::default(),
+        };
+        let deltas = Deltas {
+            deltas: vec![
+                delta(Operation::Create, 6),
+                delta(Operation::Delete, 7),
+                DeltaProto {
+                    key: "token:0x05".to_string(),
+                    ..delta(Operation::Create, 5)
+                },
+            ],
+        };
+        let created = DynamicAddressSet::from_deltas(&deltas, "pool:");
+        let transfers = Transfer::get_events_from_set(&block, &created);
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers.get("0").get("value"), U256::from(200));
+
+        let empty = MockStore::default();
+        assert!(matches!(
+            Transfer::get_events_from_set(&block, &DynamicAddressSet::from_store(&empty, "pool:")),
+            SolidityType::Null
+        ));
+    }
+
+    /// Only answers existence checks, the way StoreGetProto answers `generic_has` with `has_last`.
+    struct HasOnlyStore(HashSet<String>);
+
+    impl GenericStoreGet<SolidityType> for HasOnlyStore {
+        fn generic_get(&self, _key: SolidityType) -> SolidityType {
+            panic!("existence checks shouldn't decode the value")
+        }
+
+        fn generic_has(&self, key: SolidityType) -> bool {
+            self.0.contains(&key.to_string())
+        }
+    }
+
+    #[test]
+    fn test_address_set_from_store_uses_generic_has() {
+        // the real stores are only reachable through the substreams runtime, so this just pins the impls
+        fn assert_address_store<S: GenericStoreGet<SolidityType>>() {}
+        assert_address_store::<StoreGetProto<Struct>>();
+        assert_address_store::<StoreGetString>();
+        assert_address_store::<StoreGetRaw>();
+        assert_address_store::<StoreGetBigInt>();
+
+        let pool = Address::from_slice(&[5; 20]);
+        let store = HasOnlyStore(HashSet::from([format!(
+            "pool:{}",
+            SolidityType::Address(pool).to_string()
+        )]));
+        let pools = DynamicAddressSet::from_store(&store, "pool:");
+        assert!(pools.contains(&pool));
+        assert!(!pools.contains(&Address::from_slice(&[6; 20])));
+    }
+
+    #[test]
+    fn test_log_query() {
+        let mut block = block_with_logs(vec![
+            transfer_log(5, 1, 2, 100, false),
+            transfer_log(6, 1, 2, 200, false),
+            PbLog {
+                address: vec![5; 20],
+                topics: vec![vec![9; 32]],
+                ..Default::default()
+            },
+        ]);
+        let mut other = block.transaction_traces[0].clone();
+        other.from = vec![3; 20];
+        other.to = vec![4; 20];
+        block.transaction_traces.push(other);
+
+        let token = Address::from_slice(&[5; 20]);
+        let topic0 = Transfer::SIGNATURE_HASH;
+        assert_eq!(block.log_query().collect_alloy().len(), 6);
+        assert_eq!(
+            block.log_query().addresses(&[&token]).collect_alloy().len(),
+            4
+        );
+
+        // every filter has to match
+        let logs = block
+            .log_query()
+            .addresses(&[&token])
+            .topic0(topic0)
+            .tx_from(Address::from_slice(&[3; 20]))
+            .collect_alloy();
+        assert_eq!(logs.len(), 1);
+        let (log, meta) = &logs[0];
+        assert_eq!(log.topics().first(), Some(&topic0));
+        assert_eq!(meta.0.get("from"), Address::from_slice(&[3; 20]));
+
+        // addresses learned at runtime, ie strings read from a store
+        let pairs = vec![SolidityType::Address(token).to_string()];
+        let logs = block
+            .log_query()
+            .address_set(pairs)
+            .tx_to(Address::from_slice(&[2; 20]))
+            .collect_alloy();
+        assert_eq!(logs.len(), 2);
+
+        let err = block
+            .log_query()
+            .try_address_set(["0xnot an address"])
+            .unwrap_err();
+        assert!(matches!(err, SolError::ParseFailure { .. }), "{err}");
+        assert!(block
+            .log_query()
+            .try_address_set([SolidityType::Uint(U256::from(1))])
+            .is_err());
+
+        // the wrappers give the same logs
+        assert_eq!(
+            block.alloy_logs_with_topic(&[&token], Some(&topic0)).len(),
+            block
+                .log_query()
+                .addresses(&[&token])
+                .topic0(topic0)
+                .collect_alloy()
+                .len()
+        );
+        assert!(block
+            .log_query()
+            .tx_to(Address::from_slice(&[1; 20]))
+            .collect_alloy()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_get_events_cached() {
+        let mut block = busy_block(300);
+        // an anonymous looking log without any topics, and a log from a malformed address
+        block.transaction_traces[0]
+            .receipt
+            .as_mut()
+            .unwrap()
+            .logs
+            .extend([
+                PbLog {
+                    address: vec![10; 20],
+                    ..Default::default()
+                },
+                PbLog {
+                    address: vec![10; 3],
+                    topics: vec![vec![3; 32]],
+                    ..Default::default()
+                },
+            ]);
+        let cache = BlockEventCache::new(&block);
+        assert_eq!(cache.len(), 302);
+
+        let token = |byte: u8| Address::from_slice(&[byte; 20]);
+        let (a, b, missing) = (token(11), token(14), token(99));
+        let filters: Vec<Vec<&Address>> =
+            vec![vec![], vec![&a], vec![&b, &a], vec![&a, &a], vec![&missing]];
+        for addresses in filters {
+            assert_eq!(
+                serde_json::to_value(Transfer::get_events_cached(&cache, &addresses)).unwrap(),
+                serde_json::to_value(Transfer::get_events(&block, &addresses)).unwrap(),
+                "{addresses:?}"
+            );
+        }
+
+        let no_topic0 = cache.alloy_logs(&[], None);
+        assert_eq!(no_topic0.len(), block.alloy_logs(&[]).len());
+
+        let empty = Block::default();
+        let cache = BlockEventCache::new(&empty);
+        assert!(cache.is_empty());
+        assert!(matches!(
+            Transfer::get_events_cached(&cache, &[]),
+            SolidityType::Null
+        ));
+    }
+
+    /// Timing for decoding one event type out of a busy block, run with `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn bench_get_events_cached() {
+        let block = busy_block(5_000);
+        let token = Address::from_slice(&[11; 20]);
+        let runs = 50;
+
+        let start = std::time::Instant::now();
+        for _ in 0..runs {
+            Transfer::get_events(&block, &[&token]);
+        }
+        let uncached = start.elapsed() / runs;
+
+        let start = std::time::Instant::now();
+        let cache = BlockEventCache::new(&block);
+        let build = start.elapsed();
+
+        let start = std::time::Instant::now();
+        for _ in 0..runs {
+            Transfer::get_events_cached(&cache, &[&token]);
+        }
+        let cached = start.elapsed() / runs;
+
+        // building the cache once has to pay off over decoding the same block for every run
+        assert!(
+            build + cached * runs < uncached * runs,
+            "get_events {uncached:?}, cache build {build:?}, get_events_cached {cached:?} per event type"
+        );
+    }
+
+    #[test]
+    fn test_detail_level() {
+        let base = block_with_logs(vec![transfer_log(5, 1, 2, 100, false)]);
+        let header_only = Block {
+            header: Some(BlockHeader {
+                transactions_root: vec![1; 32],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let empty = Block {
+            header: Some(BlockHeader {
+                transactions_root: EMPTY_TRIE_ROOT.to_vec(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(reverted_call_block().detail_level(), DetailLevel::Extended);
+        assert_eq!(base.detail_level(), DetailLevel::Base);
+        assert_eq!(value_block().detail_level(), DetailLevel::Transactions);
+        assert_eq!(header_only.detail_level(), DetailLevel::Header);
+        assert_eq!(empty.detail_level(), DetailLevel::Extended);
+        assert_eq!(Block::default().detail_level(), DetailLevel::Extended);
+
+        assert_eq!(
+            base.try_alloy_logs_all_calls(&[], false).unwrap_err(),
+            SolError::UnsupportedDetailLevel {
+                operation: "read the calls of",
+                required: DetailLevel::Extended,
+                found: DetailLevel::Base,
+            }
+        );
+        assert!(value_block().try_alloy_logs(&[]).is_err());
+        assert!(value_block().try_transactions_sol(|_| true).is_ok());
+        assert!(header_only.try_transactions_sol(|_| true).is_err());
+        assert!(empty.try_alloy_logs(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tx_meta_ids() {
+        // the same transfer twice in one transaction
+        let logs = (0..2)
+            .map(|index| PbLog {
+                index,
+                ordinal: 10 + index as u64,
+                ..transfer_log(5, 1, 2, 100, false)
+            })
+            .collect();
+        let mut block = block_with_logs(logs);
+        block.transaction_traces[0].hash = vec![7; 32];
+        block.transaction_traces[0].index = 3;
+
+        let events = Transfer::get_events(&block, &[]);
+        let ids: Vec<String> = (0..2u64)
+            .map(|index| {
+                let meta = events.get(&index.to_string()).get("tx_meta");
+                assert!(
+                    matches!(meta.get("tx_hash"), SolidityType::FixedArray(hash) if hash == B256::from_slice(&[7; 32]))
+                );
+                assert_eq!(meta.get("tx_index"), U256::from(3));
+                assert_eq!(meta.get("ordinal"), U256::from(10 + index));
+                format!(
+                    "{}:{}",
+                    meta.get("tx_hash").to_string(),
+                    meta.get("log_index").to_string()
+                )
+            })
+            .collect();
+        assert_ne!(ids[0], ids[1]);
+
+        // the other log paths carry the same ids
+        let cache = BlockEventCache::new(&block);
+        let cached = cache.alloy_logs(&[], None);
+        assert_eq!(cached[1].1 .0.get("log_index"), U256::from(1));
+        assert_eq!(cached[1].1 .0.get("ordinal"), U256::from(11));
+
+        let meta = TxMeta::new(
+            &format!("0x{}", "01".repeat(20)),
+            &format!("0x{}", "02".repeat(20)),
+            &"42".to_string(),
+            &format!("0x{}", "07".repeat(32)),
+            3,
+            1,
+            11,
+        );
+        assert_eq!(
+            serde_json::to_value(SolidityType::from(meta)).unwrap(),
+            serde_json::to_value(&cached[1].1 .0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_base_block_partial_tx_meta() {
+        // a base block without the sender still decodes its events, with whatever metadata is there
+        let mut block = block_with_logs(vec![transfer_log(5, 1, 2, 100, false)]);
+        block.transaction_traces[0].from = vec![];
+        assert_eq!(block.detail_level(), DetailLevel::Base);
+
+        let events = Transfer::get_events(&block, &[]);
+        assert_eq!(events.len(), 1);
+        let meta = events.first().get("tx_meta");
+        assert!(matches!(meta.get("from"), SolidityType::Null));
+        assert_eq!(meta.get("to"), Address::from_slice(&[2; 20]));
+        assert_eq!(meta.get("block_number"), U256::from(42));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Can't read the logs of a block streamed at the transactions detail level, it needs at least base!"
+    )]
+    fn test_alloy_logs_without_receipts() {
+        value_block().alloy_logs(&[]);
     }
 }
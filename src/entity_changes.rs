@@ -0,0 +1,260 @@
+//! Converts structs into graph-node compatible EntityChanges, for substreams powered subgraphs.
+//! Only available with the `entity_changes` feature.
+
+use crate::{errors::ConvertError, json_values::SolidityType};
+use prost_wkt_types::Struct;
+use substreams::{
+    pb::substreams::store_delta::Operation as DeltaOperation,
+    scalar::BigInt,
+    store::{DeltaProto, Deltas},
+};
+use substreams_entity_change::{
+    pb::entity::{value::Typed, Array, Field, Value},
+    tables::ToValue,
+};
+
+pub use substreams_entity_change::pb::entity::{
+    entity_change::Operation as EntityOperation, EntityChange, EntityChanges,
+};
+
+/// Builds the change of the entity `entity_type` with the id `id`, with a field for each key of the struct `value`.
+/// Uints and ints are BigInts, addresses and bytes are Bytes, lists of scalars are arrays and Null fields are left unset.
+/// Panics if `value` isn't a struct or has a field graph-node can't store, see `try_to_entity_change`.
+pub fn to_entity_change(
+    entity_type: &str,
+    id: &str,
+    value: &SolidityType,
+    op: EntityOperation,
+) -> EntityChange {
+    try_to_entity_change(entity_type, id, value, op).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Same as `to_entity_change`, but returns an error instead of panicking, ie for a nested struct.
+/// Deletes don't carry any fields, so `value` is ignored for them.
+pub fn try_to_entity_change(
+    entity_type: &str,
+    id: &str,
+    value: &SolidityType,
+    op: EntityOperation,
+) -> Result<EntityChange, ConvertError> {
+    let fields = match (op, value) {
+        (EntityOperation::Delete, _) => Vec::new(),
+        (_, SolidityType::Struct(map)) => map
+            .iter()
+            .filter(|(_, value)| !matches!(value, SolidityType::Null))
+            .map(|(name, value)| {
+                Ok(Field {
+                    name: name.clone(),
+                    new_value: Some(to_field_value(name, value)?),
+                    ..Default::default()
+                })
+            })
+            .collect::<Result<_, ConvertError>>()?,
+        (_, other) => {
+            return Err(ConvertError::new(
+                None,
+                format!(
+                    "expected a struct for the entity `{id}`, found a {}",
+                    other.kind()
+                ),
+            ))
+        }
+    };
+
+    Ok(EntityChange {
+        entity: entity_type.to_string(),
+        id: id.to_string(),
+        operation: op as i32,
+        fields,
+        ..Default::default()
+    })
+}
+
+/// Converts the deltas of a proto store into changes of `entity_type`, using the key of each delta as the entity id.
+/// Creates, updates and deletes map to the same operation on the entity, unset deltas are skipped.
+/// Panics on a value graph-node can't store, see `try_deltas_to_entity_changes`.
+pub fn deltas_to_entity_changes(
+    deltas: Deltas<DeltaProto<Struct>>,
+    entity_type: &str,
+) -> EntityChanges {
+    try_deltas_to_entity_changes(deltas, entity_type).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Same as `deltas_to_entity_changes`, but returns the first error instead of panicking.
+pub fn try_deltas_to_entity_changes(
+    deltas: Deltas<DeltaProto<Struct>>,
+    entity_type: &str,
+) -> Result<EntityChanges, ConvertError> {
+    let entity_changes = deltas
+        .deltas
+        .into_iter()
+        .filter_map(|delta| {
+            let op = match delta.operation {
+                DeltaOperation::Create => EntityOperation::Create,
+                DeltaOperation::Update => EntityOperation::Update,
+                DeltaOperation::Delete => EntityOperation::Delete,
+                DeltaOperation::Unset => return None,
+            };
+            let value = SolidityType::from(delta.new_value);
+            Some(try_to_entity_change(entity_type, &delta.key, &value, op))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(EntityChanges { entity_changes })
+}
+
+fn to_field_value(name: &str, value: &SolidityType) -> Result<Value, ConvertError> {
+    match value {
+        SolidityType::Tuple(items)
+        | SolidityType::List(items)
+        | SolidityType::SizedList(_, items) => {
+            let value = items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| to_scalar_value(&format!("{name}.{index}"), item))
+                .collect::<Result<_, _>>()?;
+            Ok(Value {
+                typed: Some(Typed::Array(Array { value })),
+            })
+        }
+        other => to_scalar_value(name, other),
+    }
+}
+
+fn to_scalar_value(name: &str, value: &SolidityType) -> Result<Value, ConvertError> {
+    let value = match value {
+        SolidityType::Boolean(val) => (!val.is_zero()).to_value(),
+        SolidityType::Enum(val) => BigInt::from(val.to::<u64>()).to_value(),
+        SolidityType::Uint(_) | SolidityType::Int(_) => BigInt::try_from(value.clone())
+            .map_err(|err| ConvertError::new(Some(name.to_string()), err.to_string()))?
+            .to_value(),
+        SolidityType::Address(val) => val.to_vec().to_value(),
+        SolidityType::ByteArray(val) | SolidityType::FixedBytes(_, val) => val.to_vec().to_value(),
+        SolidityType::FixedArray(val) => val.to_vec().to_value(),
+        SolidityType::String(val) => val.clone().to_value(),
+        SolidityType::Struct(_) => return Err(ConvertError::new(
+            Some(name.to_string()),
+            "nested structs can't be entity fields, flatten them or store them as their own entity"
+                .to_string(),
+        )),
+        other => {
+            return Err(ConvertError::new(
+                Some(name.to_string()),
+                format!("a {} can't be an entity field", other.kind()),
+            ))
+        }
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aliases::*, map_literal, store_helpers::StoredValue};
+
+    fn field<'a>(change: &'a EntityChange, name: &str) -> &'a Typed {
+        change
+            .fields
+            .iter()
+            .find(|field| field.name == name)
+            .and_then(|field| field.new_value.as_ref()?.typed.as_ref())
+            .unwrap_or_else(|| panic!("Expected the field {name}"))
+    }
+
+    fn pool() -> SolidityType {
+        map_literal!(
+            "token0"; SolidityType::Address(Address::from_slice(&[1; 20])),
+            "fee"; SolidityType::Uint(U256::from(3000)),
+            "tick"; SolidityType::Int(I256::try_from(-1).unwrap()),
+            "name"; SolidityType::String("WETH/USDC".to_string()),
+            "active"; SolidityType::from(true),
+            "ticks"; SolidityType::List(vec![SolidityType::Uint(U256::from(1)), SolidityType::Uint(U256::from(2))]),
+            "owner"; SolidityType::Null
+        )
+    }
+
+    #[test]
+    fn test_to_entity_change() {
+        let change = to_entity_change("Pool", "0xabc", &pool(), EntityOperation::Create);
+        assert_eq!(change.entity, "Pool");
+        assert_eq!(change.id, "0xabc");
+        assert_eq!(change.operation, EntityOperation::Create as i32);
+        // the Null owner is left unset
+        assert_eq!(change.fields.len(), 6);
+
+        assert!(matches!(field(&change, "fee"), Typed::Bigint(fee) if fee == "3000"));
+        assert!(matches!(field(&change, "tick"), Typed::Bigint(tick) if tick == "-1"));
+        assert!(matches!(field(&change, "token0"), Typed::Bytes(_)));
+        assert!(matches!(field(&change, "name"), Typed::String(name) if name == "WETH/USDC"));
+        assert!(matches!(field(&change, "active"), Typed::Bool(true)));
+        assert!(matches!(field(&change, "ticks"), Typed::Array(array) if array.value.len() == 2));
+
+        let delete = to_entity_change(
+            "Pool",
+            "0xabc",
+            &SolidityType::Null,
+            EntityOperation::Delete,
+        );
+        assert!(delete.fields.is_empty());
+    }
+
+    #[test]
+    fn test_entity_change_errors() {
+        let nested = map_literal!("pool"; pool());
+        let err = try_to_entity_change("Swap", "1", &nested, EntityOperation::Create).unwrap_err();
+        assert_eq!(err.field.as_deref(), Some("pool"));
+        assert!(err.to_string().contains("nested structs"), "{err}");
+
+        let nested_list = map_literal!("pools"; SolidityType::List(vec![pool()]));
+        let err =
+            try_to_entity_change("Swap", "1", &nested_list, EntityOperation::Create).unwrap_err();
+        assert_eq!(err.field.as_deref(), Some("pools.0"));
+
+        let uint = SolidityType::Uint(U256::from(1));
+        assert!(try_to_entity_change("Swap", "1", &uint, EntityOperation::Update).is_err());
+    }
+
+    #[test]
+    fn test_deltas_to_entity_changes() {
+        let mut pool = pool();
+        if let SolidityType::Struct(map) = &mut pool {
+            map.shift_remove("owner");
+        }
+        let stored = pool.to_stored_struct().unwrap();
+        let delta = |operation: DeltaOperation, key: &str| DeltaProto::<Struct> {
+            operation,
+            ordinal: 0,
+            key: key.to_string(),
+            old_value: Struct::default(),
+            new_value: if operation == DeltaOperation::Delete {
+                Struct::default()
+            } else {
+                stored.clone()
+            },
+        };
+        let deltas = Deltas {
+            deltas: vec![
+                delta(DeltaOperation::Create, "a"),
+                delta(DeltaOperation::Update, "b"),
+                delta(DeltaOperation::Delete, "c"),
+                delta(DeltaOperation::Unset, "d"),
+            ],
+        };
+
+        let changes = deltas_to_entity_changes(deltas, "Pool").entity_changes;
+        let ops: Vec<_> = changes
+            .iter()
+            .map(|change| (change.id.as_str(), change.operation))
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                ("a", EntityOperation::Create as i32),
+                ("b", EntityOperation::Update as i32),
+                ("c", EntityOperation::Delete as i32),
+            ]
+        );
+        assert!(matches!(field(&changes[1], "fee"), Typed::Bigint(fee) if fee == "3000"));
+        assert!(changes[2].fields.is_empty());
+    }
+}
@@ -0,0 +1,102 @@
+//! Errors for the fallible parts of the dynamic api.
+
+use crate::block_helpers::DetailLevel;
+use std::fmt::Display;
+
+/// Returned when a SolidityType can't be converted into a typed value, ie with `SolidityType::to_sol_struct`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvertError {
+    /// The path of the field that failed to convert, ie `tick.owner`, if it could be found.
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl ConvertError {
+    pub fn new(field: Option<String>, message: String) -> Self {
+        ConvertError { field, message }
+    }
+}
+
+impl Display for ConvertError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "Couldn't convert field `{field}`: {}", self.message),
+            None => write!(f, "Couldn't convert value: {}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+/// Returned by the try_ versions of the dynamic api, instead of panicking or quietly returning Null.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolError {
+    /// The operation doesn't apply to this variant, ie getting a key from a uint.
+    WrongVariant {
+        operation: &'static str,
+        found: &'static str,
+    },
+    /// A key or string couldn't be parsed into what the operation needed.
+    ParseFailure {
+        input: String,
+        expected: &'static str,
+    },
+    /// An index past either end of a list or tuple.
+    IndexOutOfRange { index: isize, len: usize },
+    /// The two values can't be compared or combined, ie adding an address to a uint.
+    NotComparable {
+        operation: &'static str,
+        lhs: &'static str,
+        rhs: &'static str,
+    },
+    /// The value couldn't be encoded into what it's being written as, ie a Null into a store.
+    EncodingError { context: String, message: String },
+    /// Dividing a uint by zero.
+    DivisionByZero,
+    /// A uint too large to be an int256 was mixed with an int.
+    Overflow { operation: &'static str },
+    /// The block was streamed without the data the operation needs, see `BlockHelpers::detail_level`.
+    UnsupportedDetailLevel {
+        operation: &'static str,
+        required: DetailLevel,
+        found: DetailLevel,
+    },
+}
+
+impl Display for SolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolError::WrongVariant { operation, found } => {
+                write!(f, "Tried to {operation} a {found} value!")
+            }
+            SolError::ParseFailure { input, expected } => {
+                write!(f, "Couldn't parse `{input}` into {expected}!")
+            }
+            SolError::IndexOutOfRange { index, len } => {
+                write!(f, "Tried to index {index} into a value of length {len}!")
+            }
+            SolError::NotComparable {
+                operation,
+                lhs,
+                rhs,
+            } => write!(f, "Can't {operation} a {lhs} and a {rhs}!"),
+            SolError::EncodingError { context, message } => {
+                write!(f, "Couldn't encode the value for {context}: {message}")
+            }
+            SolError::DivisionByZero => write!(f, "Tried to divide by zero!"),
+            SolError::Overflow { operation } => {
+                write!(f, "Overflowed an int256 trying to {operation}!")
+            }
+            SolError::UnsupportedDetailLevel {
+                operation,
+                required,
+                found,
+            } => write!(
+                f,
+                "Can't {operation} a block streamed at the {found} detail level, it needs at least {required}!"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SolError {}
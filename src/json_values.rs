@@ -1,23 +1,31 @@
 use std::{
     collections::HashMap,
     fmt::Debug,
-    ops::{Add, Div, Mul, Sub},
+    ops::{Add, Div, Index, IndexMut, Mul, Sub},
+    str::FromStr,
 };
 
-use crate::{aliases::*, map_literal, sol_type};
-use alloy_primitives::U8;
+use crate::{
+    aliases::*,
+    errors::{ConvertError, SolError},
+    map_literal, sol_type,
+    store_helpers::decode_stored_value,
+};
+use alloy_dyn_abi::{DynSolType, DynSolValue};
+use alloy_primitives::{I256, U8};
 use alloy_sol_macro::sol;
 use alloy_sol_types::{sol_data::FixedArray, SolEnum};
-use prost_wkt_types::Struct;
-use regex::Regex;
-use serde::{Deserialize, Serialize};
+pub use indexmap::IndexMap;
+use prost_wkt_types::{value::Kind, ListValue, Struct, Value as ProtoValue};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{Map, Value};
 use substreams::{
     pb::substreams::store_delta::Operation,
-    store::{DeltaProto, Deltas},
+    scalar::{BigDecimal, BigInt},
+    store::{DeltaBigInt, DeltaProto, Deltas},
     Hex,
 };
-use substreams_ethereum::pb::eth::v2::Block;
+use substreams_ethereum::pb::eth::v2::{BigInt as PbBigInt, Block};
 
 // A helper macro to impl From<T> for solidity types
 macro_rules! impl_from {
@@ -30,14 +38,21 @@ macro_rules! impl_from {
     };
 }
 
-macro_rules! impl_to {
-    ($input: ty, $variant: ident) => {
-        impl From<SolidityType> for $input {
-            fn from(val: SolidityType) -> Self {
+// A helper macro to impl TryFrom<SolidityType> for the type held by a variant, ie `let addr: Address = value.try_into()?`
+// Any other variant is a SolError::WrongVariant, like the try_ casts.
+macro_rules! impl_try_to {
+    ($output: ty, $variant: ident, $operation: literal) => {
+        impl TryFrom<SolidityType> for $output {
+            type Error = SolError;
+
+            fn try_from(val: SolidityType) -> Result<Self, Self::Error> {
                 if let SolidityType::$variant(value) = val {
-                    value.into()
+                    Ok(value.into())
                 } else {
-                    panic!("Couldn't convert into variant!");
+                    Err(SolError::WrongVariant {
+                        operation: $operation,
+                        found: val.kind(),
+                    })
                 }
             }
         }
@@ -50,17 +65,76 @@ pub enum SolidityType {
     Boolean(U1),
     Enum(U8),
     Uint(U256),
+    /// Any signed int, ie `int24` ticks or `int256` amounts.
+    Int(I256),
     Address(Address),
     ByteArray(Bytes),
     FixedArray(alloy_primitives::B256),
+    /// bytes1 to bytes31, with the size in bytes.
+    FixedBytes(usize, Bytes),
     String(String),
     Tuple(Vec<SolidityType>),
     List(Vec<SolidityType>),
-    Struct(HashMap<String, SolidityType>),
+    /// A fixed length array, ie `uint256[2]`, with its length.
+    SizedList(usize, Vec<SolidityType>),
+    /// Keeps the order keys were inserted in, so the same value always serializes the same way.
+    Struct(IndexMap<String, SolidityType>),
     #[serde(skip)]
     Null,
 }
 
+/// Deserializes `value`, tracking the path of the field that failed, ie `tick.owner` or `ticks[0]`.
+fn deserialize_tracked<T: DeserializeOwned>(value: Value) -> Result<T, ConvertError> {
+    serde_path_to_error::deserialize(value).map_err(|err| {
+        let field = match err.path().to_string() {
+            path if path == "." => None,
+            path => Some(path),
+        };
+        ConvertError::new(field, err.into_inner().to_string())
+    })
+}
+
+/// Checks if a json object key looks like a tuple member key, ie `_0`, `_1`, etc.
+fn is_tuple_key(key: &str) -> bool {
+    match key.strip_prefix('_') {
+        Some(index) => !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()),
+        None => false,
+    }
+}
+
+/// The index of a tuple member key, keys too large for a usize sort last.
+fn tuple_index(key: &str) -> usize {
+    key[1..].parse().unwrap_or(usize::MAX)
+}
+
+/// Promotes an int and an int or uint to a pair of ints for arithmetic, a uint past the int256 max can't be promoted.
+fn signed_pair(
+    lhs: &SolidityType,
+    rhs: &SolidityType,
+    operation: &'static str,
+) -> Result<(I256, I256), SolError> {
+    let promote = |value: &SolidityType| match value {
+        SolidityType::Int(val) => Ok(*val),
+        SolidityType::Uint(val) => {
+            I256::try_from(*val).map_err(|_| SolError::Overflow { operation })
+        }
+        _ => Err(SolError::NotComparable {
+            operation,
+            lhs: lhs.kind(),
+            rhs: rhs.kind(),
+        }),
+    };
+    Ok((promote(lhs)?, promote(rhs)?))
+}
+
+fn cmp_int_uint(int: &I256, uint: &U256) -> std::cmp::Ordering {
+    if int.is_negative() {
+        std::cmp::Ordering::Less
+    } else {
+        int.into_raw().cmp(uint)
+    }
+}
+
 /// A trait that adds map like features to a type.
 pub trait MapLike {
     fn get(&self, key: &str) -> SolidityType;
@@ -69,837 +143,4975 @@ pub trait MapLike {
 
 pub trait GuessValue<T> {
     /// This function takes in a serde json value, and tries to guess the solidity type it represents, if any.
-    /// Note that this can't tell the difference between bytes values and uints because they are represented as hex values all the same.
-    fn guess_json_value(value: T) -> Option<SolidityType>;
+    /// Note that this can't tell the difference between bytes values and uints because they are represented as hex values all the same,
+    /// hex is guessed as a uint unless `GuessConfig::prefer_bytes_over_uint` is set.
+    /// Json nulls become Null, and floats become strings since there's no float type.
+    fn guess_json_value(value: T) -> Option<SolidityType> {
+        Self::guess_json_value_with(value, &GuessConfig::default())
+    }
+
+    /// Same as `guess_json_value`, but with the opt-in heuristics in `config`.
+    fn guess_json_value_with(value: T, config: &GuessConfig) -> Option<SolidityType>;
 }
 
-impl GuessValue<&Value> for SolidityType {
-    fn guess_json_value(value: &Value) -> Option<SolidityType> {
-        match &value {
-            Value::Bool(val) => Some(val.clone().into()),
-            Value::String(val) => {
-                // Address Check
-                if val.starts_with("0x") && val.len() == 42 {
-                    return Some(sol_type!(Address, val));
-                }
+/// Controls the heuristics used when guessing the solidity type of a json value.
+/// Everything but `treat_40_hex_as_address` is off by default, so strings like "1INCH" are never mistaken for numbers.
+#[derive(Debug, Clone)]
+pub struct GuessConfig {
+    /// Treat all digit strings like "12345000000" as uints, and negative ones like "-12" as ints.
+    pub numeric_strings: bool,
+    /// Treat RFC 3339 timestamps like "2023-04-12T22:27:35Z" as uints of their unix seconds.
+    pub rfc3339_timestamps: bool,
+    /// Unwrap `{"_0": value}` into the bare value instead of a one element Tuple.
+    pub collapse_single_tuples: bool,
+    /// Treat hex of an even length as bytes rather than a uint, with 32 bytes as a FixedArray.
+    /// Hex of an odd length, like the "0x1" alloy writes for small uints, is still a uint.
+    pub prefer_bytes_over_uint: bool,
+    /// Treat hex of 40 digits as an address, on by default. Turn it off when there are bytes20 values that aren't addresses.
+    pub treat_40_hex_as_address: bool,
+}
 
-                // Bytes32 / Uint256 Check
-                // NOTE We are going to treat these as uints for now. Might want to change to bytes?
-                if val.starts_with("0x") && val.len() == 66 {
-                    return Some(sol_type!(Uint, val));
-                }
+impl Default for GuessConfig {
+    fn default() -> Self {
+        GuessConfig {
+            numeric_strings: false,
+            rfc3339_timestamps: false,
+            collapse_single_tuples: false,
+            prefer_bytes_over_uint: false,
+            treat_40_hex_as_address: true,
+        }
+    }
+}
 
-                // Bytes check
-                if val.starts_with("0x") && val.len() > 66 {
-                    return Some(sol_type!(ByteArray, val));
-                }
+/// Guesses a 0x prefixed string by its length, see `GuessConfig`.
+/// None if it isn't valid hex, or is too long to be a uint, so it's kept as a String.
+fn guess_hex(val: &str, config: &GuessConfig) -> Option<SolidityType> {
+    let digits = val.strip_prefix("0x")?;
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
 
-                // If the value starts with 0x, but all the other values failed
-                if val.starts_with("0x") {
-                    Some(sol_type!(Uint, val))
-                } else {
-                    // Otherwise we treat it as a String
-                    Some(sol_type!(String, val))
-                }
-            }
+    let even = digits.len() % 2 == 0;
+    match digits.len() {
+        40 if config.treat_40_hex_as_address => val.parse().ok().map(SolidityType::Address),
+        64 if config.prefer_bytes_over_uint => val.parse().ok().map(SolidityType::FixedArray),
+        len if len > 64 && even => val.parse().ok().map(SolidityType::ByteArray),
+        _ if config.prefer_bytes_over_uint && even => val.parse().ok().map(SolidityType::ByteArray),
+        _ => val.parse().ok().map(SolidityType::Uint),
+    }
+}
 
-            Value::Object(val) => {
-                // tuple check
-                let mut keys = val.keys();
-                let key_regex = Regex::new(r"_\d+").unwrap();
-                let keys_match = keys.all(|key| {
-                    let re_match = key_regex.find(key);
-                    if let Some(re_match) = re_match {
-                        re_match.as_str() == key
-                    } else {
-                        false
-                    }
-                });
+/// Guesses a string as anything but a String, see `GuessConfig`. None if it's just a string.
+fn guess_string(val: &str, config: &GuessConfig) -> Option<SolidityType> {
+    // NOTE Anything 0x prefixed that doesn't actually parse, like a symbol that happens to start with 0x,
+    // falls through to being a String rather than panicking.
+    if let Some(value) = guess_hex(val, config) {
+        return Some(value);
+    }
 
-                // if the keys match the pattern of _0, _1, etc, it's a tuple.
-                if keys_match {
-                    let values: Vec<SolidityType> = val
-                        .values()
-                        .map(|value| SolidityType::guess_json_value(value).unwrap()) // TODO Slow, but fine for now
-                        .collect();
-                    if values.len() == 1 {
-                        return Some(values[0].clone().into());
-                    } else {
-                        return Some(SolidityType::Tuple(values).into());
-                    }
-                } else {
-                    // Otherwise if they don't match, it's a struct
-                    let kvs = val
-                        .into_iter()
-                        .map(|(key, value)| {
-                            (
-                                key.to_string(),
-                                SolidityType::guess_json_value(value).unwrap(),
-                            )
-                        })
-                        .collect::<HashMap<String, SolidityType>>();
-                    return Some(SolidityType::Struct(kvs));
-                }
-            }
-            Value::Array(arr) => {
-                // TODO Slow, but fine for now
-                let values: Vec<SolidityType> = arr
-                    .into_iter()
-                    .map(|value| SolidityType::guess_json_value(value).unwrap())
-                    .collect();
+    if config.numeric_strings && !val.is_empty() && val.bytes().all(|b| b.is_ascii_digit()) {
+        if let Ok(val) = val.parse::<U256>() {
+            return Some(SolidityType::Uint(val));
+        }
+    }
 
-                Some(SolidityType::List(values).into())
-            }
-            Value::Null => todo!("Null types shouldn't be returned?"),
-            // NOTE The only time this is returned is a number less than i32
-            Value::Number(num) => {
-                Some(SolidityType::Uint(U256::from(num.as_i64().unwrap())).into())
+    if config.rfc3339_timestamps {
+        if let Ok(timestamp) = prost_types::Timestamp::from_str(val) {
+            if let Ok(seconds) = u64::try_from(timestamp.seconds) {
+                return Some(SolidityType::Uint(U256::from(seconds)));
             }
         }
     }
+
+    // A negative decimal is a signed int, ie an int256 serialized by alloy. It's opt in like positive ones,
+    // so a field's type doesn't depend on its sign.
+    if config.numeric_strings
+        && val.len() > 1
+        && val.starts_with('-')
+        && val[1..].bytes().all(|b| b.is_ascii_digit())
+    {
+        if let Ok(int) = I256::from_dec_str(val) {
+            return Some(SolidityType::Int(int));
+        }
+    }
+
+    None
 }
 
-impl GuessValue<Value> for SolidityType {
-    fn guess_json_value(value: Value) -> Option<SolidityType> {
-        match value {
-            Value::Bool(val) => Some(val.clone().into()),
-            Value::String(val) => {
-                // Address Check
-                if val.starts_with("0x") && val.len() == 42 {
-                    return Some(sol_type!(Address, val));
-                }
+/// Builds the guess of a json object out of its guessed members.
+/// Keys like `_0`, `_1` make a tuple ordered by index, so `_10` comes after `_9`, anything else a struct.
+/// NOTE Null members are dropped since Null can't be serialized, only a top level null is kept.
+fn guess_object(members: Vec<(String, SolidityType)>, config: &GuessConfig) -> SolidityType {
+    let members = members.into_iter();
+    // an empty object has no keys to match so it's an empty struct
+    if members.len() == 0 || !members.as_slice().iter().all(|(key, _)| is_tuple_key(key)) {
+        return SolidityType::Struct(
+            members
+                .filter(|(_, value)| !matches!(value, SolidityType::Null))
+                .collect(),
+        );
+    }
 
-                // Bytes32 / Uint256 Check
-                // NOTE We are going to treat these as uints for now. Might want to change to bytes?
-                if val.starts_with("0x") && val.len() == 66 {
-                    return Some(sol_type!(Uint, val));
-                }
+    let mut members: Vec<(usize, SolidityType)> = members
+        .filter(|(_, value)| !matches!(value, SolidityType::Null))
+        .map(|(key, value)| (tuple_index(&key), value))
+        .collect();
+    members.sort_by_key(|(index, _)| *index);
 
-                // Bytes check
-                if val.starts_with("0x") && val.len() > 66 {
-                    return Some(sol_type!(ByteArray, val));
-                }
+    let mut values: Vec<SolidityType> = members.into_iter().map(|(_, value)| value).collect();
+    if config.collapse_single_tuples && values.len() == 1 {
+        values.pop().unwrap()
+    } else {
+        SolidityType::Tuple(values)
+    }
+}
 
-                // If the value starts with 0x, but all the other values failed
-                if val.starts_with("0x") {
-                    Some(sol_type!(Uint, val))
-                } else {
-                    // Otherwise we treat it as a String
-                    Some(sol_type!(String, val))
-                }
-            }
+fn guess_array(values: Vec<SolidityType>) -> SolidityType {
+    SolidityType::List(
+        values
+            .into_iter()
+            .filter(|value| !matches!(value, SolidityType::Null))
+            .collect(),
+    )
+}
 
-            Value::Object(val) => {
-                // tuple check
-                let mut keys = val.keys();
-                let key_regex = Regex::new(r"_\d+").unwrap();
-                let keys_match = keys.all(|key| {
-                    let re_match = key_regex.find(key);
-                    if let Some(re_match) = re_match {
-                        re_match.as_str() == key
-                    } else {
-                        false
-                    }
-                });
+/// NOTE Numbers up to u64::MAX are uints, negative ones are signed ints like ticks.
+/// There's no float type, so floats are kept as their string instead of being silently truncated.
+fn guess_number(num: &serde_json::Number) -> Option<SolidityType> {
+    match (num.as_u64(), num.as_i64()) {
+        (Some(num), _) => Some(SolidityType::Uint(U256::from(num))),
+        (None, Some(num)) => Some(SolidityType::Int(I256::try_from(num).ok()?)),
+        (None, None) => Some(SolidityType::String(num.to_string())),
+    }
+}
 
-                // if the keys match the pattern of _0, _1, etc, it's a tuple.
-                if keys_match {
-                    let values: Vec<SolidityType> = val
-                        .values()
-                        .map(|value| SolidityType::guess_json_value(value).unwrap()) // TODO Slow, but fine for now
-                        .collect();
-                    if values.len() == 1 {
-                        return Some(values[0].clone().into());
-                    } else {
-                        return Some(SolidityType::Tuple(values).into());
-                    }
-                } else {
-                    // Otherwise if they don't match, it's a struct
-                    let kvs = val
-                        .into_iter()
-                        .map(|(key, value)| {
-                            (
-                                key.to_string(),
-                                SolidityType::guess_json_value(value).unwrap(),
-                            )
-                        })
-                        .collect::<HashMap<String, SolidityType>>();
-                    return Some(SolidityType::Struct(kvs));
-                }
-            }
-            Value::Array(arr) => {
-                // TODO Slow, but fine for now
-                let values: Vec<SolidityType> = arr
-                    .into_iter()
-                    .map(|value| SolidityType::guess_json_value(value).unwrap())
-                    .collect();
+fn guess_value_impl(value: &Value, config: &GuessConfig) -> Option<SolidityType> {
+    match value {
+        Value::Bool(val) => Some((*val).into()),
+        Value::String(val) => {
+            Some(guess_string(val, config).unwrap_or_else(|| SolidityType::String(val.clone())))
+        }
+        Value::Object(val) => {
+            let members = val
+                .iter()
+                .map(|(key, value)| Some((key.clone(), guess_value_impl(value, config)?)))
+                .collect::<Option<_>>()?;
+            Some(guess_object(members, config))
+        }
+        Value::Array(arr) => {
+            let values = arr
+                .iter()
+                .map(|value| guess_value_impl(value, config))
+                .collect::<Option<_>>()?;
+            Some(guess_array(values))
+        }
+        Value::Null => Some(SolidityType::Null),
+        Value::Number(num) => guess_number(num),
+    }
+}
 
-                Some(SolidityType::List(values).into())
-            }
-            Value::Null => todo!("Null types shouldn't be returned?"),
-            // NOTE The only time this is returned is a number less than i32
-            Value::Number(num) => {
-                Some(SolidityType::Uint(U256::from(num.as_i64().unwrap())).into())
-            }
+/// Same as `guess_value_impl`, but moves strings, keys and the members of arrays and objects out of `value`
+/// instead of cloning them.
+fn guess_owned_value_impl(value: Value, config: &GuessConfig) -> Option<SolidityType> {
+    match value {
+        Value::String(val) => Some(guess_string(&val, config).unwrap_or(SolidityType::String(val))),
+        Value::Object(val) => {
+            let members = val
+                .into_iter()
+                .map(|(key, value)| Some((key, guess_owned_value_impl(value, config)?)))
+                .collect::<Option<_>>()?;
+            Some(guess_object(members, config))
+        }
+        Value::Array(arr) => {
+            let values = arr
+                .into_iter()
+                .map(|value| guess_owned_value_impl(value, config))
+                .collect::<Option<_>>()?;
+            Some(guess_array(values))
+        }
+        value => guess_value_impl(&value, config),
+    }
+}
+
+impl GuessValue<&Value> for SolidityType {
+    fn guess_json_value_with(value: &Value, config: &GuessConfig) -> Option<SolidityType> {
+        guess_value_impl(value, config)
+    }
+}
+
+impl GuessValue<Value> for SolidityType {
+    fn guess_json_value_with(value: Value, config: &GuessConfig) -> Option<SolidityType> {
+        guess_owned_value_impl(value, config)
+    }
+}
+
+/// Parses `value` as json first, so RPC responses can be guessed directly.
+/// Anything that isn't valid json, ie a bare `0x...` without quotes, is guessed as a json string.
+impl GuessValue<&str> for SolidityType {
+    fn guess_json_value_with(value: &str, config: &GuessConfig) -> Option<SolidityType> {
+        match serde_json::from_str::<Value>(value) {
+            Ok(value) => guess_value_impl(&value, config),
+            Err(_) => guess_value_impl(&Value::String(value.to_string()), config),
         }
     }
 }
 
+impl GuessValue<String> for SolidityType {
+    fn guess_json_value_with(value: String, config: &GuessConfig) -> Option<SolidityType> {
+        SolidityType::guess_json_value_with(value.as_str(), config)
+    }
+}
+
 impl GuessValue<&Struct> for SolidityType {
-    fn guess_json_value(value: &Struct) -> Option<SolidityType> {
-        let value = serde_json::to_value(value).unwrap();
-        SolidityType::guess_json_value(value)
+    fn guess_json_value_with(value: &Struct, config: &GuessConfig) -> Option<SolidityType> {
+        SolidityType::guess_json_value_with(proto_struct_to_json(value), config)
     }
 }
 
 impl GuessValue<Struct> for SolidityType {
-    fn guess_json_value(value: Struct) -> Option<SolidityType> {
-        let value = serde_json::to_value(value).unwrap();
-        SolidityType::guess_json_value(value)
+    fn guess_json_value_with(value: Struct, config: &GuessConfig) -> Option<SolidityType> {
+        SolidityType::guess_json_value_with(&value, config)
     }
 }
 
 impl MapLike for SolidityType {
     fn get(&self, key: &str) -> SolidityType {
-        self.get(key)
+        SolidityType::get(self, key)
     }
 
     fn insert(&mut self, key: &str, value: SolidityType) {
-        self.insert(key, value)
+        SolidityType::insert(self, key, value)
     }
 }
 
+// NOTE These call the inherent methods by path, so there's no chance of resolving back into MapLike and recursing.
 impl MapLike for &SolidityType {
     fn get(&self, key: &str) -> SolidityType {
-        (*self).get(key)
+        SolidityType::get(self, key)
     }
 
+    /// A shared reference can't be mutated, so this only inserts into a clone. Use IndexMut or the owned impl instead.
     fn insert(&mut self, key: &str, value: SolidityType) {
-        self.clone().insert(key, value)
+        SolidityType::insert(&mut (*self).clone(), key, value)
     }
 }
 
-impl SolidityType {
-    pub fn insert(&mut self, key: &str, value: SolidityType) {
+/// Reads tagged values, like the ones `to_proto_struct` writes, as is and guesses anything else.
+/// Missing keys are Null.
+impl MapLike for Struct {
+    fn get(&self, key: &str) -> SolidityType {
+        self.fields
+            .get(key)
+            .and_then(|value| {
+                proto_tagged(value).or_else(|| SolidityType::guess_json_value(proto_to_json(value)))
+            })
+            .unwrap_or(SolidityType::Null)
+    }
+
+    /// Values are written in the tagged form so they read back as the same variant, Null is a proto null.
+    fn insert(&mut self, key: &str, value: SolidityType) {
+        let value = match value.tagged_struct(false) {
+            Ok(tagged) => proto_kind(Kind::StructValue(tagged)),
+            Err(_) => proto_kind(Kind::NullValue(0)),
+        };
+        self.fields.insert(key.to_string(), value);
+    }
+}
+
+/// Reads the tagged json of the Serialize impl as is and guesses plain json, same as `from_json_lossy`.
+/// Missing keys are Null.
+impl MapLike for Map<String, Value> {
+    fn get(&self, key: &str) -> SolidityType {
+        Map::get(self, key)
+            .cloned()
+            .map_or(SolidityType::Null, SolidityType::from_json_lossy)
+    }
+
+    /// Values are written as plain json, see `to_untagged_value`.
+    fn insert(&mut self, key: &str, value: SolidityType) {
+        Map::insert(self, key.to_string(), value.to_untagged_value());
+    }
+}
+
+/// A value nested in a `to_key_string`, where containers are wrapped in parens.
+fn key_segment(value: &SolidityType) -> String {
+    match value {
+        SolidityType::Tuple(_)
+        | SolidityType::List(_)
+        | SolidityType::SizedList(..)
+        | SolidityType::Struct(_) => format!("({})", value.to_key_string()),
+        _ => value.to_key_string(),
+    }
+}
+
+fn escape_key(raw: &str) -> String {
+    let mut escaped = String::with_capacity(raw.len());
+    for char in raw.chars() {
+        if matches!(char, ':' | '(' | ')' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    }
+    escaped
+}
+
+/// Reads the result of a filter callback, where only a true boolean keeps the value.
+fn is_kept(result: SolidityType) -> Result<bool, SolError> {
+    match result {
+        SolidityType::Boolean(val) => Ok(val.to::<u8>() != 0),
+        SolidityType::Null => Ok(false),
+        other => Err(SolError::WrongVariant {
+            operation: "filter by",
+            found: other.kind(),
+        }),
+    }
+}
+
+/// Splits a `get_path` or `set_path` path, skipping empty segments so a leading slash works too.
+fn path_segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split(['.', '/']).filter(|segment| !segment.is_empty())
+}
+
+/// Returned by Index for any missing key or index.
+static NULL: SolidityType = SolidityType::Null;
+
+/// The position in a list or tuple of length `len` of the index in `key`, where negative indexes count back from the end,
+/// ie "-1" is the last element. Shared by `get` and Index so they agree on every key.
+fn list_position(key: &str, len: usize) -> Result<usize, SolError> {
+    let index: isize = key.parse().map_err(|_| SolError::ParseFailure {
+        input: key.to_string(),
+        expected: "a list index",
+    })?;
+    let position = if index < 0 {
+        len.checked_sub(index.unsigned_abs())
+    } else {
+        Some(index as usize)
+    };
+    position
+        .filter(|position| *position < len)
+        .ok_or(SolError::IndexOutOfRange { index, len })
+}
+
+/// Reads a struct key, or a list or tuple index given as a string, negative ones counting back from the end like `get`.
+/// Anything missing is Null.
+impl Index<&str> for SolidityType {
+    type Output = SolidityType;
+
+    fn index(&self, key: &str) -> &SolidityType {
         match self {
-            SolidityType::Tuple(ref mut val) => {
-                let key = key
-                    .parse()
-                    .expect("Couldn't parse key into number for tuple insert!");
-                val.insert(key, value);
+            SolidityType::Struct(map) => map.get(key).unwrap_or(&NULL),
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => {
+                list_position(key, vals.len()).map_or(&NULL, |position| &vals[position])
             }
-            SolidityType::List(ref mut list) => {
-                let key = key
+            _ => &NULL,
+        }
+    }
+}
+
+impl Index<usize> for SolidityType {
+    type Output = SolidityType;
+
+    fn index(&self, index: usize) -> &SolidityType {
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => vals.get(index).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
+}
+
+/// Like serde_json's Value, missing struct keys are inserted as Null and indexing into Null turns it into a struct,
+/// so nested assignments like `value["pool"]["reserve0"] = reserve` just work.
+/// Out of range list indexes and indexing into scalars panic.
+impl IndexMut<&str> for SolidityType {
+    fn index_mut(&mut self, key: &str) -> &mut SolidityType {
+        if let SolidityType::Null = self {
+            *self = SolidityType::Struct(IndexMap::new());
+        }
+
+        match self {
+            SolidityType::Struct(map) => map.entry(key.to_string()).or_insert(SolidityType::Null),
+            SolidityType::Tuple(_) | SolidityType::List(_) | SolidityType::SizedList(..) => {
+                let index: usize = key
                     .parse()
-                    .expect("Couldn't parse key into number for list insert!");
-                list.insert(key, value);
+                    .expect("Couldn't parse key into number for list index!");
+                &mut self[index]
             }
-            SolidityType::Struct(ref mut map) => {
-                map.insert(key.to_string(), value);
+            _ => panic!("Tried to index into a {} value!", self.kind()),
+        }
+    }
+}
+
+impl IndexMut<usize> for SolidityType {
+    fn index_mut(&mut self, index: usize) -> &mut SolidityType {
+        let kind = self.kind();
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => {
+                let len = vals.len();
+                vals.get_mut(index).unwrap_or_else(|| {
+                    panic!("Tried to index {index} into a {kind} of length {len}!")
+                })
             }
-            _ => {}
-        };
+            _ => panic!("Tried to index into a {kind} value!"),
+        }
     }
+}
 
-    pub fn get(&self, key: &str) -> SolidityType {
-        match &self {
-            SolidityType::Tuple(val) => {
-                if val.len() == 0 {
-                    return SolidityType::Null;
-                }
-                let key: usize = key.parse().expect(&format!(
-                    "{} Couldn't parse key into number for tuple get!",
-                    key
-                ));
-                let value = val.get(key);
-                if let Some(value) = value {
-                    value.clone()
-                } else {
-                    SolidityType::Null
+impl SolidityType {
+    /// Inserts into a struct by key, or into a list or tuple at the index in `key`, shifting everything after it along.
+    /// Indexes past the end pad the list with Nulls up to the index, so the value always lands at the index it was given.
+    /// Non numeric keys on lists and tuples, and inserts into any other variant, do nothing. Use `try_insert` to find out why.
+    pub fn insert(&mut self, key: &str, value: SolidityType) {
+        let _ = self.try_insert(key, value);
+    }
+
+    /// Same as `insert`, but errors instead of silently doing nothing.
+    pub fn try_insert(&mut self, key: &str, value: SolidityType) -> Result<(), SolError> {
+        match self {
+            SolidityType::Tuple(ref mut vals) | SolidityType::List(ref mut vals) => {
+                let index: usize = key.parse().map_err(|_| SolError::ParseFailure {
+                    input: key.to_string(),
+                    expected: "a list index",
+                })?;
+                if index > vals.len() {
+                    vals.resize(index, SolidityType::Null);
                 }
+                vals.insert(index, value);
+                Ok(())
             }
-            SolidityType::List(list) => {
-                if list.len() == 0 {
-                    return SolidityType::Null;
-                }
-                let key: usize = key
-                    .parse()
-                    .expect("Couldn't parse key into number for list get!");
-                let value = list.get(key);
-                if let Some(value) = value {
-                    value.clone()
-                } else {
-                    SolidityType::Null
-                }
+            SolidityType::Struct(ref mut map) => {
+                map.insert(key.to_string(), value);
+                Ok(())
             }
-            SolidityType::Struct(map) => {
-                let value = map.get(key);
-                if let Some(value) = value {
-                    value.clone()
-                } else {
-                    SolidityType::Null
-                }
+            _ => Err(SolError::WrongVariant {
+                operation: "insert",
+                found: self.kind(),
+            }),
+        }
+    }
+
+    /// Gets a struct key, or the index in `key` of a list or tuple, where negative indexes count back from the end,
+    /// ie "-1" is the last element. Missing keys, out of range indexes, non numeric list keys and scalars are all Null.
+    pub fn get(&self, key: &str) -> SolidityType {
+        self.try_get(key).unwrap_or(SolidityType::Null)
+    }
+
+    /// Same as `get`, but non numeric list keys, out of range indexes and getting from a scalar are errors.
+    /// Missing struct keys are still Null.
+    pub fn try_get(&self, key: &str) -> Result<SolidityType, SolError> {
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => {
+                let position = list_position(key, vals.len())?;
+                Ok(vals[position].clone())
             }
-            _ => SolidityType::Null,
+            SolidityType::Struct(map) => Ok(map.get(key).cloned().unwrap_or(SolidityType::Null)),
+            SolidityType::Null => Ok(SolidityType::Null),
+            _ => Err(SolError::WrongVariant {
+                operation: "get",
+                found: self.kind(),
+            }),
         }
     }
 
+    /// Gets a nested value by a dotted or slash separated path, ie "pool.token0.address" or "/swaps/0/amount".
+    /// Struct keys and list or tuple indexes are followed by reference, so only the value at the end is cloned.
+    /// Any missing segment is Null, and negative indexes count back from the end like `get`.
+    pub fn get_path(&self, path: &str) -> SolidityType {
+        path_segments(path)
+            .fold(self, |value, segment| &value[segment])
+            .clone()
+    }
+
+    /// Sets a nested value by a dotted or slash separated path, creating Structs for any missing segment along the way,
+    /// so `value.set_path("pool.token0.address", address)` works on Null.
+    /// Like IndexMut, this panics on out of range list indexes and on paths through scalars.
+    pub fn set_path(&mut self, path: &str, value: SolidityType) {
+        let target = path_segments(path).fold(self, |parent, segment| &mut parent[segment]);
+        *target = value;
+    }
+
+    /// Maps over the values of a list, tuple or struct, returning the same variant it was given.
+    /// Struct values keep their keys, use `map_entries` if the callback needs the key too.
+    /// NOTE Any value the callback maps to Null is dropped, so map doubles as a filter.
+    /// Use `strict_map` if the output should keep the same length as the input.
     pub fn map<F>(&self, callback: F) -> SolidityType
+    where
+        F: Fn(&SolidityType) -> SolidityType,
+    {
+        self.try_map(callback).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as `map`, but errors on scalars rather than panicking.
+    pub fn try_map<F>(&self, callback: F) -> Result<SolidityType, SolError>
     where
         F: Fn(&SolidityType) -> SolidityType,
     {
         match self {
-            SolidityType::Tuple(vals) => {
+            SolidityType::Struct(_) => Ok(self.map_entries(|_, value| callback(value))),
+            _ => self.try_map_indexed(|_, item| callback(item)),
+        }
+    }
+
+    /// Same as `map`, but the callback also gets the index of each value.
+    pub fn map_indexed<F>(&self, callback: F) -> SolidityType
+    where
+        F: Fn(usize, &SolidityType) -> SolidityType,
+    {
+        self.try_map_indexed(callback)
+            .unwrap_or_else(|err| panic!("{err} Use map_entries for structs."))
+    }
+
+    /// Same as `map_indexed`, but errors on structs and scalars rather than panicking.
+    pub fn try_map_indexed<F>(&self, callback: F) -> Result<SolidityType, SolError>
+    where
+        F: Fn(usize, &SolidityType) -> SolidityType,
+    {
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => {
                 let values: Vec<SolidityType> = vals
                     .iter()
-                    .filter_map(|item| {
-                        let value = callback(item);
-                        if let SolidityType::Null = value {
-                            None
-                        } else {
-                            Some(value)
-                        }
-                    })
-                    .map(|item| item.clone())
+                    .enumerate()
+                    .map(|(index, item)| callback(index, item))
+                    .filter(|value| !matches!(value, SolidityType::Null))
                     .collect();
-                if values.len() == 0 {
-                    SolidityType::Null
+                if values.is_empty() {
+                    Ok(SolidityType::Null)
                 } else {
-                    SolidityType::List(values)
+                    Ok(self.with_values(values))
                 }
             }
-            SolidityType::List(list) => {
-                let values: Vec<SolidityType> = list
+            SolidityType::Null => Ok(SolidityType::Null),
+            _ => Err(SolError::WrongVariant {
+                operation: "map over",
+                found: self.kind(),
+            }),
+        }
+    }
+
+    /// Same as `map`, but Null values are kept so the output has the same length, or the same keys, as the input.
+    pub fn strict_map<F>(&self, callback: F) -> SolidityType
+    where
+        F: Fn(&SolidityType) -> SolidityType,
+    {
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => {
+                self.with_values(vals.iter().map(|item| callback(item)).collect())
+            }
+            SolidityType::Struct(map) => SolidityType::Struct(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), callback(value)))
+                    .collect(),
+            ),
+            SolidityType::Null => SolidityType::Null,
+            _ => panic!("Tried to map over a scalar value!"),
+        }
+    }
+
+    /// Maps over the entries of a struct, keeping their keys.
+    /// Like `map`, any entry the callback maps to Null is dropped.
+    pub fn map_entries<F>(&self, callback: F) -> SolidityType
+    where
+        F: Fn(&str, &SolidityType) -> SolidityType,
+    {
+        match self {
+            SolidityType::Struct(map) => {
+                let values: IndexMap<String, SolidityType> = map
                     .iter()
-                    .filter_map(|item| {
-                        let value = callback(item);
-                        if let SolidityType::Null = value {
-                            None
-                        } else {
-                            Some(value)
-                        }
-                    })
-                    .map(|item| item.clone())
+                    .map(|(key, value)| (key.clone(), callback(key, value)))
+                    .filter(|(_, value)| !matches!(value, SolidityType::Null))
                     .collect();
-                if values.len() == 0 {
+                if values.is_empty() {
                     SolidityType::Null
                 } else {
-                    SolidityType::List(values)
+                    SolidityType::Struct(values)
                 }
             }
-            SolidityType::Struct(_) => panic!("Tried to map over a struct!"),
+            SolidityType::Tuple(_) | SolidityType::List(_) | SolidityType::SizedList(..) => {
+                panic!("Tried to map over the entries of a list! Use map_indexed instead.")
+            }
             SolidityType::Null => SolidityType::Null,
             _ => panic!("Tried to map over a scalar value!"),
         }
     }
 
+    /// Filters the values of a list, tuple or struct, returning the same variant it was given.
+    /// The callback must return a boolean or Null, where Null drops the value. Null if nothing is kept.
     pub fn filter<F>(&self, callback: F) -> SolidityType
     where
         F: Fn(&SolidityType) -> SolidityType,
     {
-        match self {
-            SolidityType::Tuple(vals) => {
-                let values: Vec<SolidityType> = vals
-                    .iter()
-                    .filter_map(|item| {
-                        let callback_value = callback(item);
-                        match callback_value {
-                            SolidityType::Boolean(val) => {
-                                let value: u8 = val.to();
-                                if value == 0 {
-                                    None
-                                } else {
-                                    Some(item.clone())
-                                }
-                            }
-                            SolidityType::Null => None,
-                            _ => panic!(
-                                "Tried to filter over a tuple, but found a non boolean or null value!"
-                            ),
-                        }
-                    })
-                    .collect();
+        self.try_filter(callback)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
 
-                if values.len() == 0 {
-                    SolidityType::Null
-                } else {
-                    SolidityType::List(values)
+    /// Same as `filter`, but errors on scalars and callbacks returning anything but a boolean or Null.
+    pub fn try_filter<F>(&self, callback: F) -> Result<SolidityType, SolError>
+    where
+        F: Fn(&SolidityType) -> SolidityType,
+    {
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => {
+                let mut values = Vec::new();
+                for item in vals {
+                    if is_kept(callback(item))? {
+                        values.push(item.clone());
+                    }
                 }
-            }
-
-            SolidityType::List(list) => {
-                let values: Vec<SolidityType> = list
-                    .iter()
-                    .filter_map(|item| {
-                        let callback_value = callback(item);
-                        match callback_value {
-                            SolidityType::Boolean(val) => {
-                                let value: u8 = val.to();
-                                if value == 0 {
-                                    None
-                                } else {
-                                    Some(item.clone())
-                                }
-                            }
-                            SolidityType::Null => None,
-                            _ => panic!(
-                                "Tried to filter over a list, but found a non boolean or null value!"
-                            ),
-                        }
-                    })
-                    .collect();
 
-                if values.len() == 0 {
-                    SolidityType::Null
+                if values.is_empty() {
+                    Ok(SolidityType::Null)
                 } else {
-                    SolidityType::List(values)
+                    Ok(self.with_values(values))
                 }
             }
-            SolidityType::Struct(_) => panic!("Tried to filter over a struct!"),
-            SolidityType::Null => SolidityType::Null,
-            _ => panic!("Tried to filter over a scalar value!"),
+            SolidityType::Struct(_) => self.try_filter_entries(|_, value| callback(value)),
+            SolidityType::Null => Ok(SolidityType::Null),
+            _ => Err(SolError::WrongVariant {
+                operation: "filter over",
+                found: self.kind(),
+            }),
         }
     }
 
-    pub fn to_maybe_value(&self) -> Option<SolidityType> {
-        match self {
-            SolidityType::Tuple(vals) => {
-                let values: Vec<SolidityType> = vals
-                    .iter()
-                    .filter_map(|item| item.to_maybe_value())
-                    .collect();
-                if vals.len() == 0 {
-                    return None;
-                } else {
-                    Some(SolidityType::Tuple(values))
-                }
-            }
-            SolidityType::List(vals) => {
-                let values: Vec<SolidityType> = vals
-                    .iter()
-                    .filter_map(|item| item.to_maybe_value())
-                    .collect();
-                if vals.len() == 0 {
-                    return None;
-                } else {
-                    Some(SolidityType::List(values))
-                }
-            }
+    /// Filters the entries of a struct, where the callback also gets the key of each value. Like `filter`,
+    /// the callback must return a boolean or Null, and it's Null if nothing is kept.
+    pub fn filter_entries<F>(&self, callback: F) -> SolidityType
+    where
+        F: Fn(&str, &SolidityType) -> SolidityType,
+    {
+        self.try_filter_entries(callback)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as `filter_entries`, but errors on anything but a struct or Null rather than panicking.
+    pub fn try_filter_entries<F>(&self, callback: F) -> Result<SolidityType, SolError>
+    where
+        F: Fn(&str, &SolidityType) -> SolidityType,
+    {
+        match self {
             SolidityType::Struct(map) => {
-                let iter = map
-                    .into_iter()
-                    .map(|(k, v)| (k, v.to_maybe_value()))
-                    .filter(|(_k, v)| v.is_some())
-                    .map(|(k, v)| (k.to_string(), v.unwrap()))
-                    .collect::<HashMap<String, SolidityType>>();
-                if iter.is_empty() {
-                    None
+                let mut values = IndexMap::new();
+                for (key, value) in map {
+                    if is_kept(callback(key, value))? {
+                        values.insert(key.clone(), value.clone());
+                    }
+                }
+
+                if values.is_empty() {
+                    Ok(SolidityType::Null)
                 } else {
-                    Some(SolidityType::Struct(iter))
+                    Ok(SolidityType::Struct(values))
                 }
             }
-            SolidityType::Null => None,
-            _ => Some(self.clone()),
+            SolidityType::Null => Ok(SolidityType::Null),
+            _ => Err(SolError::WrongVariant {
+                operation: "filter the entries of",
+                found: self.kind(),
+            }),
         }
     }
-}
 
-impl AsRef<SolidityType> for SolidityType {
-    fn as_ref(&self) -> &SolidityType {
-        &self
+    /// Renders a scalar as a String like `type_casts::string`, but errors on lists, tuples, structs and Null
+    /// rather than quietly returning Null.
+    pub fn try_to_string(&self) -> Result<SolidityType, SolError> {
+        match self {
+            SolidityType::String(_) => Ok(self.clone()),
+            SolidityType::Tuple(_)
+            | SolidityType::List(_)
+            | SolidityType::SizedList(..)
+            | SolidityType::Struct(_)
+            | SolidityType::Null => Err(SolError::WrongVariant {
+                operation: "convert to a string",
+                found: self.kind(),
+            }),
+            _ => Ok(SolidityType::String(self.to_string())),
+        }
     }
-}
 
-impl ToString for SolidityType {
-    fn to_string(&self) -> String {
-        match &self {
-            SolidityType::Boolean(val) => {
-                let value: u8 = val.to();
-                if value == 0 {
-                    "false".to_string()
-                } else {
-                    "true".to_string()
-                }
+    /// Same as `+`, but errors on values that can't be added rather than panicking.
+    /// Null is ignored on either side, so an accumulator can start out as a missing store value,
+    /// and anything added to a String is concatenated.
+    /// Mixing an int with a uint promotes the uint to an int, erroring if it's past the int256 max.
+    pub fn try_add(self, rhs: impl Into<SolidityType>) -> Result<SolidityType, SolError> {
+        let rhs: SolidityType = rhs.into();
+        match (self, rhs) {
+            (SolidityType::Null, rhs) => Ok(rhs),
+            (lhs, SolidityType::Null) => Ok(lhs),
+            // NOTE If we add something to a string, it will concat. I'm not sure if I will keep this or not.
+            (SolidityType::String(mut lhs), rhs) => {
+                lhs.push_str(&rhs.to_string());
+                Ok(SolidityType::String(lhs))
             }
-            SolidityType::Enum(val) => {
-                let value: u8 = val.to();
-                value.to_string()
+            (SolidityType::Uint(lh), SolidityType::Uint(rh)) => Ok(SolidityType::Uint(lh + rh)),
+            (lhs, rhs) if lhs.is_signed_pair(&rhs) => {
+                let (lh, rh) = signed_pair(&lhs, &rhs, "add")?;
+                Ok(SolidityType::Int(lh.wrapping_add(rh)))
             }
-            SolidityType::Uint(val) => val.to_string(),
-            SolidityType::Address(val) => val.to_string(),
-            SolidityType::ByteArray(val) => val.to_string(),
-            SolidityType::FixedArray(val) => val.to_string(),
-            SolidityType::String(val) => val.to_string(),
-            SolidityType::Null => "null".to_string(),
-            SolidityType::Tuple(_) => panic!("Can't convert a tuple to a string!"),
-            SolidityType::List(_) => panic!("Can't convert a list to a string!"),
-            SolidityType::Struct(_) => panic!("Can't convert a struct to a string!"),
+            (lhs, rhs) => Err(SolError::NotComparable {
+                operation: "add",
+                lhs: lhs.kind(),
+                rhs: rhs.kind(),
+            }),
         }
     }
-}
 
-impl From<Struct> for SolidityType {
-    fn from(value: Struct) -> Self {
-        let value = serde_json::to_value(value).unwrap();
-        if let Some(value) = serde_json::from_value(value.clone()).ok() {
-            value
-        } else {
-            SolidityType::guess_json_value(value).unwrap()
+    /// Same as `-`, but errors on anything but numbers rather than panicking.
+    /// Subtracting a Null leaves the value as is, but Null minus anything is Null, since it isn't the value negated.
+    /// Add is the only op treating a Null as zero, Mul and Div are Null if either side is.
+    /// An int with an int or a uint is an int, see `try_add`.
+    pub fn try_sub(self, rhs: impl Into<SolidityType>) -> Result<SolidityType, SolError> {
+        let rhs: SolidityType = rhs.into();
+        match (self, rhs) {
+            (SolidityType::Null, _) => Ok(SolidityType::Null),
+            (lhs, SolidityType::Null) => Ok(lhs),
+            (SolidityType::Uint(lh), SolidityType::Uint(rh)) => Ok(SolidityType::Uint(lh - rh)),
+            (lhs, rhs) if lhs.is_signed_pair(&rhs) => {
+                let (lh, rh) = signed_pair(&lhs, &rhs, "subtract")?;
+                Ok(SolidityType::Int(lh.wrapping_sub(rh)))
+            }
+            (lhs, rhs) => Err(SolError::NotComparable {
+                operation: "subtract",
+                lhs: lhs.kind(),
+                rhs: rhs.kind(),
+            }),
         }
     }
-}
 
-impl From<Deltas<DeltaProto<Struct>>> for SolidityType {
-    fn from(value: Deltas<DeltaProto<Struct>>) -> Self {
-        let deltas = value.deltas;
-        let deltas = deltas.into_iter().map(SolidityType::from).collect();
-        SolidityType::List(deltas)
+    /// Same as `*`, but errors on anything but numbers rather than panicking. A Null on either side is Null.
+    pub fn try_mul(self, rhs: impl Into<SolidityType>) -> Result<SolidityType, SolError> {
+        let rhs: SolidityType = rhs.into();
+        match (self, rhs) {
+            (SolidityType::Null, _) | (_, SolidityType::Null) => Ok(SolidityType::Null),
+            (SolidityType::Uint(lh), SolidityType::Uint(rh)) => Ok(SolidityType::Uint(lh * rh)),
+            (lhs, rhs) if lhs.is_signed_pair(&rhs) => {
+                let (lh, rh) = signed_pair(&lhs, &rhs, "multiply")?;
+                Ok(SolidityType::Int(lh.wrapping_mul(rh)))
+            }
+            (lhs, rhs) => Err(SolError::NotComparable {
+                operation: "multiply",
+                lhs: lhs.kind(),
+                rhs: rhs.kind(),
+            }),
+        }
     }
-}
 
-impl From<DeltaProto<Struct>> for SolidityType {
-    fn from(value: DeltaProto<Struct>) -> Self {
-        let DeltaProto {
-            operation,
-            key,
-            old_value,
-            new_value,
-            ..
-        } = value;
+    /// Same as `/`, but errors on anything but numbers and on division by zero rather than panicking.
+    /// A Null on either side is Null.
+    pub fn try_div(self, rhs: impl Into<SolidityType>) -> Result<SolidityType, SolError> {
+        let rhs: SolidityType = rhs.into();
+        match (self, rhs) {
+            (SolidityType::Null, _) | (_, SolidityType::Null) => Ok(SolidityType::Null),
+            (SolidityType::Uint(_), SolidityType::Uint(rh)) if rh.is_zero() => {
+                Err(SolError::DivisionByZero)
+            }
+            (SolidityType::Uint(lh), SolidityType::Uint(rh)) => Ok(SolidityType::Uint(lh / rh)),
+            (lhs, rhs) if lhs.is_signed_pair(&rhs) => {
+                let (lh, rh) = signed_pair(&lhs, &rhs, "divide")?;
+                if rh.is_zero() {
+                    return Err(SolError::DivisionByZero);
+                }
+                Ok(SolidityType::Int(lh.wrapping_div(rh)))
+            }
+            (lhs, rhs) => Err(SolError::NotComparable {
+                operation: "divide",
+                lhs: lhs.kind(),
+                rhs: rhs.kind(),
+            }),
+        }
+    }
 
-        map_literal!(
-            "operation"; SolidityType::from(operation),
-            "key"; SolidityType::String(key),
-            "old_value"; SolidityType::from(old_value),
-            "new_value"; SolidityType::from(new_value)
+    /// Same as `try_add`, but None if the result overflows rather than wrapping, or if `try_add` would error.
+    pub fn checked_add(self, rhs: impl Into<SolidityType>) -> Option<SolidityType> {
+        self.numeric_op(
+            rhs.into(),
+            "add",
+            |lhs, rhs| lhs.try_add(rhs),
+            U256::checked_add,
+            I256::checked_add,
         )
+        .ok()?
     }
-}
 
-impl From<Operation> for SolidityType {
-    fn from(value: Operation) -> Self {
-        match value {
-            Operation::Unset => SolidityType::String("Unset".to_string()),
-            Operation::Create => SolidityType::String("Create".to_string()),
-            Operation::Update => SolidityType::String("Update".to_string()),
-            Operation::Delete => SolidityType::String("Update".to_string()),
-        }
+    /// Same as `try_sub`, but None if the result underflows, ie a uint balance going below zero.
+    pub fn checked_sub(self, rhs: impl Into<SolidityType>) -> Option<SolidityType> {
+        self.numeric_op(
+            rhs.into(),
+            "subtract",
+            |lhs, rhs| lhs.try_sub(rhs),
+            U256::checked_sub,
+            I256::checked_sub,
+        )
+        .ok()?
     }
-}
 
-// NOTE I might want to change this to try_from
-impl From<Value> for SolidityType {
-    fn from(value: Value) -> Self {
-        serde_json::from_value(value).unwrap()
+    pub fn checked_mul(self, rhs: impl Into<SolidityType>) -> Option<SolidityType> {
+        self.numeric_op(
+            rhs.into(),
+            "multiply",
+            |lhs, rhs| lhs.try_mul(rhs),
+            U256::checked_mul,
+            I256::checked_mul,
+        )
+        .ok()?
     }
-}
 
-impl From<&SolidityType> for SolidityType {
-    fn from(value: &SolidityType) -> Self {
-        value.clone()
+    /// None on division by zero, and for the one overflowing division, int256 min by -1.
+    pub fn checked_div(self, rhs: impl Into<SolidityType>) -> Option<SolidityType> {
+        self.numeric_op(
+            rhs.into(),
+            "divide",
+            |lhs, rhs| lhs.try_div(rhs),
+            U256::checked_div,
+            I256::checked_div,
+        )
+        .ok()?
     }
-}
 
-// NOTE I might want to change this to try_from
-impl From<Option<Value>> for SolidityType {
-    fn from(value: Option<Value>) -> Self {
-        serde_json::from_value(value.expect("Tried to convert a None value into a SolidityType!"))
-            .unwrap()
+    /// Same as `+`, but clamps at the bounds of a uint256 or int256 rather than wrapping.
+    /// Still panics on anything but numbers, and on a uint too large to be an int256 mixed with an int.
+    pub fn saturating_add(self, rhs: impl Into<SolidityType>) -> SolidityType {
+        self.saturating_op(
+            rhs.into(),
+            "add",
+            |lhs, rhs| lhs.try_add(rhs),
+            |lhs, rhs| Some(lhs.saturating_add(rhs)),
+            |lhs, rhs| Some(lhs.saturating_add(rhs)),
+        )
     }
-}
-
-pub fn block_meta(block: &Block) -> (&Vec<u8>, u64, u64) {
-    let hash = &block.hash;
-    let number = block.number;
-    let timestamp = block.timestamp_seconds();
-    (hash, number, timestamp)
-}
-
-pub fn format_hex(input: &[u8]) -> String {
-    format!("0x{}", Hex(input).to_string())
-}
-
-impl_from!(Address, Address);
-impl_to!(Address, Address);
-
-impl_from!(String, String);
-impl_to!(String, String);
-
-impl_from!(U1, Boolean);
 
-impl_from!(alloy_primitives::U256, Uint);
-impl_to!(alloy_primitives::U256, Uint);
-
-impl_from!(Bytes, ByteArray);
-impl_to!(Bytes, ByteArray);
-
-impl_from!(alloy_primitives::B256, FixedArray);
-impl_to!(alloy_primitives::B256, FixedArray);
+    /// Same as `-`, but a uint stops at zero rather than underflowing, see `saturating_add`.
+    pub fn saturating_sub(self, rhs: impl Into<SolidityType>) -> SolidityType {
+        self.saturating_op(
+            rhs.into(),
+            "subtract",
+            |lhs, rhs| lhs.try_sub(rhs),
+            |lhs, rhs| Some(lhs.saturating_sub(rhs)),
+            |lhs, rhs| Some(lhs.saturating_sub(rhs)),
+        )
+    }
 
-impl From<Vec<u8>> for SolidityType {
-    fn from(value: Vec<u8>) -> Self {
-        if value.len() == 32 {
-            SolidityType::FixedArray(alloy_primitives::B256::from_slice(&value))
-        } else {
-            SolidityType::ByteArray(Bytes::copy_from_slice(&value))
-        }
+    pub fn saturating_mul(self, rhs: impl Into<SolidityType>) -> SolidityType {
+        self.saturating_op(
+            rhs.into(),
+            "multiply",
+            |lhs, rhs| lhs.try_mul(rhs),
+            |lhs, rhs| Some(lhs.saturating_mul(rhs)),
+            |lhs, rhs| Some(lhs.saturating_mul(rhs)),
+        )
     }
-}
 
-impl From<bool> for SolidityType {
-    fn from(value: bool) -> Self {
-        SolidityType::Boolean(U1::from(value))
+    fn saturating_op(
+        self,
+        rhs: SolidityType,
+        operation: &'static str,
+        fallback: fn(SolidityType, SolidityType) -> Result<SolidityType, SolError>,
+        uint_op: fn(U256, U256) -> Option<U256>,
+        int_op: fn(I256, I256) -> Option<I256>,
+    ) -> SolidityType {
+        self.numeric_op(rhs, operation, fallback, uint_op, int_op)
+            .unwrap_or_else(|err| panic!("{err}"))
+            .expect("saturating ops always return a value")
     }
-}
 
-impl From<SolidityType> for bool {
-    fn from(value: SolidityType) -> Self {
-        if let SolidityType::Boolean(val) = value {
-            let value: u8 = val.to();
-            if value == 0 {
-                false
-            } else {
-                true
+    /// Applies `uint_op` to two uints or `int_op` to an int and a number, anything else, Nulls included, goes through `fallback`.
+    /// The inner None is the op itself failing, ie an overflow.
+    fn numeric_op(
+        self,
+        rhs: SolidityType,
+        operation: &'static str,
+        fallback: fn(SolidityType, SolidityType) -> Result<SolidityType, SolError>,
+        uint_op: fn(U256, U256) -> Option<U256>,
+        int_op: fn(I256, I256) -> Option<I256>,
+    ) -> Result<Option<SolidityType>, SolError> {
+        match (self, rhs) {
+            (SolidityType::Uint(lh), SolidityType::Uint(rh)) => {
+                Ok(uint_op(lh, rh).map(SolidityType::Uint))
             }
-        } else {
-            panic!("Tried to convert a non boolean value into a boolean!");
+            (lhs, rhs) if lhs.is_signed_pair(&rhs) => {
+                let (lh, rh) = signed_pair(&lhs, &rhs, operation)?;
+                Ok(int_op(lh, rh).map(SolidityType::Int))
+            }
+            (lhs, rhs) => fallback(lhs, rhs).map(Some),
         }
     }
-}
-
-// Binary Op Trait Implementations
-impl<T> Add<T> for SolidityType
-where
-    SolidityType: From<T>,
-{
-    type Output = Self;
-
-    fn add(self, rhs: T) -> Self::Output {
-        let rhs: SolidityType = Into::into(rhs);
 
-        if let SolidityType::Null = &self {
-            return rhs;
+    /// Orders two numbers, strings or addresses, and errors on anything else rather than panicking.
+    /// Ints and uints compare by value, so a negative int is less than any uint.
+    pub fn try_cmp(&self, other: &SolidityType) -> Result<std::cmp::Ordering, SolError> {
+        match (self, other) {
+            (SolidityType::Uint(lh), SolidityType::Uint(rh)) => Ok(lh.cmp(rh)),
+            (SolidityType::Int(lh), SolidityType::Int(rh)) => Ok(lh.cmp(rh)),
+            (SolidityType::Int(lh), SolidityType::Uint(rh)) => Ok(cmp_int_uint(lh, rh)),
+            (SolidityType::Uint(lh), SolidityType::Int(rh)) => Ok(cmp_int_uint(rh, lh).reverse()),
+            (SolidityType::String(lh), SolidityType::String(rh)) => Ok(lh.cmp(rh)),
+            (SolidityType::Address(lh), SolidityType::Address(rh)) => Ok(lh.cmp(rh)),
+            _ => Err(SolError::NotComparable {
+                operation: "compare",
+                lhs: self.kind(),
+                rhs: other.kind(),
+            }),
         }
+    }
 
-        if let &SolidityType::Null = &rhs {
-            return self;
+    /// A compact form for store keys, ie `0xabc...:1681336800` for a tuple of a pool address and a timestamp.
+    /// A list, tuple or struct joins its values with ':', a struct in sorted key order, and nested ones are wrapped in parens.
+    /// Scalars are the same as `to_string`, except ':', '(', ')' and '\' are escaped with a '\'.
+    /// NOTE Only the values make it into the key, not the variants or field names. So a list, tuple or sized list of the same values,
+    /// or structs with the same values under different field names, share a key, as do a uint and its decimal string
+    /// or a one element list and its value. Keep a store's keys to a single shape, or use `StoreKey` to label the segments.
+    pub fn to_key_string(&self) -> String {
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => {
+                vals.iter().map(key_segment).collect::<Vec<_>>().join(":")
+            }
+            SolidityType::Struct(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by(|(lh, _), (rh, _)| lh.cmp(rh));
+                entries
+                    .into_iter()
+                    .map(|(_, value)| key_segment(value))
+                    .collect::<Vec<_>>()
+                    .join(":")
+            }
+            _ => escape_key(&self.to_string()),
         }
+    }
 
-        // NOTE If we add something to a string, it will concat. I'm not sure if I will keep this or not.
-        if let SolidityType::String(str) = &self {
-            let mut return_string = str.clone();
-            return_string.push_str(&rhs.to_string());
-            return SolidityType::String(return_string);
+    /// Renders an address as lowercase 0x prefixed hex, the canonical form for store keys.
+    pub fn to_lowercase_string(&self) -> String {
+        match self {
+            SolidityType::Address(val) => format_hex(val.as_slice()),
+            _ => panic!("Tried to format a {} as an address!", self.kind()),
         }
+    }
 
-        if let (SolidityType::Uint(lh), SolidityType::Uint(rh)) = (&self, &rhs) {
-            let sum = lh + rh;
-            return SolidityType::Uint(sum);
+    /// Renders an address with its EIP-55 checksum, or the EIP-1191 checksum when a `chain_id` is given.
+    pub fn to_checksum_string(&self, chain_id: Option<u64>) -> String {
+        match self {
+            SolidityType::Address(val) => val.to_checksum(chain_id),
+            _ => panic!("Tried to format a {} as an address!", self.kind()),
         }
-        panic!("Can't add {self:?} and {rhs:?} together!");
     }
-}
 
-impl<T> Sub<T> for SolidityType
-where
-    SolidityType: From<T>,
-{
-    type Output = Self;
+    /// Merges the entries of `other` into this struct, where `other` wins on conflicting keys.
+    /// Null on either side is treated as an empty struct, merging any other variant panics.
+    pub fn merge(&mut self, other: SolidityType) {
+        self.merge_with(other, false)
+    }
 
-    fn sub(self, rhs: T) -> Self::Output {
-        let rhs: SolidityType = Into::into(rhs);
+    /// Same as `merge`, but conflicting keys that are structs on both sides are merged recursively.
+    pub fn merge_deep(&mut self, other: SolidityType) {
+        self.merge_with(other, true)
+    }
 
-        if let SolidityType::Null = &self {
-            return rhs;
+    fn merge_with(&mut self, other: SolidityType, deep: bool) {
+        match (&mut *self, other) {
+            (_, SolidityType::Null) => {}
+            (SolidityType::Null, SolidityType::Struct(rh)) => *self = SolidityType::Struct(rh),
+            (SolidityType::Struct(lh), SolidityType::Struct(rh)) => {
+                for (key, value) in rh {
+                    match (lh.get_mut(&key), value) {
+                        (
+                            Some(existing @ SolidityType::Struct(_)),
+                            value @ SolidityType::Struct(_),
+                        ) if deep => existing.merge_with(value, deep),
+                        (_, value) => {
+                            lh.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (lh, rh) => panic!("Tried to merge a {} into a {}!", rh.kind(), lh.kind()),
         }
+    }
 
-        if let &SolidityType::Null = &rhs {
-            return self;
+    /// Wraps the values in the same container variant as self.
+    /// A sized list only stays sized if it still has the same length, otherwise it's a plain list.
+    fn with_values(&self, values: Vec<SolidityType>) -> SolidityType {
+        match self {
+            SolidityType::Tuple(_) => SolidityType::Tuple(values),
+            SolidityType::SizedList(size, _) if *size == values.len() => {
+                SolidityType::SizedList(*size, values)
+            }
+            _ => SolidityType::List(values),
         }
+    }
 
-        match (&self, &rhs) {
-            (SolidityType::Uint(lh), SolidityType::Uint(rh)) => {
-                let sum = lh - rh;
-                SolidityType::Uint(sum)
-            }
-            _ => panic!("Can't add {self:?} and {rhs:?}! Both values must be a uint!"),
+    /// The name of the variant, used in error messages.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            SolidityType::Boolean(_) => "boolean",
+            SolidityType::Enum(_) => "enum",
+            SolidityType::Uint(_) => "uint",
+            SolidityType::Int(_) => "int",
+            SolidityType::Address(_) => "address",
+            SolidityType::ByteArray(_) => "bytes",
+            SolidityType::FixedArray(_) => "bytes32",
+            SolidityType::FixedBytes(..) => "fixed bytes",
+            SolidityType::String(_) => "string",
+            SolidityType::Tuple(_) => "tuple",
+            SolidityType::List(_) => "list",
+            SolidityType::SizedList(..) => "sized list",
+            SolidityType::Struct(_) => "struct",
+            SolidityType::Null => "null",
         }
     }
-}
 
-impl<T> Mul<T> for SolidityType
-where
-    SolidityType: From<T>,
-{
-    type Output = Self;
+    /// Folds over the values of a list or tuple, starting from `init`.
+    /// Folding over Null returns `init` untouched.
+    pub fn fold<F>(&self, init: SolidityType, callback: F) -> SolidityType
+    where
+        F: Fn(SolidityType, &SolidityType) -> SolidityType,
+    {
+        self.values_for("fold").iter().fold(init, callback)
+    }
 
-    fn mul(self, rhs: T) -> Self::Output {
-        if let SolidityType::Null = &self {
-            return SolidityType::Null;
+    /// The values of a list or tuple for `fold` and the aggregates, none for Null. Panics on structs and scalars.
+    fn values_for(&self, operation: &str) -> &[SolidityType] {
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => vals,
+            SolidityType::Null => &[],
+            _ => panic!("Tried to {operation} over a {}!", self.kind()),
         }
+    }
 
-        let rhs: SolidityType = Into::into(rhs);
+    /// Iterates over borrowed values, see the `IntoIterator` impl for `&SolidityType`.
+    pub fn iter(&self) -> std::slice::Iter<'_, SolidityType> {
+        self.into_iter()
+    }
 
-        if let &SolidityType::Null = &rhs {
-            return SolidityType::Null;
-        }
-        match (&self, &rhs) {
-            (SolidityType::Uint(lh), SolidityType::Uint(rh)) => {
-                let sum = lh * rh;
-                SolidityType::Uint(sum)
-            }
-            _ => panic!("Can't add {self:?} and {rhs:?}! Both values must be a uint!"),
+    /// Takes the values out of a list or tuple, keeping their types. Anything else is None.
+    pub fn into_vec(self) -> Option<Vec<SolidityType>> {
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => Some(vals),
+            _ => None,
         }
     }
-}
 
-impl<T> Div<T> for SolidityType
-where
-    SolidityType: From<T>,
-{
-    type Output = Self;
+    /// Iterates over the key value pairs of a struct. Null yields nothing.
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &SolidityType)> {
+        let map = match self {
+            SolidityType::Struct(map) => Some(map),
+            SolidityType::Null => None,
+            _ => panic!("Tried to get the entries of a {}!", self.kind()),
+        };
+        map.into_iter().flatten()
+    }
 
-    fn div(self, rhs: T) -> Self::Output {
-        if let SolidityType::Null = &self {
-            return SolidityType::Null;
-        }
+    /// Sums the uints and ints in a list or tuple, treating Null as zero. Any int makes the sum an int, see `try_add`.
+    /// Panics on any other value, naming its index.
+    pub fn sum(&self) -> SolidityType {
+        self.sum_by(|item| item.clone())
+    }
 
-        let rhs: SolidityType = Into::into(rhs);
+    /// Same as `sum`, but sums the value at `path` of each element, ie `transfers.sum_field("amount")`.
+    /// The path is read like `get_path`, so elements missing it count as zero.
+    pub fn sum_field(&self, path: &str) -> SolidityType {
+        self.sum_by(|item| item.get_path(path))
+    }
 
-        if let &SolidityType::Null = &rhs {
-            return SolidityType::Null;
-        }
-        match (&self, &rhs) {
-            (SolidityType::Uint(lh), SolidityType::Uint(rh)) => {
-                let sum = lh / rh;
-                SolidityType::Uint(sum)
+    fn sum_by<F>(&self, value: F) -> SolidityType
+    where
+        F: Fn(&SolidityType) -> SolidityType,
+    {
+        let mut total = SolidityType::Uint(U256::ZERO);
+        for (index, item) in self.values_for("sum").iter().enumerate() {
+            let item = value(item);
+            if !matches!(
+                item,
+                SolidityType::Uint(_) | SolidityType::Int(_) | SolidityType::Null
+            ) {
+                panic!("Tried to sum a {} value at index {index}!", item.kind());
             }
-            _ => panic!("Can't add {self:?} and {rhs:?}! Both values must be a uint!"),
+            total = total
+                .try_add(item)
+                .unwrap_or_else(|err| panic!("{err} At index {index}."));
         }
+        total
     }
-}
 
-impl<T> PartialEq<T> for SolidityType
-where
-    SolidityType: From<T>,
-    T: Clone + Debug,
-{
-    fn eq(&self, other: &T) -> bool {
-        // TODO This isn't the most performant, but I don't think it's the end of the world
-        let rhs: SolidityType = Into::into(other.clone());
-        match (&self, &rhs) {
-            (SolidityType::Uint(lh), SolidityType::Uint(rh)) => lh == rh,
-            (SolidityType::Address(lh), SolidityType::Address(rh)) => lh == rh,
-            _ => panic!("Can't compare {self:?} and {other:?}"),
-        }
+    /// The smallest value in a list or tuple of numbers, strings or addresses, skipping Nulls, see `try_cmp`.
+    /// Null if there aren't any values. Panics on values that can't be compared, naming the index.
+    pub fn min(&self) -> SolidityType {
+        self.extreme("min", std::cmp::Ordering::Less)
     }
 
-    fn ne(&self, other: &T) -> bool {
-        // TODO This isn't the most performant, but I don't think it's the end of the world
-        let rhs: SolidityType = Into::into(other.clone());
-        match (&self, &rhs) {
-            (SolidityType::Uint(lh), SolidityType::Uint(rh)) => lh != rh,
-            (SolidityType::Address(lh), SolidityType::Address(rh)) => lh != rh,
-            _ => panic!("Can't compare {self:?} and {other:?}"),
-        }
+    /// The largest value in a list or tuple, see `min`.
+    pub fn max(&self) -> SolidityType {
+        self.extreme("max", std::cmp::Ordering::Greater)
     }
-}
 
-impl<T> PartialOrd<T> for SolidityType
-where
-    SolidityType: From<T>,
-    T: Clone + Debug,
-{
-    fn partial_cmp(&self, other: &T) -> Option<std::cmp::Ordering> {
-        // TODO This isn't the most performant, but I don't think it's the end of the world
-        let rhs: SolidityType = Into::into(other.clone());
-        match (&self, &rhs) {
-            (SolidityType::Uint(lh), SolidityType::Uint(rh)) => lh.partial_cmp(rh),
-            (SolidityType::String(lh), SolidityType::String(rh)) => lh.partial_cmp(rh),
-            (SolidityType::Address(lh), SolidityType::Address(rh)) => lh.partial_cmp(rh),
-            _ => panic!("Can't compare {self:?} and {other:?}"),
+    fn extreme(&self, operation: &str, wanted: std::cmp::Ordering) -> SolidityType {
+        let mut best: Option<&SolidityType> = None;
+        for (index, item) in self.values_for(operation).iter().enumerate() {
+            if let SolidityType::Null = item {
+                continue;
+            }
+            let ordering = match best {
+                Some(best) => item
+                    .try_cmp(best)
+                    .unwrap_or_else(|err| panic!("{err} At index {index}.")),
+                None => wanted,
+            };
+            if ordering == wanted {
+                best = Some(item);
+            }
         }
+        best.cloned().unwrap_or(SolidityType::Null)
     }
-}
+
+    /// Whether an arithmetic op on the two values is signed, ie an int with an int or a uint.
+    fn is_signed_pair(&self, other: &SolidityType) -> bool {
+        matches!(
+            (self, other),
+            (
+                SolidityType::Int(_),
+                SolidityType::Int(_) | SolidityType::Uint(_)
+            ) | (SolidityType::Uint(_), SolidityType::Int(_))
+        )
+    }
+
+    /// Scales a uint or int down by 10^decimals without any rounding, ie a raw token amount into whole tokens.
+    /// Panics for any other variant, see `try_to_bigdecimal`.
+    pub fn to_bigdecimal(&self, decimals: u8) -> BigDecimal {
+        self.try_to_bigdecimal(decimals)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_to_bigdecimal(&self, decimals: u8) -> Result<BigDecimal, SolError> {
+        let (negative, digits) = match self {
+            SolidityType::Uint(val) => (false, val.to_string()),
+            SolidityType::Int(val) => (val.is_negative(), val.unsigned_abs().to_string()),
+            _ => {
+                return Err(SolError::WrongVariant {
+                    operation: "convert to a decimal",
+                    found: self.kind(),
+                })
+            }
+        };
+
+        // goes through the string so every digit is kept, whatever the precision of the decimal
+        let decimals = decimals as usize;
+        let digits = format!("{digits:0>width$}", width = decimals + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - decimals);
+        let sign = if negative { "-" } else { "" };
+        let decimal = format!("{sign}{whole}.{fraction}");
+        decimal.parse().map_err(|_| SolError::ParseFailure {
+            input: decimal,
+            expected: "a decimal",
+        })
+    }
+
+    /// Checks if a numeric value (uint, int, enum or boolean) is zero. Every other variant is never zero.
+    pub fn is_zero(&self) -> bool {
+        match self {
+            SolidityType::Uint(val) => val.is_zero(),
+            SolidityType::Int(val) => val.is_zero(),
+            SolidityType::Enum(val) => val.is_zero(),
+            SolidityType::Boolean(val) => val.is_zero(),
+            _ => false,
+        }
+    }
+
+    /// Checks if a numeric value (uint, enum or boolean) is less than 2^bits, ie it fits in a `uint<bits>`.
+    /// Every other variant never fits.
+    pub fn fits_in_bits(&self, bits: usize) -> bool {
+        match self {
+            SolidityType::Uint(val) => val.bit_len() <= bits,
+            SolidityType::Enum(val) => val.bit_len() <= bits,
+            SolidityType::Boolean(val) => val.bit_len() <= bits,
+            _ => false,
+        }
+    }
+
+    /// Returns the number of values in a list, tuple or struct. Null has a length of 0.
+    pub fn len(&self) -> usize {
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => vals.len(),
+            SolidityType::Struct(map) => map.len(),
+            SolidityType::Null => 0,
+            _ => panic!("Tried to get the length of a scalar value!"),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn first(&self) -> SolidityType {
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => {
+                vals.first().cloned().unwrap_or(SolidityType::Null)
+            }
+            SolidityType::Struct(_) => panic!("Tried to get the first value of a struct!"),
+            SolidityType::Null => SolidityType::Null,
+            _ => panic!("Tried to get the first value of a scalar value!"),
+        }
+    }
+
+    pub fn last(&self) -> SolidityType {
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => {
+                vals.last().cloned().unwrap_or(SolidityType::Null)
+            }
+            SolidityType::Struct(_) => panic!("Tried to get the last value of a struct!"),
+            SolidityType::Null => SolidityType::Null,
+            _ => panic!("Tried to get the last value of a scalar value!"),
+        }
+    }
+
+    /// Checks if a list or tuple contains a value. Values of different variants are never equal.
+    pub fn contains(&self, value: &SolidityType) -> bool {
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => vals.iter().any(|item| item.strict_eq(value)),
+            SolidityType::Struct(_) => panic!("Tried to check if a struct contains a value!"),
+            SolidityType::Null => false,
+            _ => panic!("Tried to check if a scalar value contains a value!"),
+        }
+    }
+
+    /// The raw bytes of a bytes like value (bytes, bytes32 or address).
+    fn byte_slice(&self) -> Option<&[u8]> {
+        match self {
+            SolidityType::ByteArray(val) => Some(&val[..]),
+            SolidityType::FixedArray(val) => Some(val.as_slice()),
+            SolidityType::FixedBytes(_, val) => Some(&val[..]),
+            SolidityType::Address(val) => Some(val.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes in `start..end` as a ByteArray, or Null if the range is out of bounds or the value isn't bytes like.
+    pub fn slice(&self, start: usize, end: usize) -> SolidityType {
+        match self.byte_slice().and_then(|bytes| bytes.get(start..end)) {
+            Some(slice) => SolidityType::ByteArray(Bytes::copy_from_slice(slice)),
+            None => SolidityType::Null,
+        }
+    }
+
+    /// Appends the bytes of `other` onto these bytes, returning Null if either value isn't bytes like.
+    pub fn concat(&self, other: &SolidityType) -> SolidityType {
+        match (self.byte_slice(), other.byte_slice()) {
+            (Some(lh), Some(rh)) => SolidityType::ByteArray([lh, rh].concat().into()),
+            _ => SolidityType::Null,
+        }
+    }
+
+    /// Returns the number of bytes in a bytes like value. Null has a length of 0.
+    pub fn len_bytes(&self) -> usize {
+        match (self, self.byte_slice()) {
+            (_, Some(bytes)) => bytes.len(),
+            (SolidityType::Null, _) => 0,
+            _ => panic!("Tried to get the byte length of a {} value!", self.kind()),
+        }
+    }
+
+    /// Reads the 20 byte address starting at `offset`, or Null if there aren't enough bytes.
+    /// ie offset 0 for packed encodings, and 12 for a left padded abi word.
+    pub fn take_address_at(&self, offset: usize) -> SolidityType {
+        match self.slice(offset, offset + 20) {
+            SolidityType::ByteArray(bytes) => SolidityType::Address(Address::from_slice(&bytes)),
+            _ => SolidityType::Null,
+        }
+    }
+
+    /// Builds a String if the bytes are valid UTF-8, and keeps them as a ByteArray otherwise,
+    /// so token names and symbols with garbage bytes can't break anything downstream.
+    pub fn from_string_bytes(bytes: &[u8]) -> SolidityType {
+        match std::str::from_utf8(bytes) {
+            Ok(string) => SolidityType::String(string.to_string()),
+            Err(_) => SolidityType::ByteArray(Bytes::copy_from_slice(bytes)),
+        }
+    }
+
+    /// Same as `from_string_bytes`, for the old tokens (MKR, SAI) that return their name and symbol as a zero padded bytes32.
+    pub fn from_bytes32_string(word: &alloy_primitives::B256) -> SolidityType {
+        let len = word
+            .iter()
+            .rposition(|byte| *byte != 0)
+            .map_or(0, |last| last + 1);
+        SolidityType::from_string_bytes(&word[..len])
+    }
+
+    /// Whether the value holds valid UTF-8 text. Strings always do in rust, but alloy decodes invalid string parameters lossily,
+    /// so a String containing the replacement character is treated as invalid.
+    pub fn is_valid_utf8(&self) -> bool {
+        match self {
+            SolidityType::String(val) => !val.contains(char::REPLACEMENT_CHARACTER),
+            SolidityType::ByteArray(val) => std::str::from_utf8(val).is_ok(),
+            _ => false,
+        }
+    }
+
+    /// The text of a string or bytes value, with any invalid UTF-8 replaced. Zero padding is trimmed from a bytes32.
+    /// Every other variant is rendered with `to_string`.
+    pub fn to_lossy_string(&self) -> String {
+        match self {
+            SolidityType::String(val) => val.clone(),
+            SolidityType::ByteArray(val) => String::from_utf8_lossy(val).into_owned(),
+            SolidityType::FixedArray(val) => {
+                SolidityType::from_bytes32_string(val).to_lossy_string()
+            }
+            _ => self.to_string(),
+        }
+    }
+
+    /// The raw bytes of a string or bytes like value.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            SolidityType::String(val) => Some(val.as_bytes()),
+            _ => self.byte_slice(),
+        }
+    }
+
+    /// The sorted dot separated paths of the leaves of a `diff`, ie `["owner", "reserves.token0"]`.
+    pub fn changed_keys(&self) -> Vec<String> {
+        let SolidityType::Struct(map) = self else {
+            return Vec::new();
+        };
+
+        let mut keys: Vec<String> = map
+            .iter()
+            .flat_map(|(key, val)| match val {
+                SolidityType::Struct(_) => val
+                    .changed_keys()
+                    .into_iter()
+                    .map(|nested| format!("{key}.{nested}"))
+                    .collect(),
+                _ => vec![key.clone()],
+            })
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    /// Equality that compares what the values mean rather than how they're represented, for comparing store keys and the like.
+    /// Hex strings, addresses (in any case), bytes32 and bytes with the same bytes are equal, as are uints and numeric strings
+    /// of the same value. Lists, tuples and structs compare their members loosely. PartialEq stays strict.
+    pub fn loose_eq(&self, other: &SolidityType) -> bool {
+        if self.strict_eq(other) {
+            return true;
+        }
+
+        match (self, other) {
+            (
+                SolidityType::Tuple(lh) | SolidityType::List(lh) | SolidityType::SizedList(_, lh),
+                SolidityType::Tuple(rh) | SolidityType::List(rh) | SolidityType::SizedList(_, rh),
+            ) => lh.len() == rh.len() && lh.iter().zip(rh).all(|(l, r)| l.loose_eq(r)),
+            (SolidityType::Struct(lh), SolidityType::Struct(rh)) => {
+                lh.len() == rh.len()
+                    && lh
+                        .iter()
+                        .all(|(key, l)| rh.get(key).map_or(false, |r| l.loose_eq(r)))
+            }
+            (SolidityType::Uint(num), SolidityType::String(string))
+            | (SolidityType::String(string), SolidityType::Uint(num)) => {
+                U256::from_str(string.trim()).map_or(false, |value| value == *num)
+            }
+            (SolidityType::Int(num), SolidityType::String(string))
+            | (SolidityType::String(string), SolidityType::Int(num)) => {
+                I256::from_dec_str(string.trim()).map_or(false, |value| value == *num)
+            }
+            (SolidityType::Int(int), SolidityType::Uint(uint))
+            | (SolidityType::Uint(uint), SolidityType::Int(int)) => {
+                cmp_int_uint(int, uint) == std::cmp::Ordering::Equal
+            }
+            _ => match (self.loose_bytes(), other.loose_bytes()) {
+                (Some(lh), Some(rh)) => lh == rh,
+                _ => false,
+            },
+        }
+    }
+
+    /// The bytes of a bytes like value, including 0x prefixed hex strings.
+    fn loose_bytes(&self) -> Option<Vec<u8>> {
+        match self {
+            SolidityType::String(val) if val.starts_with("0x") => {
+                alloy_primitives::hex::decode(val).ok()
+            }
+            _ => self.byte_slice().map(|bytes| bytes.to_vec()),
+        }
+    }
+
+    /// Structural equality that never panics, values of different variants are never equal.
+    fn strict_eq(&self, other: &SolidityType) -> bool {
+        match (self, other) {
+            (SolidityType::Boolean(lh), SolidityType::Boolean(rh)) => lh == rh,
+            (SolidityType::Enum(lh), SolidityType::Enum(rh)) => lh == rh,
+            (SolidityType::Uint(lh), SolidityType::Uint(rh)) => lh == rh,
+            (SolidityType::Int(lh), SolidityType::Int(rh)) => lh == rh,
+            (SolidityType::Address(lh), SolidityType::Address(rh)) => lh == rh,
+            (SolidityType::ByteArray(lh), SolidityType::ByteArray(rh)) => lh == rh,
+            (SolidityType::FixedArray(lh), SolidityType::FixedArray(rh)) => lh == rh,
+            (SolidityType::FixedBytes(_, lh), SolidityType::FixedBytes(_, rh)) => lh == rh,
+            (SolidityType::String(lh), SolidityType::String(rh)) => lh == rh,
+            (SolidityType::Tuple(lh), SolidityType::Tuple(rh))
+            | (SolidityType::List(lh), SolidityType::List(rh))
+            | (SolidityType::SizedList(_, lh), SolidityType::SizedList(_, rh)) => {
+                lh.len() == rh.len() && lh.iter().zip(rh).all(|(l, r)| l.strict_eq(r))
+            }
+            (SolidityType::Struct(lh), SolidityType::Struct(rh)) => {
+                lh.len() == rh.len()
+                    && lh
+                        .iter()
+                        .all(|(key, l)| rh.get(key).map_or(false, |r| l.strict_eq(r)))
+            }
+            (SolidityType::Null, SolidityType::Null) => true,
+            _ => false,
+        }
+    }
+
+    /// Flattens nested structs and tuples into a single struct, joining the keys with `separator`.
+    /// ie `Struct{a: Struct{b: 1}}` becomes `Struct{"a.b": 1}`, and tuple members are keyed by their index.
+    /// Lists are left as is, use `flatten_lists` to also explode them.
+    pub fn flatten(&self, separator: &str) -> SolidityType {
+        self.flatten_with(separator, false)
+    }
+
+    /// Same as `flatten`, but lists are also exploded and keyed by their index.
+    pub fn flatten_lists(&self, separator: &str) -> SolidityType {
+        self.flatten_with(separator, true)
+    }
+
+    fn flatten_with(&self, separator: &str, explode_lists: bool) -> SolidityType {
+        match self {
+            SolidityType::Struct(_) | SolidityType::Tuple(_) => {}
+            SolidityType::List(_) | SolidityType::SizedList(..) if explode_lists => {}
+            _ => return self.clone(),
+        }
+
+        let mut map = IndexMap::new();
+        self.flatten_into(None, separator, explode_lists, &mut map);
+        SolidityType::Struct(map)
+    }
+
+    fn flatten_into(
+        &self,
+        prefix: Option<&str>,
+        separator: &str,
+        explode_lists: bool,
+        map: &mut IndexMap<String, SolidityType>,
+    ) {
+        let join = |key: &str| match prefix {
+            Some(prefix) => format!("{prefix}{separator}{key}"),
+            None => key.to_string(),
+        };
+
+        match self {
+            SolidityType::Struct(values) => {
+                for (key, value) in values {
+                    value.flatten_into(Some(&join(key)), separator, explode_lists, map);
+                }
+            }
+            SolidityType::Tuple(values) => {
+                for (index, value) in values.iter().enumerate() {
+                    let key = join(&index.to_string());
+                    value.flatten_into(Some(&key), separator, explode_lists, map);
+                }
+            }
+            SolidityType::List(values) | SolidityType::SizedList(_, values) if explode_lists => {
+                for (index, value) in values.iter().enumerate() {
+                    let key = join(&index.to_string());
+                    value.flatten_into(Some(&key), separator, explode_lists, map);
+                }
+            }
+            _ => {
+                if let Some(prefix) = prefix {
+                    map.insert(prefix.to_string(), self.clone());
+                }
+            }
+        }
+    }
+
+    /// Rebuilds the nested shape of a struct produced by `flatten`.
+    /// NOTE Any nested struct whose keys are exactly 0..n is rebuilt as a tuple, so exploded lists come back as tuples.
+    pub fn unflatten(&self, separator: &str) -> SolidityType {
+        let values = if let SolidityType::Struct(values) = self {
+            values
+        } else {
+            return self.clone();
+        };
+
+        let mut root = IndexMap::new();
+        for (key, value) in values {
+            let segments: Vec<&str> = key.split(separator).collect();
+            insert_segments(&mut root, &segments, value.clone());
+        }
+
+        SolidityType::Struct(root).rebuild_tuples()
+    }
+
+    fn rebuild_tuples(self) -> SolidityType {
+        match self {
+            SolidityType::Struct(mut map) => {
+                let is_tuple =
+                    !map.is_empty() && (0..map.len()).all(|i| map.contains_key(&i.to_string()));
+                if is_tuple {
+                    let values = (0..map.len())
+                        .filter_map(|i| map.shift_remove(&i.to_string()))
+                        .map(|value| value.rebuild_tuples())
+                        .collect();
+                    SolidityType::Tuple(values)
+                } else {
+                    let map = map
+                        .into_iter()
+                        .map(|(key, value)| (key, value.rebuild_tuples()))
+                        .collect();
+                    SolidityType::Struct(map)
+                }
+            }
+            _ => self,
+        }
+    }
+
+    /// Converts the value into plain json, without the type tags used by the serde representation.
+    /// Struct keys are inserted in sorted order, and Null becomes a json null.
+    pub fn to_untagged_value(&self) -> Value {
+        self.untagged_value(false)
+    }
+
+    /// The same tagged json the Serialize impl writes, but with uints as decimal strings rather than hex,
+    /// for sinks and consumers that expect them that way. Deserializing accepts either form.
+    /// Null has no tagged form, so it becomes a json null, like in `to_untagged_value`.
+    pub fn to_tagged_value_decimal(&self) -> Value {
+        let tagged = |kind: &str, value: Value| {
+            let mut map = Map::new();
+            map.insert("type".to_string(), Value::String(kind.to_string()));
+            map.insert("value".to_string(), value);
+            Value::Object(map)
+        };
+
+        match self {
+            SolidityType::Uint(val) => tagged("uint", Value::String(val.to_string())),
+            SolidityType::Tuple(vals) => tagged(
+                "tuple",
+                vals.iter()
+                    .map(|val| val.to_tagged_value_decimal())
+                    .collect(),
+            ),
+            SolidityType::List(vals) => tagged(
+                "list",
+                vals.iter()
+                    .map(|val| val.to_tagged_value_decimal())
+                    .collect(),
+            ),
+            SolidityType::SizedList(size, vals) => tagged(
+                "sizedList",
+                Value::Array(vec![
+                    Value::from(*size),
+                    vals.iter()
+                        .map(|val| val.to_tagged_value_decimal())
+                        .collect(),
+                ]),
+            ),
+            SolidityType::Struct(map) => tagged(
+                "struct",
+                Value::Object(
+                    map.iter()
+                        .map(|(key, val)| (key.clone(), val.to_tagged_value_decimal()))
+                        .collect(),
+                ),
+            ),
+            SolidityType::Null => Value::Null,
+            _ => serde_json::to_value(self).unwrap_or(Value::Null),
+        }
+    }
+
+    /// Converts the value back into a type generated by `loose_sol!`, or anything else that implements Deserialize.
+    /// The value goes through its untagged json form, with uints small enough to be a json number written as one,
+    /// since that's what fields like uint8 deserialize from. The error names the field that failed, if it can be found.
+    pub fn to_sol_struct<T: DeserializeOwned>(&self) -> Result<T, ConvertError> {
+        deserialize_tracked(self.untagged_value(true))
+    }
+
+    /// Reads json in the tagged form `Serialize` writes, ie `{"type": "uint", "value": "0x1"}`.
+    /// A malformed payload, like an unknown "type", errors with the field that failed rather than panicking.
+    pub fn try_from_tagged_json(value: Value) -> Result<SolidityType, ConvertError> {
+        deserialize_tracked(value)
+    }
+
+    /// Reads the tagged form if the json is in it, and otherwise guesses it like `guess_json_value`,
+    /// so plain json that wasn't written by this crate converts too. Json that can't even be guessed is Null.
+    pub fn from_json_lossy(value: Value) -> SolidityType {
+        match SolidityType::deserialize(&value) {
+            Ok(value) => value,
+            Err(_) => SolidityType::guess_json_value(value).unwrap_or(SolidityType::Null),
+        }
+    }
+
+    fn untagged_value(&self, small_uints_as_numbers: bool) -> Value {
+        match self {
+            SolidityType::Boolean(val) => {
+                let value: u8 = val.to();
+                Value::Bool(value != 0)
+            }
+            SolidityType::Enum(val) => {
+                let value: u8 = val.to();
+                Value::from(value)
+            }
+            SolidityType::Uint(val) if small_uints_as_numbers && val.bit_len() <= 64 => {
+                Value::from(val.to::<u64>())
+            }
+            SolidityType::Uint(val) => serde_json::to_value(val).unwrap(),
+            SolidityType::Int(val) if small_uints_as_numbers => match i64::try_from(*val) {
+                Ok(val) => Value::from(val),
+                Err(_) => Value::String(val.to_string()),
+            },
+            SolidityType::Int(val) => Value::String(val.to_string()),
+            SolidityType::Address(val) => serde_json::to_value(val).unwrap(),
+            SolidityType::ByteArray(val) => serde_json::to_value(val).unwrap(),
+            SolidityType::FixedArray(val) => serde_json::to_value(val).unwrap(),
+            SolidityType::FixedBytes(_, val) => serde_json::to_value(val).unwrap(),
+            SolidityType::String(val) => Value::String(val.clone()),
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => Value::Array(
+                vals.iter()
+                    .map(|val| val.untagged_value(small_uints_as_numbers))
+                    .collect(),
+            ),
+            SolidityType::Struct(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                let object = keys
+                    .into_iter()
+                    .map(|key| (key.clone(), map[key].untagged_value(small_uints_as_numbers)))
+                    .collect::<Map<String, Value>>();
+                Value::Object(object)
+            }
+            SolidityType::Null => Value::Null,
+        }
+    }
+
+    /// ABI encodes the value as a single parameter, the same bytes `SolValue::abi_encode` gives for the equivalent rust type.
+    /// Structs are encoded as tuples with their fields in key sorted order, since a Struct doesn't keep the declared order.
+    /// Panics if the value holds a Null.
+    pub fn abi_encode(&self) -> Vec<u8> {
+        match self.try_abi_encode() {
+            Ok(encoded) => encoded,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    pub fn try_abi_encode(&self) -> Result<Vec<u8>, SolError> {
+        Ok(self.to_dyn_value()?.abi_encode())
+    }
+
+    /// Decodes `data` as a single parameter of the solidity type `schema`, ie "(address,uint256,bytes)".
+    /// Tuples decode into Tuples, so a struct encoded with `abi_encode` comes back with its fields by position.
+    pub fn abi_decode(data: &[u8], schema: &str) -> Result<SolidityType, SolError> {
+        let ty = DynSolType::parse(schema).map_err(|_| SolError::ParseFailure {
+            input: schema.to_string(),
+            expected: "a solidity type",
+        })?;
+        let value = ty.abi_decode(data).map_err(|err| SolError::EncodingError {
+            context: format!("abi decode {schema}"),
+            message: err.to_string(),
+        })?;
+        Ok(value.into())
+    }
+
+    /// Converts the value into the DynSolValue it would be ABI encoded as, see `abi_encode`.
+    pub fn to_dyn_value(&self) -> Result<DynSolValue, SolError> {
+        let value = match self {
+            SolidityType::Boolean(val) => DynSolValue::Bool(val.to::<u8>() != 0),
+            SolidityType::Enum(val) => DynSolValue::Uint(U256::from(val.to::<u8>()), 8),
+            SolidityType::Uint(val) => DynSolValue::Uint(*val, 256),
+            SolidityType::Int(val) => DynSolValue::Int(*val, 256),
+            SolidityType::Address(val) => DynSolValue::Address(*val),
+            SolidityType::ByteArray(val) => DynSolValue::Bytes(val.to_vec()),
+            SolidityType::FixedArray(val) => DynSolValue::FixedBytes(*val, 32),
+            SolidityType::FixedBytes(size, val) if *size == val.len() && *size <= 32 => {
+                let mut word = alloy_primitives::B256::ZERO;
+                word[..*size].copy_from_slice(val);
+                DynSolValue::FixedBytes(word, *size)
+            }
+            SolidityType::FixedBytes(size, val) => {
+                return Err(SolError::EncodingError {
+                    context: format!("bytes{size}"),
+                    message: format!("found {} bytes", val.len()),
+                })
+            }
+            SolidityType::String(val) => DynSolValue::String(val.clone()),
+            SolidityType::Tuple(vals) => DynSolValue::Tuple(Self::dyn_values(vals.iter())?),
+            SolidityType::List(vals) => DynSolValue::Array(Self::dyn_values(vals.iter())?),
+            SolidityType::SizedList(_, vals) => {
+                DynSolValue::FixedArray(Self::dyn_values(vals.iter())?)
+            }
+            SolidityType::Struct(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                DynSolValue::Tuple(Self::dyn_values(keys.into_iter().map(|key| &map[key]))?)
+            }
+            SolidityType::Null => {
+                return Err(SolError::WrongVariant {
+                    operation: "abi encode",
+                    found: self.kind(),
+                })
+            }
+        };
+        Ok(value)
+    }
+
+    fn dyn_values<'a>(
+        vals: impl Iterator<Item = &'a SolidityType>,
+    ) -> Result<Vec<DynSolValue>, SolError> {
+        vals.map(SolidityType::to_dyn_value).collect()
+    }
+
+    /// Converts the value into a protobuf Struct for module outputs, dropping any Null values along the way.
+    /// The struct is the same tagged form `Serialize` writes, ie `{"type": "uint", "value": "0x1"}`, so `From<Struct>` reads it back,
+    /// but it's built directly rather than through serde_json. Returns None if nothing is left once the Nulls are dropped.
+    pub fn to_proto_struct(&self) -> Option<Struct> {
+        self.to_maybe_value()?.tagged_struct(false).ok()
+    }
+
+    /// The tagged form as a protobuf Struct, see `to_proto_struct`. Errors if the value holds a Null, which has no tagged form.
+    pub fn try_to_tagged_struct(&self) -> Result<Struct, SolError> {
+        self.tagged_struct(false)
+    }
+
+    /// With `decimal_uints` the uints are written as decimal strings, like `to_tagged_value_decimal`.
+    pub(crate) fn tagged_struct(&self, decimal_uints: bool) -> Result<Struct, SolError> {
+        let tagged_list = |vals: &[SolidityType]| -> Result<ProtoValue, SolError> {
+            let values = vals
+                .iter()
+                .map(|val| {
+                    Ok(proto_kind(Kind::StructValue(
+                        val.tagged_struct(decimal_uints)?,
+                    )))
+                })
+                .collect::<Result<Vec<_>, SolError>>()?;
+            Ok(proto_list(values))
+        };
+
+        let (kind, value) = match self {
+            SolidityType::Boolean(val) => ("boolean", proto_string(format!("{val:#x}"))),
+            SolidityType::Enum(val) => ("enum", proto_string(format!("{val:#x}"))),
+            SolidityType::Uint(val) if decimal_uints => ("uint", proto_string(val.to_string())),
+            SolidityType::Uint(val) => ("uint", proto_string(format!("{val:#x}"))),
+            SolidityType::Int(val) => ("int", proto_string(val.to_string())),
+            SolidityType::Address(val) => ("address", proto_string(format_hex(val.as_slice()))),
+            SolidityType::ByteArray(val) => ("byteArray", proto_string(format_hex(val))),
+            SolidityType::FixedArray(val) => {
+                ("fixedArray", proto_string(format_hex(val.as_slice())))
+            }
+            SolidityType::FixedBytes(size, val) => (
+                "fixedBytes",
+                proto_list(vec![proto_number(*size), proto_string(format_hex(val))]),
+            ),
+            SolidityType::String(val) => ("string", proto_string(val.clone())),
+            SolidityType::Tuple(vals) => ("tuple", tagged_list(vals)?),
+            SolidityType::List(vals) => ("list", tagged_list(vals)?),
+            SolidityType::SizedList(size, vals) => (
+                "sizedList",
+                proto_list(vec![proto_number(*size), tagged_list(vals)?]),
+            ),
+            SolidityType::Struct(map) => {
+                let fields = map
+                    .iter()
+                    .map(|(key, value)| {
+                        let value = value.tagged_struct(decimal_uints)?;
+                        Ok((key.clone(), proto_kind(Kind::StructValue(value))))
+                    })
+                    .collect::<Result<HashMap<_, _>, SolError>>()?;
+                ("struct", proto_kind(Kind::StructValue(Struct { fields })))
+            }
+            SolidityType::Null => {
+                return Err(SolError::WrongVariant {
+                    operation: "encode",
+                    found: self.kind(),
+                })
+            }
+        };
+
+        Ok(Struct {
+            fields: HashMap::from([
+                ("type".to_string(), proto_string(kind.to_string())),
+                ("value".to_string(), value),
+            ]),
+        })
+    }
+
+    /// Reads the tagged form back without going through serde_json, see `to_proto_struct`.
+    /// None if the struct isn't in the tagged form, or any of its members fail to parse.
+    pub fn from_tagged_struct(value: &Struct) -> Option<SolidityType> {
+        let Some(Kind::StringValue(kind)) = value.fields.get("type")?.kind.as_ref() else {
+            return None;
+        };
+        let value = value.fields.get("value")?;
+        let string = || proto_str(value);
+        let sized = || match proto_items(value)? {
+            [size, inner] => Some((proto_size(size)?, inner)),
+            _ => None,
+        };
+
+        let value = match kind.as_str() {
+            "boolean" => SolidityType::Boolean(string()?.parse().ok()?),
+            "enum" => SolidityType::Enum(string()?.parse().ok()?),
+            "uint" => SolidityType::Uint(string()?.parse().ok()?),
+            "int" => SolidityType::Int(string()?.parse().ok()?),
+            "address" => SolidityType::Address(string()?.parse().ok()?),
+            "byteArray" => SolidityType::ByteArray(string()?.parse().ok()?),
+            "fixedArray" => SolidityType::FixedArray(string()?.parse().ok()?),
+            "fixedBytes" => {
+                let (size, bytes) = sized()?;
+                SolidityType::FixedBytes(size, proto_str(bytes)?.parse().ok()?)
+            }
+            "string" => SolidityType::String(string()?.to_string()),
+            "tuple" => SolidityType::Tuple(proto_tagged_items(value)?),
+            "list" => SolidityType::List(proto_tagged_items(value)?),
+            "sizedList" => {
+                let (size, items) = sized()?;
+                SolidityType::SizedList(size, proto_tagged_items(items)?)
+            }
+            "struct" => {
+                let Some(Kind::StructValue(fields)) = value.kind.as_ref() else {
+                    return None;
+                };
+                // proto structs don't keep an order, so the keys are sorted to read back the same way every time
+                let mut fields: Vec<(String, SolidityType)> = fields
+                    .fields
+                    .iter()
+                    .map(|(key, value)| Some((key.clone(), proto_tagged(value)?)))
+                    .collect::<Option<_>>()?;
+                fields.sort_by(|(lh, _), (rh, _)| lh.cmp(rh));
+                SolidityType::Struct(fields.into_iter().collect())
+            }
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Drops Null members recursively. Lists, tuples and structs left empty collapse to None, all the way up,
+    /// so a struct of empty lists is None too. Same as `prune(EmptyPolicy::Skip)`.
+    pub fn to_maybe_value(&self) -> Option<SolidityType> {
+        self.prune(EmptyPolicy::Skip)
+    }
+
+    /// Returns the value untouched, or None if it or any member, at any depth, is Null.
+    /// Use it to reject partially populated outputs instead of writing what's left of them.
+    pub fn to_maybe_value_strict(&self) -> Option<SolidityType> {
+        if self.has_null() {
+            None
+        } else {
+            Some(self.clone())
+        }
+    }
+
+    fn has_null(&self) -> bool {
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => vals.iter().any(SolidityType::has_null),
+            SolidityType::Struct(map) => map.values().any(SolidityType::has_null),
+            SolidityType::Null => true,
+            _ => false,
+        }
+    }
+
+    /// Drops Null members recursively, and handles the lists, tuples and structs left empty according to `policy`.
+    /// Returns None if there's nothing left to write.
+    pub fn prune(&self, policy: EmptyPolicy) -> Option<SolidityType> {
+        let pruned = match self {
+            SolidityType::Tuple(vals) => {
+                SolidityType::Tuple(vals.iter().filter_map(|item| item.prune(policy)).collect())
+            }
+            SolidityType::List(vals) | SolidityType::SizedList(_, vals) => {
+                self.with_values(vals.iter().filter_map(|item| item.prune(policy)).collect())
+            }
+            SolidityType::Struct(map) => SolidityType::Struct(
+                map.iter()
+                    .filter_map(|(k, v)| Some((k.to_string(), v.prune(policy)?)))
+                    .collect(),
+            ),
+            SolidityType::Null => return None,
+            _ => return Some(self.clone()),
+        };
+
+        let is_empty = match &pruned {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => vals.is_empty(),
+            SolidityType::Struct(map) => map.is_empty(),
+            _ => false,
+        };
+
+        match policy {
+            _ if !is_empty => Some(pruned),
+            EmptyPolicy::Skip => None,
+            EmptyPolicy::WriteEmpty => Some(pruned),
+        }
+    }
+}
+
+/// What `SolidityType::prune` does with lists, tuples and structs that are empty once their Nulls are dropped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EmptyPolicy {
+    /// Drop them, so a value that's empty all the way down isn't written at all.
+    #[default]
+    Skip,
+    /// Keep them as explicit empties, ie `[]` or `{}`.
+    WriteEmpty,
+}
+
+// Inserts a value into a nested struct, creating the intermediate structs as needed
+fn insert_segments(
+    map: &mut IndexMap<String, SolidityType>,
+    segments: &[&str],
+    value: SolidityType,
+) {
+    match segments {
+        [] => {}
+        [last] => {
+            map.insert(last.to_string(), value);
+        }
+        [first, rest @ ..] => {
+            let entry = map
+                .entry(first.to_string())
+                .or_insert_with(|| SolidityType::Struct(IndexMap::new()));
+            if let SolidityType::Struct(inner) = entry {
+                insert_segments(inner, rest, value);
+            }
+        }
+    }
+}
+
+/// Lists and tuples yield their values, scalars yield themselves and Null yields nothing.
+/// Structs panic, use `entries` instead.
+impl IntoIterator for SolidityType {
+    type Item = SolidityType;
+    type IntoIter = std::vec::IntoIter<SolidityType>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => vals.into_iter(),
+            SolidityType::Struct(_) => {
+                panic!("Tried to iterate over a struct! Use entries instead.")
+            }
+            SolidityType::Null => Vec::new().into_iter(),
+            scalar => vec![scalar].into_iter(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a SolidityType {
+    type Item = &'a SolidityType;
+    type IntoIter = std::slice::Iter<'a, SolidityType>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            SolidityType::Tuple(vals)
+            | SolidityType::List(vals)
+            | SolidityType::SizedList(_, vals) => vals.iter(),
+            SolidityType::Struct(_) => {
+                panic!("Tried to iterate over a struct! Use entries instead.")
+            }
+            SolidityType::Null => [].iter(),
+            scalar => std::slice::from_ref(scalar).iter(),
+        }
+    }
+}
+
+impl FromIterator<SolidityType> for SolidityType {
+    fn from_iter<I: IntoIterator<Item = SolidityType>>(iter: I) -> Self {
+        SolidityType::List(iter.into_iter().collect())
+    }
+}
+
+impl FromIterator<(String, SolidityType)> for SolidityType {
+    fn from_iter<I: IntoIterator<Item = (String, SolidityType)>>(iter: I) -> Self {
+        SolidityType::Struct(iter.into_iter().collect())
+    }
+}
+
+/// Serializes the wrapped value with its uints as decimal strings, see `SolidityType::to_tagged_value_decimal`.
+/// Pass it to `GenericStore::generic_set` (or use `generic_set_decimal`) to pick decimal mode for a store.
+#[derive(Debug, Clone)]
+pub struct DecimalUints(pub SolidityType);
+
+impl Serialize for DecimalUints {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.to_tagged_value_decimal().serialize(serializer)
+    }
+}
+
+impl AsRef<SolidityType> for DecimalUints {
+    fn as_ref(&self) -> &SolidityType {
+        &self.0
+    }
+}
+
+impl AsRef<SolidityType> for SolidityType {
+    fn as_ref(&self) -> &SolidityType {
+        &self
+    }
+}
+
+impl ToString for SolidityType {
+    fn to_string(&self) -> String {
+        match &self {
+            SolidityType::Boolean(val) => {
+                let value: u8 = val.to();
+                if value == 0 {
+                    "false".to_string()
+                } else {
+                    "true".to_string()
+                }
+            }
+            SolidityType::Enum(val) => {
+                let value: u8 = val.to();
+                value.to_string()
+            }
+            SolidityType::Uint(val) => val.to_string(),
+            SolidityType::Int(val) => val.to_string(),
+            // NOTE Addresses are lowercase rather than checksummed, since that's how store keys are written across the ecosystem.
+            // Use to_checksum_string for display.
+            SolidityType::Address(_) => self.to_lowercase_string(),
+            SolidityType::ByteArray(val) => val.to_string(),
+            SolidityType::FixedArray(val) => val.to_string(),
+            SolidityType::FixedBytes(_, val) => val.to_string(),
+            SolidityType::String(val) => val.to_string(),
+            SolidityType::Null => "null".to_string(),
+            // Composite values are rendered as compact json, with the struct keys sorted so the output is stable.
+            SolidityType::Tuple(_)
+            | SolidityType::List(_)
+            | SolidityType::SizedList(..)
+            | SolidityType::Struct(_) => self.to_untagged_value().to_string(),
+        }
+    }
+}
+
+/// Reads the tagged form `to_proto_struct` writes directly, and guesses any other struct, see `GuessValue`.
+impl From<Struct> for SolidityType {
+    fn from(value: Struct) -> Self {
+        match SolidityType::from_tagged_struct(&value) {
+            Some(value) => value,
+            None => SolidityType::guess_json_value(proto_struct_to_json(&value)).unwrap(),
+        }
+    }
+}
+
+impl From<Deltas<DeltaProto<Struct>>> for SolidityType {
+    fn from(value: Deltas<DeltaProto<Struct>>) -> Self {
+        let deltas = value.deltas;
+        let deltas = deltas.into_iter().map(SolidityType::from).collect();
+        SolidityType::List(deltas)
+    }
+}
+
+/// Same as converting the deltas into a SolidityType, but only the deltas with `operation`, ie only the Creates.
+pub fn deltas_filtered(deltas: Deltas<DeltaProto<Struct>>, operation: Operation) -> SolidityType {
+    SolidityType::List(
+        deltas
+            .deltas
+            .into_iter()
+            .filter(|delta| delta.operation == operation)
+            .map(SolidityType::from)
+            .collect(),
+    )
+}
+
+/// Same as converting the deltas into a SolidityType, but only the deltas whose key starts with `prefix`,
+/// with the prefix stripped from their "key", ie `pool:0xabc...` becomes `0xabc...` for the prefix `pool:`.
+pub fn deltas_with_prefix(deltas: Deltas<DeltaProto<Struct>>, prefix: &str) -> SolidityType {
+    SolidityType::List(
+        deltas
+            .deltas
+            .into_iter()
+            .filter_map(|mut delta| {
+                delta.key = delta.key.strip_prefix(prefix)?.to_string();
+                Some(SolidityType::from(delta))
+            })
+            .collect(),
+    )
+}
+
+impl From<DeltaProto<Struct>> for SolidityType {
+    fn from(value: DeltaProto<Struct>) -> Self {
+        let DeltaProto {
+            operation,
+            key,
+            old_value,
+            new_value,
+            ..
+        } = value;
+
+        let old_value = decode_stored_value(old_value);
+        let new_value = decode_stored_value(new_value);
+        // only the fields that changed, for sinks that write updates field by field
+        let changes = (operation == Operation::Update).then(|| {
+            let removed = removed_keys(&old_value, &new_value)
+                .into_iter()
+                .map(SolidityType::String)
+                .collect();
+            (diff(&old_value, &new_value), SolidityType::List(removed))
+        });
+
+        let mut delta = map_literal!(
+            "operation"; SolidityType::from(operation),
+            "key"; SolidityType::String(key)
+        );
+        // a Create has no old value and a Delete no new one, and Null can't be serialized, so they're left out
+        for (name, value) in [("old_value", old_value), ("new_value", new_value)] {
+            if !matches!(value, SolidityType::Null) {
+                delta.insert(name, value);
+            }
+        }
+        // removed fields are listed by name, the diff only has the ones still there
+        if let Some((changes, removed)) = changes {
+            delta.insert("diff", changes);
+            delta.insert("removed", removed);
+        }
+        delta
+    }
+}
+
+impl From<Deltas<DeltaBigInt>> for SolidityType {
+    fn from(value: Deltas<DeltaBigInt>) -> Self {
+        let deltas = value.deltas;
+        let deltas = deltas.into_iter().map(SolidityType::from).collect();
+        SolidityType::List(deltas)
+    }
+}
+
+impl From<DeltaBigInt> for SolidityType {
+    fn from(value: DeltaBigInt) -> Self {
+        let DeltaBigInt {
+            operation,
+            key,
+            old_value,
+            new_value,
+            ..
+        } = value;
+
+        map_literal!(
+            "operation"; SolidityType::from(operation),
+            "key"; SolidityType::String(key),
+            "old_value"; SolidityType::from(old_value),
+            "new_value"; SolidityType::from(new_value)
+        )
+    }
+}
+
+// NOTE Negative values become ints, and values that don't fit in a uint256 or an int256 become Null.
+impl From<BigInt> for SolidityType {
+    fn from(value: BigInt) -> Self {
+        let (sign, bytes) = value.to_bytes_be();
+        if sign == num_bigint::Sign::Minus {
+            return I256::from_dec_str(&value.to_string())
+                .map(SolidityType::Int)
+                .unwrap_or(SolidityType::Null);
+        }
+
+        match U256::try_from_be_slice(&bytes) {
+            Some(value) => SolidityType::Uint(value),
+            None => SolidityType::Null,
+        }
+    }
+}
+
+/// The big endian unsigned bytes of a protobuf BigInt, ie a transaction's value, which can be well past a u64.
+/// Anything wider than 256 bits becomes Null.
+impl From<&PbBigInt> for SolidityType {
+    fn from(value: &PbBigInt) -> Self {
+        U256::try_from_be_slice(&value.bytes)
+            .map(SolidityType::Uint)
+            .unwrap_or(SolidityType::Null)
+    }
+}
+
+impl TryFrom<SolidityType> for BigInt {
+    type Error = SolError;
+
+    fn try_from(value: SolidityType) -> Result<Self, Self::Error> {
+        match value {
+            SolidityType::Uint(val) => Ok(BigInt::from_unsigned_bytes_be(&val.to_be_bytes::<32>())),
+            SolidityType::Int(val) => Ok(BigInt::from_signed_bytes_be(&val.to_be_bytes::<32>())),
+            _ => Err(SolError::WrongVariant {
+                operation: "make a BigInt out of",
+                found: value.kind(),
+            }),
+        }
+    }
+}
+
+// NOTE We don't have a decimal variant, so decimals are kept as their string representation to avoid losing precision.
+// See `to_bigdecimal` for the way from a raw uint amount to a decimal.
+impl From<BigDecimal> for SolidityType {
+    fn from(value: BigDecimal) -> Self {
+        SolidityType::String(value.to_string())
+    }
+}
+
+impl TryFrom<SolidityType> for BigDecimal {
+    type Error = SolError;
+
+    fn try_from(value: SolidityType) -> Result<Self, Self::Error> {
+        let input = match value {
+            SolidityType::Uint(val) => val.to_string(),
+            SolidityType::Int(val) => val.to_string(),
+            SolidityType::String(val) => val,
+            _ => {
+                return Err(SolError::WrongVariant {
+                    operation: "make a BigDecimal out of",
+                    found: value.kind(),
+                })
+            }
+        };
+        input.parse().map_err(|_| SolError::ParseFailure {
+            input,
+            expected: "a BigDecimal",
+        })
+    }
+}
+
+/// Operations are written as their name, "Unset", "Create", "Update" or "Delete", so they read as is in a sink.
+impl From<Operation> for SolidityType {
+    fn from(value: Operation) -> Self {
+        match value {
+            Operation::Unset => SolidityType::String("Unset".to_string()),
+            Operation::Create => SolidityType::String("Create".to_string()),
+            Operation::Update => SolidityType::String("Update".to_string()),
+            Operation::Delete => SolidityType::String("Delete".to_string()),
+        }
+    }
+}
+
+/// Reads an operation back from its name, or from an enum or uint of its protobuf value,
+/// 0 for Unset, 1 for Create, 2 for Update and 3 for Delete.
+impl TryFrom<SolidityType> for Operation {
+    type Error = SolError;
+
+    fn try_from(value: SolidityType) -> Result<Self, Self::Error> {
+        let code = match &value {
+            SolidityType::String(name) => match name.as_str() {
+                "Unset" => Some(0),
+                "Create" => Some(1),
+                "Update" => Some(2),
+                "Delete" => Some(3),
+                _ => None,
+            },
+            SolidityType::Enum(val) => Some(val.to::<u8>()),
+            SolidityType::Uint(val) => u8::try_from(val).ok(),
+            _ => None,
+        };
+
+        match code {
+            Some(0) => Ok(Operation::Unset),
+            Some(1) => Ok(Operation::Create),
+            Some(2) => Ok(Operation::Update),
+            Some(3) => Ok(Operation::Delete),
+            _ => Err(SolError::WrongVariant {
+                operation: "make a store delta operation out of",
+                found: value.kind(),
+            }),
+        }
+    }
+}
+
+// NOTE I might want to change this to try_from
+/// Reads the tagged form `Serialize` writes, and guesses any other json, see `SolidityType::from_json_lossy`.
+impl From<Value> for SolidityType {
+    fn from(value: Value) -> Self {
+        SolidityType::from_json_lossy(value)
+    }
+}
+
+impl From<&SolidityType> for SolidityType {
+    fn from(value: &SolidityType) -> Self {
+        value.clone()
+    }
+}
+
+// NOTE I might want to change this to try_from
+/// None is Null, like an `Option<Struct>` input with the `optional` annotation in `format_inputs!`.
+impl From<Option<Value>> for SolidityType {
+    fn from(value: Option<Value>) -> Self {
+        match value {
+            Some(value) => SolidityType::from_json_lossy(value),
+            None => SolidityType::Null,
+        }
+    }
+}
+
+pub fn block_meta(block: &Block) -> (&Vec<u8>, u64, u64) {
+    let hash = &block.hash;
+    let number = block.number;
+    let timestamp = block.timestamp_seconds();
+    (hash, number, timestamp)
+}
+
+/// Returns the block metadata as a struct with the hash, number, timestamp, parent_hash and base_fee_per_gas.
+/// Anything missing from the block, like the base fee before London, is Null.
+pub fn block_meta_sol(block: &Block) -> SolidityType {
+    let header = block.header.as_ref();
+
+    let timestamp = header
+        .and_then(|header| header.timestamp.as_ref())
+        .map(|timestamp| SolidityType::Uint(U256::from(timestamp.seconds as u64)))
+        .unwrap_or(SolidityType::Null);
+
+    let parent_hash = header
+        .map(|header| hash_or_null(&header.parent_hash))
+        .unwrap_or(SolidityType::Null);
+
+    let base_fee_per_gas = header
+        .and_then(|header| header.base_fee_per_gas.as_ref())
+        .and_then(|fee| U256::try_from_be_slice(&fee.bytes))
+        .map(SolidityType::Uint)
+        .unwrap_or(SolidityType::Null);
+
+    map_literal!(
+        "hash"; hash_or_null(&block.hash),
+        "number"; SolidityType::Uint(U256::from(block.number)),
+        "timestamp"; timestamp,
+        "parent_hash"; parent_hash,
+        "base_fee_per_gas"; base_fee_per_gas
+    )
+}
+
+pub(crate) fn hash_or_null(bytes: &[u8]) -> SolidityType {
+    if bytes.len() == 32 {
+        SolidityType::FixedArray(alloy_primitives::B256::from_slice(bytes))
+    } else {
+        SolidityType::Null
+    }
+}
+
+/// A `filter` predicate keeping the values that loosely equal `value`, see `SolidityType::loose_eq`.
+pub fn eq_loose<T: Into<SolidityType>>(value: T) -> impl Fn(&SolidityType) -> SolidityType {
+    let value: SolidityType = value.into();
+    move |item| SolidityType::from(item.loose_eq(&value))
+}
+
+/// A `filter` predicate keeping the addresses equal to `address`, however either side is written.
+pub fn eq_address<T: Into<SolidityType>>(address: T) -> impl Fn(&SolidityType) -> SolidityType {
+    eq_loose(address)
+}
+
+/// The keys of `new` whose values differ from `old`, compared with `SolidityType::loose_eq`.
+/// Nested structs are diffed recursively and everything else is compared as a whole, so a changed list comes out in full.
+/// Keys that were removed are left out, see `removed_keys`. Anything that isn't a pair of structs diffs to `new` if it changed.
+pub fn diff(old: &SolidityType, new: &SolidityType) -> SolidityType {
+    let (SolidityType::Struct(old_map), SolidityType::Struct(new_map)) = (old, new) else {
+        return if old.loose_eq(new) {
+            SolidityType::Struct(IndexMap::new())
+        } else {
+            new.clone()
+        };
+    };
+
+    let mut changes = IndexMap::new();
+    for (key, new_val) in new_map {
+        let old_val = old_map.get(key).unwrap_or(&SolidityType::Null);
+
+        let change = match (old_val, new_val) {
+            (SolidityType::Struct(_), SolidityType::Struct(_)) => {
+                Some(diff(old_val, new_val)).filter(|change| !change.changed_keys().is_empty())
+            }
+            _ => (!old_val.loose_eq(new_val)).then(|| new_val.clone()),
+        };
+        if let Some(change) = change {
+            changes.insert(key.clone(), change);
+        }
+    }
+
+    SolidityType::Struct(changes)
+}
+
+/// The keys of `old` that are gone from `new`, dotted like `changed_keys`, since `diff` leaves them out.
+/// Nested structs are checked recursively, anything that isn't a pair of structs has nothing removed.
+pub fn removed_keys(old: &SolidityType, new: &SolidityType) -> Vec<String> {
+    let (SolidityType::Struct(old_map), SolidityType::Struct(new_map)) = (old, new) else {
+        return Vec::new();
+    };
+
+    let mut keys: Vec<String> = old_map
+        .iter()
+        .flat_map(|(key, old_val)| match new_map.get(key) {
+            None => vec![key.clone()],
+            Some(new_val) => removed_keys(old_val, new_val)
+                .into_iter()
+                .map(|nested| format!("{key}.{nested}"))
+                .collect(),
+        })
+        .collect();
+    keys.sort();
+    keys
+}
+
+/// Parses a string into the scalar `variant` named, ie `parse_sol_type("Uint", "0x2a")`, see `sol_type!`.
+/// Uints, ints and enums take decimal or 0x hex, booleans take "true", "false", "1" or "0",
+/// and byte arrays take 0x hex of any even length. Fixed bytes are sized by the bytes given.
+pub fn parse_sol_type(variant: &str, input: &str) -> Result<SolidityType, SolError> {
+    let failure = |expected: &'static str| SolError::ParseFailure {
+        input: input.to_string(),
+        expected,
+    };
+
+    match variant {
+        "Boolean" => match input {
+            "true" | "1" => Ok(true.into()),
+            "false" | "0" => Ok(false.into()),
+            _ => Err(failure("a boolean")),
+        },
+        "Enum" => Ok(SolidityType::Enum(
+            input.parse().map_err(|_| failure("an enum"))?,
+        )),
+        "Uint" => Ok(SolidityType::Uint(
+            input.parse().map_err(|_| failure("a uint"))?,
+        )),
+        "Int" => Ok(SolidityType::Int(
+            input.parse().map_err(|_| failure("an int"))?,
+        )),
+        "Address" => Ok(SolidityType::Address(
+            input.parse().map_err(|_| failure("an address"))?,
+        )),
+        "ByteArray" => Ok(SolidityType::ByteArray(
+            parse_hex_bytes(input).ok_or_else(|| failure("a byte array"))?,
+        )),
+        "FixedArray" => Ok(SolidityType::FixedArray(
+            input.parse().map_err(|_| failure("a bytes32"))?,
+        )),
+        "FixedBytes" => match parse_hex_bytes(input) {
+            Some(bytes) if (1..32).contains(&bytes.len()) => {
+                Ok(SolidityType::FixedBytes(bytes.len(), bytes))
+            }
+            _ => Err(failure("fixed bytes")),
+        },
+        "String" => Ok(SolidityType::String(input.to_string())),
+        _ => Err(SolError::ParseFailure {
+            input: variant.to_string(),
+            expected: "a variant that can be parsed from a string",
+        }),
+    }
+}
+
+/// 0x prefixed hex of an even length.
+fn parse_hex_bytes(input: &str) -> Option<Bytes> {
+    let hex = input.strip_prefix("0x")?;
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    hex.parse().ok()
+}
+
+pub fn format_hex(input: &[u8]) -> String {
+    format!("0x{}", Hex(input).to_string())
+}
+
+/// Checks that a mixed case address carries a valid EIP-55 checksum, ie when ingesting external json.
+/// All lowercase or all uppercase addresses have no checksum to validate, so they return false.
+pub fn validate_checksum(address: &str) -> bool {
+    Address::parse_checksummed(address, None).is_ok()
+}
+
+impl_from!(Address, Address);
+impl_try_to!(Address, Address, "make an address out of");
+
+impl_from!(String, String);
+impl_try_to!(String, String, "make a string out of");
+
+impl_from!(U1, Boolean);
+
+impl_from!(alloy_primitives::U256, Uint);
+impl_try_to!(alloy_primitives::U256, Uint, "make a uint out of");
+
+impl_from!(I256, Int);
+impl_try_to!(I256, Int, "make an int out of");
+
+impl_from!(Bytes, ByteArray);
+impl_try_to!(Bytes, ByteArray, "make bytes out of");
+
+impl_from!(alloy_primitives::B256, FixedArray);
+impl_try_to!(alloy_primitives::B256, FixedArray, "make a bytes32 out of");
+
+// bytes32 is a FixedArray, see above
+macro_rules! impl_from_fixed_bytes {
+    ($($size: literal),*) => {
+        $(impl From<alloy_primitives::FixedBytes<$size>> for SolidityType {
+            fn from(val: alloy_primitives::FixedBytes<$size>) -> Self {
+                SolidityType::FixedBytes($size, Bytes::copy_from_slice(val.as_slice()))
+            }
+        })*
+    };
+}
+
+impl_from_fixed_bytes!(4, 8, 16, 20);
+
+impl From<Vec<u8>> for SolidityType {
+    fn from(value: Vec<u8>) -> Self {
+        if value.len() == 32 {
+            SolidityType::FixedArray(alloy_primitives::B256::from_slice(&value))
+        } else {
+            SolidityType::ByteArray(Bytes::copy_from_slice(&value))
+        }
+    }
+}
+
+fn proto_kind(kind: Kind) -> ProtoValue {
+    ProtoValue { kind: Some(kind) }
+}
+
+fn proto_string(value: String) -> ProtoValue {
+    proto_kind(Kind::StringValue(value))
+}
+
+/// Proto numbers are doubles, anything past 2^53 is written as a string so it doesn't lose precision.
+fn proto_number(value: usize) -> ProtoValue {
+    if value as u64 > MAX_SAFE_INTEGER {
+        proto_string(value.to_string())
+    } else {
+        proto_kind(Kind::NumberValue(value as f64))
+    }
+}
+
+fn proto_list(values: Vec<ProtoValue>) -> ProtoValue {
+    proto_kind(Kind::ListValue(ListValue { values }))
+}
+
+fn proto_str(value: &ProtoValue) -> Option<&str> {
+    match value.kind.as_ref()? {
+        Kind::StringValue(value) => Some(value),
+        _ => None,
+    }
+}
+
+fn proto_items(value: &ProtoValue) -> Option<&[ProtoValue]> {
+    match value.kind.as_ref()? {
+        Kind::ListValue(list) => Some(&list.values),
+        _ => None,
+    }
+}
+
+/// A size written by `proto_number`.
+fn proto_size(value: &ProtoValue) -> Option<usize> {
+    match value.kind.as_ref()? {
+        Kind::NumberValue(size) if size.fract() == 0.0 && *size >= 0.0 => Some(*size as usize),
+        Kind::StringValue(size) => size.parse().ok(),
+        _ => None,
+    }
+}
+
+fn proto_tagged(value: &ProtoValue) -> Option<SolidityType> {
+    match value.kind.as_ref()? {
+        Kind::StructValue(tagged) => SolidityType::from_tagged_struct(tagged),
+        _ => None,
+    }
+}
+
+fn proto_tagged_items(value: &ProtoValue) -> Option<Vec<SolidityType>> {
+    proto_items(value)?.iter().map(proto_tagged).collect()
+}
+
+/// The largest integer a double holds exactly.
+const MAX_SAFE_INTEGER: u64 = 1 << 53;
+
+/// Converts a protobuf value into json structurally, for guessing values that aren't in the tagged form.
+/// Whole numbers a double holds exactly become json integers, so they're guessed as uints and ints rather than floats.
+fn proto_to_json(value: &ProtoValue) -> Value {
+    match &value.kind {
+        None | Some(Kind::NullValue(_)) => Value::Null,
+        Some(Kind::NumberValue(num))
+            if num.fract() == 0.0 && num.abs() <= MAX_SAFE_INTEGER as f64 =>
+        {
+            if *num >= 0.0 {
+                Value::from(*num as u64)
+            } else {
+                Value::from(*num as i64)
+            }
+        }
+        Some(Kind::NumberValue(num)) => Value::from(*num),
+        Some(Kind::StringValue(string)) => Value::String(string.clone()),
+        Some(Kind::BoolValue(flag)) => Value::Bool(*flag),
+        Some(Kind::StructValue(value)) => proto_struct_to_json(value),
+        Some(Kind::ListValue(list)) => {
+            Value::Array(list.values.iter().map(proto_to_json).collect())
+        }
+    }
+}
+
+fn proto_struct_to_json(value: &Struct) -> Value {
+    Value::Object(
+        value
+            .fields
+            .iter()
+            .map(|(key, value)| (key.clone(), proto_to_json(value)))
+            .collect(),
+    )
+}
+
+/// Converts an ABI decoded value without guessing, so bytes32 stays FixedArray and struct fields keep their names.
+/// Ints of any size become Int, see `to_dyn_value` for the way back.
+impl From<DynSolValue> for SolidityType {
+    fn from(value: DynSolValue) -> Self {
+        match value {
+            DynSolValue::Bool(val) => val.into(),
+            DynSolValue::Int(val, _) => SolidityType::Int(val),
+            DynSolValue::Uint(val, _) => SolidityType::Uint(val),
+            DynSolValue::FixedBytes(val, 32) => SolidityType::FixedArray(val),
+            DynSolValue::FixedBytes(val, size) => {
+                SolidityType::FixedBytes(size, Bytes::copy_from_slice(&val[..size]))
+            }
+            DynSolValue::Address(val) => SolidityType::Address(val),
+            DynSolValue::Function(val) => {
+                SolidityType::ByteArray(Bytes::copy_from_slice(val.as_slice()))
+            }
+            DynSolValue::Bytes(val) => SolidityType::ByteArray(val.into()),
+            DynSolValue::String(val) => SolidityType::String(val),
+            DynSolValue::Array(vals) => {
+                SolidityType::List(vals.into_iter().map(SolidityType::from).collect())
+            }
+            DynSolValue::FixedArray(vals) => SolidityType::SizedList(
+                vals.len(),
+                vals.into_iter().map(SolidityType::from).collect(),
+            ),
+            DynSolValue::Tuple(vals) => {
+                SolidityType::Tuple(vals.into_iter().map(SolidityType::from).collect())
+            }
+            DynSolValue::CustomStruct {
+                prop_names, tuple, ..
+            } => SolidityType::Struct(
+                prop_names
+                    .into_iter()
+                    .zip(tuple.into_iter().map(SolidityType::from))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<bool> for SolidityType {
+    fn from(value: bool) -> Self {
+        SolidityType::Boolean(U1::from(value))
+    }
+}
+
+impl TryFrom<SolidityType> for bool {
+    type Error = SolError;
+
+    fn try_from(value: SolidityType) -> Result<Self, Self::Error> {
+        if let SolidityType::Boolean(val) = value {
+            let value: u8 = val.to();
+            Ok(value != 0)
+        } else {
+            Err(SolError::WrongVariant {
+                operation: "make a bool out of",
+                found: value.kind(),
+            })
+        }
+    }
+}
+
+// Binary Op Trait Implementations
+impl<T> Add<T> for SolidityType
+where
+    SolidityType: From<T>,
+{
+    type Output = Self;
+
+    fn add(self, rhs: T) -> Self::Output {
+        self.try_add(rhs).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T> Sub<T> for SolidityType
+where
+    SolidityType: From<T>,
+{
+    type Output = Self;
+
+    fn sub(self, rhs: T) -> Self::Output {
+        self.try_sub(rhs).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T> Mul<T> for SolidityType
+where
+    SolidityType: From<T>,
+{
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        self.try_mul(rhs).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T> Div<T> for SolidityType
+where
+    SolidityType: From<T>,
+{
+    type Output = Self;
+
+    fn div(self, rhs: T) -> Self::Output {
+        self.try_div(rhs).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+/// Values of the same variant compare structurally, lists, tuples and structs member by member.
+/// Values of different variants are never equal, not even an int and a uint of the same value, see `loose_eq` for that.
+impl<T> PartialEq<T> for SolidityType
+where
+    SolidityType: From<T>,
+    T: Clone + Debug,
+{
+    fn eq(&self, other: &T) -> bool {
+        // TODO This isn't the most performant, but I don't think it's the end of the world
+        let rhs: SolidityType = Into::into(other.clone());
+        self.strict_eq(&rhs)
+    }
+}
+
+/// Orders values like `try_cmp`, anything it can't compare is None rather than a panic.
+/// To stay consistent with `==`, an int and a uint of the same value are None rather than Equal,
+/// while an int and a uint of different values still order by value.
+impl<T> PartialOrd<T> for SolidityType
+where
+    SolidityType: From<T>,
+    T: Clone + Debug,
+{
+    fn partial_cmp(&self, other: &T) -> Option<std::cmp::Ordering> {
+        // TODO This isn't the most performant, but I don't think it's the end of the world
+        let rhs: SolidityType = Into::into(other.clone());
+        match self.try_cmp(&rhs).ok()? {
+            std::cmp::Ordering::Equal if !self.strict_eq(&rhs) => None,
+            ordering => Some(ordering),
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use alloy_primitives::address;
 
-    use super::*;
+    use super::*;
+    use crate::try_sol_type;
+
+    #[test]
+    fn test_serializations() {
+        let solidity_value = SolidityType::from(false);
+        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
+        println!("Bool: {}", &as_value);
+        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
+        println!("Bool Deserialized: {:?}", &from_value);
+
+        let solidity_value = SolidityType::Enum(U8::from(2));
+        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
+        println!("Enum: {}", &as_value);
+        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
+        println!("Enum Deserialized: {:?}", &from_value);
+
+        let solidity_value = SolidityType::Uint(U256::from(42069));
+        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
+        println!("Uint: {}", &as_value);
+        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
+        println!("Uint Deserialized: {:?}", &from_value);
+
+        let solidity_value =
+            SolidityType::Address(address!("000000000000Ad05Ccc4F10045630fb830B95127"));
+        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
+        println!("Address: {}", &as_value);
+        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
+        println!("Address Deserialized: {:?}", &from_value);
+
+        // TODO BYTES!
+        // TODO FIXEDARRAY!
+
+        let solidity_value = SolidityType::from("Hello World!".to_string());
+        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
+        println!("String: {}", &as_value);
+        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
+        println!("String Deserialized: {:?}", &from_value);
+
+        let solidity_value = SolidityType::Tuple(vec![
+            SolidityType::from(false),
+            SolidityType::Address(address!("000000000000Ad05Ccc4F10045630fb830B95127")),
+        ]);
+        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
+        println!("Tuple: {}", &as_value);
+        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
+        println!("Tuple Deserialized: {:?}", &from_value);
+
+        let solidity_value = SolidityType::List(vec![
+            SolidityType::from(false),
+            SolidityType::Address(address!("000000000000Ad05Ccc4F10045630fb830B95127")),
+        ]);
+        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
+        println!("List: {}", &as_value);
+        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
+        println!("List Deserialized: {:?}", &from_value);
+
+        let mut struct_map: IndexMap<String, SolidityType> = IndexMap::new();
+        struct_map.insert("bool".to_string(), SolidityType::from(false));
+        struct_map.insert(
+            "addr".to_string(),
+            SolidityType::Address(address!("000000000000Ad05Ccc4F10045630fb830B95127")),
+        );
+        struct_map.insert(
+            "foo".to_string(),
+            SolidityType::List(vec![
+                SolidityType::from(false),
+                SolidityType::Address(address!("000000000000Ad05Ccc4F10045630fb830B95127")),
+            ]),
+        );
+
+        let solidity_value = SolidityType::Struct(struct_map);
+        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
+        println!("Map: {}", &as_value);
+        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
+        println!("Map Deserialized: {:?}", &from_value);
+    }
+
+    #[test]
+    fn test_sum_field() {
+        let transfer = |amount: SolidityType| map_literal! { "amount"; amount };
+        let transfers = SolidityType::List(vec![
+            transfer(SolidityType::Uint(U256::from(10))),
+            transfer(SolidityType::Uint(U256::from(5))),
+            map_literal! { "memo"; SolidityType::String("no amount".to_string()) },
+        ]);
+        assert!(transfers
+            .sum_field("amount")
+            .strict_eq(&SolidityType::Uint(U256::from(15))));
+
+        // an int anywhere makes the whole sum an int
+        let deltas = SolidityType::List(vec![
+            SolidityType::Uint(U256::from(10)),
+            SolidityType::Int(I256::try_from(-15).unwrap()),
+        ]);
+        assert_eq!(deltas.sum(), I256::try_from(-5).unwrap());
+        assert_eq!(deltas.min(), I256::try_from(-15).unwrap());
+
+        assert!(SolidityType::Null
+            .sum()
+            .strict_eq(&SolidityType::Uint(U256::ZERO)));
+        assert!(matches!(SolidityType::Null.max(), SolidityType::Null));
+        assert!(matches!(
+            SolidityType::List(vec![SolidityType::Null]).min(),
+            SolidityType::Null
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Tried to sum a string value at index 1!")]
+    fn test_sum_names_the_index() {
+        let list = SolidityType::List(vec![
+            SolidityType::Uint(U256::from(1)),
+            SolidityType::String("2".to_string()),
+        ]);
+        list.sum();
+    }
+
+    #[test]
+    #[should_panic(expected = "At index 2.")]
+    fn test_max_names_the_index() {
+        let list = SolidityType::List(vec![
+            SolidityType::Uint(U256::from(1)),
+            SolidityType::Uint(U256::from(2)),
+            SolidityType::Address(Address::ZERO),
+        ]);
+        list.max();
+    }
+
+    #[test]
+    fn test_sum() {
+        let list = SolidityType::List(vec![
+            SolidityType::Uint(U256::from(1)),
+            SolidityType::Null,
+            SolidityType::Uint(U256::from(2)),
+            SolidityType::Uint(U256::from(3)),
+        ]);
+
+        assert!(list.sum().strict_eq(&SolidityType::Uint(U256::from(6))));
+        assert_eq!(list.min(), U256::from(1));
+        assert_eq!(list.max(), U256::from(3));
+        assert_eq!(list.len(), 4);
+        assert!(list.first().strict_eq(&SolidityType::Uint(U256::from(1))));
+        assert!(list.last().strict_eq(&SolidityType::Uint(U256::from(3))));
+        assert!(list.contains(&SolidityType::Uint(U256::from(2))));
+        assert!(!list.contains(&SolidityType::Uint(U256::from(4))));
+        assert!(!list.contains(&SolidityType::from("2".to_string())));
+
+        let max = crate::reduce!(list, SolidityType::Null, |acc, item| match (&acc, item) {
+            (SolidityType::Uint(lh), SolidityType::Uint(rh)) if lh >= rh => acc,
+            (_, SolidityType::Null) => acc,
+            _ => item.clone(),
+        });
+        assert!(max.strict_eq(&SolidityType::Uint(U256::from(3))));
+    }
+
+    fn nested_fixture() -> SolidityType {
+        let token = map_literal!(
+            "address"; SolidityType::Address(address!("000000000000Ad05Ccc4F10045630fb830B95127")),
+            "decimals"; SolidityType::Uint(U256::from(18))
+        );
+        let pool = map_literal!(
+            "token0"; token,
+            "fee"; SolidityType::Uint(U256::from(3000))
+        );
+        let swaps = SolidityType::List(vec![
+            map_literal!("amount"; SolidityType::Uint(U256::from(1))),
+            map_literal!("amount"; SolidityType::Uint(U256::from(2))),
+        ]);
+
+        map_literal!(
+            "pool"; pool,
+            "reserves"; SolidityType::Tuple(vec![
+                SolidityType::Uint(U256::from(10)),
+                SolidityType::Uint(U256::from(20)),
+            ]),
+            "swaps"; swaps
+        )
+    }
+
+    #[test]
+    fn test_flatten() {
+        let nested = nested_fixture();
+
+        let flat = nested.flatten(".");
+        assert_eq!(flat.len(), 6);
+        assert!(flat
+            .get("pool.token0.decimals")
+            .strict_eq(&SolidityType::Uint(U256::from(18))));
+        assert!(flat
+            .get("reserves.1")
+            .strict_eq(&SolidityType::Uint(U256::from(20))));
+        assert!(flat.get("swaps").strict_eq(&nested.get("swaps")));
+        assert!(flat.unflatten(".").strict_eq(&nested));
+
+        let exploded = nested.flatten_lists("/");
+        assert_eq!(exploded.len(), 7);
+        assert!(exploded
+            .get("swaps/1/amount")
+            .strict_eq(&SolidityType::Uint(U256::from(2))));
+
+        let rebuilt = exploded.unflatten("/");
+        assert!(rebuilt.get("pool").strict_eq(&nested.get("pool")));
+        assert!(matches!(rebuilt.get("swaps"), SolidityType::Tuple(_)));
+        assert!(rebuilt
+            .get("swaps")
+            .get("0")
+            .get("amount")
+            .strict_eq(&SolidityType::Uint(U256::from(1))));
+    }
+
+    #[test]
+    fn test_to_proto_struct() {
+        let empty = map_literal!("foo"; SolidityType::Null);
+        assert!(empty.to_proto_struct().is_none());
+
+        let value = map_literal!(
+            "foo"; SolidityType::Uint(U256::from(1)),
+            "bar"; SolidityType::Null
+        );
+        let proto = value.to_proto_struct().unwrap();
+        let round_trip = SolidityType::from(proto);
+        assert_eq!(round_trip.len(), 1);
+        assert!(round_trip
+            .get("foo")
+            .strict_eq(&SolidityType::Uint(U256::from(1))));
+
+        // built directly, but the same struct serde_json gives
+        let every_variant = map_literal!(
+            "bool"; SolidityType::from(true),
+            "enum"; SolidityType::Enum(U8::from(2)),
+            "uint"; SolidityType::Uint(U256::from(255)),
+            "zero"; SolidityType::Uint(U256::ZERO),
+            "int"; SolidityType::Int(I256::try_from(-5).unwrap()),
+            "address"; SolidityType::Address(address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2")),
+            "bytes"; SolidityType::ByteArray(Bytes::from(vec![0xde, 0xad])),
+            "bytes32"; SolidityType::FixedArray(alloy_primitives::B256::from([1; 32])),
+            "bytes4"; SolidityType::FixedBytes(4, Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbb])),
+            "string"; SolidityType::String("gm".to_string()),
+            "tuple"; SolidityType::Tuple(vec![SolidityType::Uint(U256::from(1))]),
+            "list"; SolidityType::List(vec![SolidityType::from(false)]),
+            "sized"; SolidityType::SizedList(1, vec![SolidityType::String("a".to_string())]),
+            "struct"; map_literal!("inner"; SolidityType::Uint(U256::from(7)))
+        );
+        let via_serde: Struct =
+            serde_json::from_value(serde_json::to_value(&every_variant).unwrap()).unwrap();
+        let direct = every_variant.to_proto_struct().unwrap();
+        assert_eq!(
+            serde_json::to_value(&direct).unwrap(),
+            serde_json::to_value(&via_serde).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_from_json_lossy() {
+        use serde_json::json;
+
+        let tagged =
+            serde_json::to_value(map_literal!("fee"; SolidityType::Uint(U256::from(500)))).unwrap();
+        let value = SolidityType::from(tagged.clone());
+        assert_eq!(value.get("fee"), U256::from(500));
+        assert!(SolidityType::try_from_tagged_json(tagged)
+            .unwrap()
+            .strict_eq(&value));
+
+        // plain json is guessed rather than panicking
+        let plain = json!({"pool": "0x1f98431c8ad98523631ae4a59f267346ea31f984", "fee": 500});
+        let value = SolidityType::from(plain.clone());
+        assert!(matches!(value.get("pool"), SolidityType::Address(_)));
+        assert_eq!(value.get("fee"), U256::from(500));
+        assert!(SolidityType::from_json_lossy(plain.clone()).strict_eq(&value));
+        assert!(SolidityType::from(Some(plain)).strict_eq(&value));
+        assert!(matches!(
+            SolidityType::from(None::<Value>),
+            SolidityType::Null
+        ));
+
+        // a tag this crate doesn't write is guessed as plain json, but is an error when it has to be tagged
+        let unknown =
+            json!({"type": "struct", "value": {"fee": {"type": "float", "value": "0.3"}}});
+        let value = SolidityType::from(unknown.clone());
+        assert!(matches!(value.get("type"), SolidityType::String(tag) if tag == "struct"));
+        let err = SolidityType::try_from_tagged_json(unknown).unwrap_err();
+        assert!(err.to_string().contains("float"), "{err}");
+        assert!(
+            err.field
+                .as_deref()
+                .map_or(false, |field| field.contains("fee")),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn test_struct_order() {
+        let build = || {
+            map_literal!(
+                "zeta"; SolidityType::Uint(U256::from(1)),
+                "alpha"; SolidityType::from(true),
+                "mid"; map_literal!("b"; SolidityType::Uint(U256::from(2)), "a"; SolidityType::Uint(U256::from(3)))
+            )
+        };
+
+        let first = serde_json::to_string(&build()).unwrap();
+        let second = serde_json::to_string(&build()).unwrap();
+        assert_eq!(first, second);
+
+        // keys keep the order they were inserted in, through serde too
+        let SolidityType::Struct(map) = serde_json::from_str::<SolidityType>(&first).unwrap()
+        else {
+            panic!("Expected a struct");
+        };
+        assert_eq!(map.keys().collect::<Vec<_>>(), ["zeta", "alpha", "mid"]);
+        let SolidityType::Struct(inner) = &map["mid"] else {
+            panic!("Expected a struct");
+        };
+        assert_eq!(inner.keys().collect::<Vec<_>>(), ["b", "a"]);
+    }
+
+    #[test]
+    fn test_parse_sol_type() {
+        assert_eq!(sol_type!(Uint, "42"), U256::from(42));
+        assert_eq!(sol_type!(Uint, "0x2a"), U256::from(42));
+        assert_eq!(
+            sol_type!(Int, "-42"),
+            SolidityType::Int(I256::try_from(-42).unwrap())
+        );
+        assert_eq!(sol_type!(Enum, "2"), SolidityType::Enum(U8::from(2)));
+        for (input, expected) in [("true", true), ("1", true), ("false", false), ("0", false)] {
+            assert_eq!(sol_type!(Boolean, input), SolidityType::from(expected));
+        }
+        assert_eq!(
+            sol_type!(ByteArray, "0xdeadbeef"),
+            SolidityType::ByteArray(Bytes::from(vec![0xde, 0xad, 0xbe, 0xef]))
+        );
+        assert_eq!(
+            sol_type!(ByteArray, "0x"),
+            SolidityType::ByteArray(Bytes::new())
+        );
+        assert_eq!(
+            sol_type!(FixedBytes, "0xa9059cbb"),
+            SolidityType::FixedBytes(4, Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbb]))
+        );
+        assert_eq!(
+            sol_type!(String, "gm".to_string()),
+            SolidityType::String("gm".to_string())
+        );
+
+        assert!(try_sol_type!(Uint, "soon").is_none());
+        assert!(try_sol_type!(Boolean, "yes").is_none());
+        assert!(try_sol_type!(ByteArray, "0xabc").is_none());
+        assert!(try_sol_type!(ByteArray, "abcd").is_none());
+        assert!(try_sol_type!(Address, "0x1f98").is_none());
+        assert!(try_sol_type!(Struct, "{}").is_none());
+        assert!(try_sol_type!(Address, "0x1f98431c8ad98523631ae4a59f267346ea31f984").is_some());
+
+        assert_eq!(
+            parse_sol_type("Uint", "soon").unwrap_err().to_string(),
+            "Couldn't parse `soon` into a uint!"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Couldn't parse `0x1f98` into an address!")]
+    fn test_sol_type_panics_with_input() {
+        sol_type!(Address, "0x1f98");
+    }
+
+    #[test]
+    fn test_from_tagged_struct() {
+        use serde_json::json;
+
+        let value = map_literal!(
+            "int"; SolidityType::Int(I256::try_from(-5).unwrap()),
+            "bytes4"; SolidityType::FixedBytes(4, Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbb])),
+            "pools"; SolidityType::List(vec![
+                map_literal!(
+                    "pool"; SolidityType::Address(address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2")),
+                    "reserves"; SolidityType::SizedList(2, vec![
+                        SolidityType::Uint(U256::MAX),
+                        SolidityType::Uint(U256::ZERO),
+                    ])
+                ),
+                map_literal!("pool"; SolidityType::Tuple(vec![SolidityType::from(true)]))
+            ])
+        );
+
+        // reads back the same value the old serde_json path did
+        let proto = value.to_proto_struct().unwrap();
+        let direct = SolidityType::from_tagged_struct(&proto).unwrap();
+        let via_serde: SolidityType =
+            serde_json::from_value(serde_json::to_value(&proto).unwrap()).unwrap();
+        assert!(direct.strict_eq(&value));
+        assert!(direct.strict_eq(&via_serde));
+        assert!(SolidityType::from(proto).strict_eq(&value));
+
+        // decimal uints and sizes written as strings are read too
+        let decimal: Struct = serde_json::from_value(json!({
+            "type": "sizedList",
+            "value": ["2", [{"type": "uint", "value": "100"}, {"type": "uint", "value": "0x64"}]]
+        }))
+        .unwrap();
+        let expected = SolidityType::SizedList(2, vec![SolidityType::Uint(U256::from(100)); 2]);
+        assert!(SolidityType::from(decimal).strict_eq(&expected));
+
+        // anything that isn't tagged is guessed, with whole numbers as uints rather than floats
+        let untagged: Struct = serde_json::from_value(json!({"amount": 5, "name": "gm"})).unwrap();
+        assert!(SolidityType::from_tagged_struct(&untagged).is_none());
+        let guessed = SolidityType::from(untagged);
+        assert_eq!(guessed.get("amount"), U256::from(5));
+        assert!(matches!(guessed.get("name"), SolidityType::String(name) if name == "gm"));
+
+        assert!(matches!(
+            SolidityType::Null.try_to_tagged_struct(),
+            Err(SolError::WrongVariant { .. })
+        ));
+    }
+
+    #[test]
+    fn test_proto_number_precision() {
+        use serde_json::json;
+
+        assert!(matches!(proto_number(3).kind, Some(Kind::NumberValue(size)) if size == 3.0));
+        let big = (MAX_SAFE_INTEGER + 1) as usize;
+        assert!(
+            matches!(proto_number(big).kind, Some(Kind::StringValue(size)) if size == big.to_string())
+        );
+        assert_eq!(proto_size(&proto_number(big)), Some(big));
+        assert_eq!(
+            proto_to_json(&proto_kind(Kind::NumberValue(-2.0))),
+            json!(-2)
+        );
+        assert_eq!(
+            proto_to_json(&proto_kind(Kind::NumberValue(1.5))),
+            json!(1.5)
+        );
+    }
+
+    #[test]
+    fn test_composite_to_string() {
+        let inner = map_literal!(
+            "addr"; SolidityType::Address(address!("000000000000Ad05Ccc4F10045630fb830B95127")),
+            "flag"; SolidityType::from(true)
+        );
+        let value = map_literal!(
+            "b"; SolidityType::Uint(U256::from(255)),
+            "a"; inner,
+            "list"; SolidityType::List(vec![
+                SolidityType::from("x".to_string()),
+                SolidityType::Tuple(vec![SolidityType::Uint(U256::from(1))]),
+            ])
+        );
+
+        let expected = r#"{"a":{"addr":"0x000000000000ad05ccc4f10045630fb830b95127","flag":true},"b":"0xff","list":["x",["0x1"]]}"#;
+        assert_eq!(value.to_string(), expected);
+        assert_eq!(value.clone().to_string(), value.to_string());
+
+        // scalars keep their existing rendering
+        assert_eq!(SolidityType::Uint(U256::from(255)).to_string(), "255");
+
+        // the same entries inserted in a different order render the same
+        let mut reordered = SolidityType::Struct(IndexMap::new());
+        for key in ["list", "a", "b"] {
+            reordered.insert(key, value.get(key));
+        }
+        assert_eq!(reordered.to_string(), expected);
+        assert_eq!(reordered.to_key_string(), value.to_key_string());
+    }
+
+    #[test]
+    fn test_to_key_string() {
+        let pool = SolidityType::Address(address!("000000000000Ad05Ccc4F10045630fb830B95127"));
+        let key = SolidityType::Tuple(vec![
+            pool.clone(),
+            SolidityType::Uint(U256::from(1_681_336_800)),
+        ]);
+        assert_eq!(
+            key.to_key_string(),
+            "0x000000000000ad05ccc4f10045630fb830b95127:1681336800"
+        );
+        assert_eq!(pool.to_key_string(), pool.to_string());
+
+        let string = |val: &str| SolidityType::String(val.to_string());
+        let structs = map_literal! { "b"; string("2"), "a"; string("1") };
+        assert_eq!(structs.to_key_string(), "1:2");
+
+        // the separators in strings are escaped, so only the one element list keys the same as its value
+        let keys = [
+            SolidityType::List(vec![string("a"), string("b")]),
+            string("a:b"),
+            SolidityType::List(vec![string("a:b")]),
+            SolidityType::List(vec![SolidityType::List(vec![string("a"), string("b")])]),
+            SolidityType::List(vec![string("(a"), string("b)")]),
+        ]
+        .map(|value| value.to_key_string());
+        assert_eq!(keys, ["a:b", "a\\:b", "a\\:b", "(a:b)", "\\(a:b\\)"]);
+
+        // the variants and field names aren't part of the key
+        let keys = [
+            SolidityType::List(vec![string("1"), string("2")]),
+            SolidityType::Tuple(vec![string("1"), string("2")]),
+            SolidityType::SizedList(2, vec![string("1"), string("2")]),
+            map_literal! { "x"; string("1"), "y"; string("2") },
+        ]
+        .map(|value| value.to_key_string());
+        assert_eq!(keys, ["1:2"; 4]);
+    }
+
+    #[test]
+    fn test_bigint_round_trip() {
+        let values = [
+            U256::from(1) << 255,
+            (U256::from(1) << 255) + U256::from(1),
+            (U256::from(1) << 255) - U256::from(1),
+            U256::MAX,
+            U256::ZERO,
+        ];
+
+        for value in values {
+            let big_int = BigInt::try_from(SolidityType::Uint(value)).unwrap();
+            assert_eq!(big_int.to_string(), value.to_string());
+            assert_eq!(SolidityType::from(big_int), value);
+        }
+
+        let too_big = BigInt::from_unsigned_bytes_be(&[1; 33]);
+        assert!(matches!(SolidityType::from(too_big), SolidityType::Null));
+
+        let negative = BigInt::from_signed_bytes_be(&[0xff]);
+        let minus_one = I256::try_from(-1).unwrap();
+        assert_eq!(SolidityType::from(negative), minus_one);
+        for value in [I256::MIN, minus_one, I256::MAX] {
+            let big_int = BigInt::try_from(SolidityType::Int(value)).unwrap();
+            assert_eq!(big_int.to_string(), value.to_string());
+            assert_eq!(SolidityType::from(big_int), value);
+        }
+        let too_small = BigInt::from_signed_bytes_be(&[0x80; 33]);
+        assert!(matches!(SolidityType::from(too_small), SolidityType::Null));
+
+        assert!(matches!(
+            BigInt::try_from(SolidityType::from(true)),
+            Err(SolError::WrongVariant {
+                found: "boolean",
+                ..
+            })
+        ));
+        assert!(matches!(
+            BigDecimal::try_from(SolidityType::String("1.2.3".to_string())),
+            Err(SolError::ParseFailure { input, .. }) if input == "1.2.3"
+        ));
+    }
+
+    #[test]
+    fn test_scalar_serde_round_trip() {
+        let big_int: BigInt = "123456789012345678901234567890".parse().unwrap();
+        let value = SolidityType::from(big_int.clone());
+        let json = serde_json::to_string(&value).unwrap();
+        let round_trip: SolidityType = serde_json::from_str(&json).unwrap();
+        assert_eq!(BigInt::try_from(round_trip).unwrap(), big_int);
+
+        let negative: BigInt = "-123456789012345678901234567890".parse().unwrap();
+        let json = serde_json::to_string(&SolidityType::from(negative.clone())).unwrap();
+        let round_trip: SolidityType = serde_json::from_str(&json).unwrap();
+        assert_eq!(BigInt::try_from(round_trip).unwrap(), negative);
+
+        let decimal: BigDecimal = "1234567.890123456789012345".parse().unwrap();
+        let json = serde_json::to_string(&SolidityType::from(decimal.clone())).unwrap();
+        let round_trip: SolidityType = serde_json::from_str(&json).unwrap();
+        assert_eq!(BigDecimal::try_from(round_trip).unwrap(), decimal);
+    }
+
+    #[test]
+    fn test_to_bigdecimal() {
+        let decimal = |val: &str| val.parse::<BigDecimal>().unwrap();
+        let raw = SolidityType::Uint("1234567890123456789012345".parse().unwrap());
+        assert_eq!(raw.to_bigdecimal(18), decimal("1234567.890123456789012345"));
+        assert_eq!(raw.to_bigdecimal(0), decimal("1234567890123456789012345"));
+        assert_eq!(
+            SolidityType::Uint(U256::from(5)).to_bigdecimal(18),
+            decimal("0.000000000000000005")
+        );
+        assert_eq!(
+            SolidityType::Uint(U256::MAX).to_bigdecimal(18),
+            decimal(&format!("{}e-18", U256::MAX))
+        );
+
+        let int = SolidityType::Int(I256::try_from(-1_500_000).unwrap());
+        assert_eq!(int.to_bigdecimal(6), decimal("-1.5"));
+        assert!(matches!(
+            SolidityType::from(true).try_to_bigdecimal(6),
+            Err(SolError::WrongVariant { .. })
+        ));
+    }
+
+    #[test]
+    fn test_pb_bigint() {
+        let wei = PbBigInt {
+            bytes: vec![0x0d, 0xe0, 0xb6, 0xb3, 0xa7, 0x64, 0x00, 0x00],
+        };
+        assert_eq!(SolidityType::from(&wei), U256::from(10).pow(U256::from(18)));
+        assert_eq!(SolidityType::from(&PbBigInt::default()), U256::ZERO);
+
+        let too_wide = PbBigInt { bytes: vec![1; 33] };
+        assert!(matches!(SolidityType::from(&too_wide), SolidityType::Null));
+    }
+
+    #[test]
+    fn test_bigdecimal_conversion() {
+        let decimal: BigDecimal =
+            "57896044618658097711785492504343953926634992332820282019728792003956564819968.5"
+                .parse()
+                .unwrap();
+        let value = SolidityType::from(decimal.clone());
+        assert_eq!(BigDecimal::try_from(value).unwrap(), decimal);
+
+        let value = SolidityType::Uint(U256::MAX);
+        let decimal = BigDecimal::try_from(value).unwrap();
+        assert_eq!(decimal.to_string(), U256::MAX.to_string());
+    }
+
+    #[test]
+    fn test_map_preserves_variant() {
+        let tuple = SolidityType::Tuple(vec![
+            SolidityType::Uint(U256::from(1)),
+            SolidityType::Uint(U256::from(2)),
+        ]);
+
+        let doubled = tuple.map(|item| item.clone() * U256::from(2));
+        assert!(doubled.strict_eq(&SolidityType::Tuple(vec![
+            SolidityType::Uint(U256::from(2)),
+            SolidityType::Uint(U256::from(4)),
+        ])));
+
+        let filtered = tuple.filter(|item| SolidityType::from(item.clone() > U256::from(1)));
+        assert!(
+            filtered.strict_eq(&SolidityType::Tuple(vec![SolidityType::Uint(U256::from(
+                2
+            ))]))
+        );
+
+        let indexed = tuple.map_indexed(|index, item| item.clone() + U256::from(index));
+        assert!(indexed.last().strict_eq(&SolidityType::Uint(U256::from(3))));
+
+        let list = SolidityType::List(vec![
+            SolidityType::Uint(U256::from(1)),
+            SolidityType::Uint(U256::from(2)),
+        ]);
+        let strict = list.strict_map(|_| SolidityType::Null);
+        assert_eq!(strict.len(), 2);
+        assert!(matches!(
+            list.map(|_| SolidityType::Null),
+            SolidityType::Null
+        ));
+    }
+
+    #[test]
+    fn test_map_entries() {
+        let value = map_literal!(
+            "amount0"; SolidityType::Uint(U256::from(1)),
+            "amount1"; SolidityType::Uint(U256::from(2)),
+            "sender"; SolidityType::Address(address!("000000000000Ad05Ccc4F10045630fb830B95127"))
+        );
+
+        let amounts = value.map_entries(|key, value| {
+            if key.starts_with("amount") {
+                value.clone() * U256::from(10)
+            } else {
+                SolidityType::Null
+            }
+        });
+
+        assert_eq!(amounts.len(), 2);
+        assert!(amounts
+            .get("amount1")
+            .strict_eq(&SolidityType::Uint(U256::from(20))));
+        assert!(matches!(amounts.get("sender"), SolidityType::Null));
+    }
+
+    /// A corpus of json documents and the tagged serialization of what they're guessed as.
+    fn guess_corpus() -> Vec<(Value, Value)> {
+        use serde_json::json;
+
+        let word = format!("0x{}", "11".repeat(32));
+        let long_bytes = format!("0x{}", "22".repeat(34));
+
+        vec![
+            (json!(true), json!({"type": "boolean", "value": "0x1"})),
+            (json!(42), json!({"type": "uint", "value": "0x2a"})),
+            (json!("hello"), json!({"type": "string", "value": "hello"})),
+            (json!("0x1f"), json!({"type": "uint", "value": "0x1f"})),
+            (
+                json!("0x000000000000Ad05Ccc4F10045630fb830B95127"),
+                json!({"type": "address", "value": "0x000000000000ad05ccc4f10045630fb830b95127"}),
+            ),
+            (json!(word.clone()), json!({"type": "uint", "value": word})),
+            (
+                json!(long_bytes.clone()),
+                json!({"type": "byteArray", "value": long_bytes}),
+            ),
+            (
+                json!({"_0": "0x1", "_1": "hi"}),
+                json!({"type": "tuple", "value": [
+                    {"type": "uint", "value": "0x1"},
+                    {"type": "string", "value": "hi"}
+                ]}),
+            ),
+            (
+                json!({"_0": "0x1"}),
+                json!({"type": "tuple", "value": [{"type": "uint", "value": "0x1"}]}),
+            ),
+            (json!({}), json!({"type": "struct", "value": {}})),
+            (json!(-5), json!({"type": "int", "value": "-5"})),
+            (json!("-12"), json!({"type": "string", "value": "-12"})),
+            (
+                json!({"amount": 5, "memo": "gm", "flags": [true, false]}),
+                json!({"type": "struct", "value": {
+                    "amount": {"type": "uint", "value": "0x5"},
+                    "memo": {"type": "string", "value": "gm"},
+                    "flags": {"type": "list", "value": [
+                        {"type": "boolean", "value": "0x1"},
+                        {"type": "boolean", "value": "0x0"}
+                    ]}
+                }}),
+            ),
+            (
+                json!([{"_0": 1, "_1": 2}, {"_0": 3, "_1": 4}]),
+                json!({"type": "list", "value": [
+                    {"type": "tuple", "value": [
+                        {"type": "uint", "value": "0x1"},
+                        {"type": "uint", "value": "0x2"}
+                    ]},
+                    {"type": "tuple", "value": [
+                        {"type": "uint", "value": "0x3"},
+                        {"type": "uint", "value": "0x4"}
+                    ]}
+                ]}),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_guess_corpus() {
+        for (input, expected) in guess_corpus() {
+            let borrowed = SolidityType::guess_json_value(&input).unwrap();
+            let owned = SolidityType::guess_json_value(input.clone()).unwrap();
+
+            assert_eq!(
+                serde_json::to_value(&borrowed).unwrap(),
+                expected,
+                "{input}"
+            );
+            assert_eq!(serde_json::to_value(&owned).unwrap(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_tuple_keys() {
+        assert!(is_tuple_key("_0"));
+        assert!(is_tuple_key("_12"));
+        assert!(!is_tuple_key("_0x"));
+        assert!(!is_tuple_key("foo_1"));
+        assert!(!is_tuple_key("_"));
+        assert!(!is_tuple_key("0"));
+
+        let value = serde_json::json!({ "_0": "0x01", "_1": "hello" });
+        let guess = SolidityType::guess_json_value(&value).unwrap();
+        assert!(matches!(guess, SolidityType::Tuple(_)));
+
+        let value = serde_json::json!({ "_0x": "0x01", "foo_1": "hello" });
+        let guess = SolidityType::guess_json_value(&value).unwrap();
+        assert!(matches!(guess, SolidityType::Struct(_)));
+    }
+
+    #[test]
+    fn test_into_vec() {
+        let uints = vec![
+            SolidityType::Uint(U256::from(1)),
+            SolidityType::Uint(U256::from(2)),
+        ];
+        for value in [
+            SolidityType::List(uints.clone()),
+            SolidityType::Tuple(uints.clone()),
+            SolidityType::SizedList(2, uints.clone()),
+        ] {
+            let vals = value.into_vec().unwrap();
+            assert_eq!(vals.len(), 2);
+            assert_eq!(vals[1], U256::from(2));
+        }
+
+        assert!(SolidityType::Null.into_vec().is_none());
+        assert!(SolidityType::Uint(U256::from(1)).into_vec().is_none());
+        assert!(map_literal! { "a"; SolidityType::Uint(U256::from(1)) }
+            .into_vec()
+            .is_none());
+    }
+
+    #[test]
+    fn test_guess_nulls() {
+        let value = serde_json::json!({ "amount": 5, "memo": null });
+        let guess = SolidityType::guess_json_value(&value).unwrap();
+        assert_eq!(guess.len(), 1);
+        assert_eq!(guess.get("amount"), U256::from(5));
+        assert!(matches!(&guess, SolidityType::Struct(map) if !map.contains_key("memo")));
+        assert!(serde_json::to_value(&guess).is_ok());
+
+        // only the non null members are kept
+        let value = serde_json::json!(["0x1", null, "0x3"]);
+        let guess = SolidityType::guess_json_value(value).unwrap();
+        assert_eq!(guess.len(), 2);
+        assert_eq!(guess.get("1"), U256::from(3));
+
+        let value = serde_json::json!({ "_0": "0x1", "_1": null, "_2": { "memo": null } });
+        let guess = SolidityType::guess_json_value(value).unwrap();
+        assert!(matches!(&guess, SolidityType::Tuple(values) if values.len() == 2));
+        assert!(serde_json::to_value(&guess).is_ok());
+
+        assert!(matches!(
+            SolidityType::guess_json_value(Value::Null),
+            Some(SolidityType::Null)
+        ));
+    }
+
+    #[test]
+    fn test_guess_owned_matches_borrowed() {
+        let value = serde_json::json!({
+            "pool": "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640",
+            "symbol": "0xBTC",
+            "ticks": [-1, 2, null],
+            "amounts": { "_1": "0x2", "_0": "0x1" },
+            "memo": "hello",
+            "fee": 3000
+        });
+        let config = GuessConfig {
+            numeric_strings: true,
+            ..Default::default()
+        };
+        let borrowed = SolidityType::guess_json_value_with(&value, &config).unwrap();
+        let owned = SolidityType::guess_json_value_with(value, &config).unwrap();
+        assert_eq!(owned, borrowed);
+        assert_eq!(
+            serde_json::to_value(&owned).unwrap(),
+            serde_json::to_value(&borrowed).unwrap()
+        );
+    }
+
+    fn delta(operation: Operation, key: &str, value: u64) -> DeltaProto<Struct> {
+        let value =
+            crate::store_helpers::encode_stored_value(&SolidityType::Uint(U256::from(value)))
+                .unwrap();
+        DeltaProto {
+            operation,
+            ordinal: 0,
+            key: key.to_string(),
+            old_value: Struct::default(),
+            new_value: value,
+        }
+    }
+
+    fn pool_deltas() -> Deltas<DeltaProto<Struct>> {
+        Deltas {
+            deltas: vec![
+                delta(Operation::Create, "pool:0xa", 1),
+                delta(Operation::Update, "pool:0xa", 2),
+                delta(Operation::Create, "token:0xb", 3),
+                delta(Operation::Delete, "pool:0xc", 4),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_deltas_filtered() {
+        let creates = deltas_filtered(pool_deltas(), Operation::Create);
+        assert_eq!(creates.len(), 2);
+        assert!(creates.iter().all(
+            |delta| matches!(delta.get("operation"), SolidityType::String(op) if op == "Create")
+        ));
+        assert_eq!(creates.get("1").get("new_value"), U256::from(3));
+
+        // only updates carry a diff, and a create without an old value still serializes
+        assert!(
+            matches!(creates.first(), SolidityType::Struct(ref map) if !map.contains_key("diff") && !map.contains_key("old_value"))
+        );
+        assert!(serde_json::to_value(&creates).is_ok());
+        let updates = deltas_filtered(pool_deltas(), Operation::Update);
+        assert!(
+            matches!(updates.first(), SolidityType::Struct(ref map) if map.contains_key("diff"))
+        );
+
+        let deletes = deltas_filtered(pool_deltas(), Operation::Delete);
+        assert_eq!(deletes.len(), 1);
+        assert!(
+            matches!(deletes.first().get("operation"), SolidityType::String(op) if op == "Delete")
+        );
+    }
+
+    #[test]
+    fn test_update_delta_with_removed_field() {
+        let encode =
+            |value: &SolidityType| crate::store_helpers::encode_stored_value(value).unwrap();
+        let old = map_literal! {
+            "owner"; sol_type!(Address, "0x0000000000000000000000000000000000000001"),
+            "fee"; SolidityType::Uint(U256::from(3000))
+        };
+        let new = map_literal! {
+            "fee"; SolidityType::Uint(U256::from(500))
+        };
+        let delta = SolidityType::from(DeltaProto {
+            operation: Operation::Update,
+            ordinal: 0,
+            key: "pool:0xa".to_string(),
+            old_value: encode(&old),
+            new_value: encode(&new),
+        });
+
+        // the removed owner used to be a Null in the diff, which can't be serialized
+        assert!(serde_json::to_value(&delta).is_ok());
+        assert_eq!(delta.get("diff").changed_keys(), ["fee"]);
+        assert_eq!(delta.get("diff").get("fee"), U256::from(500));
+        assert!(
+            matches!(delta.get("removed"), SolidityType::List(ref keys) if keys == &[SolidityType::String("owner".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_operation_round_trip() {
+        let operations = [
+            Operation::Unset,
+            Operation::Create,
+            Operation::Update,
+            Operation::Delete,
+        ];
+        let names: Vec<String> = operations
+            .iter()
+            .map(|operation| {
+                SolidityType::from(delta(*operation, "pool", 1))
+                    .get("operation")
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(names, ["Unset", "Create", "Update", "Delete"]);
+
+        for (code, operation) in operations.into_iter().enumerate() {
+            assert_eq!(
+                Operation::try_from(SolidityType::from(operation)).unwrap(),
+                operation
+            );
+            assert_eq!(
+                Operation::try_from(SolidityType::Enum(U8::from(code))).unwrap(),
+                operation
+            );
+            assert_eq!(
+                Operation::try_from(SolidityType::Uint(U256::from(code))).unwrap(),
+                operation
+            );
+        }
+
+        let err = Operation::try_from(SolidityType::String("Upsert".to_string())).unwrap_err();
+        assert!(matches!(
+            err,
+            SolError::WrongVariant {
+                found: "string",
+                ..
+            }
+        ));
+        assert!(Operation::try_from(SolidityType::Uint(U256::from(4))).is_err());
+        assert!(Operation::try_from(SolidityType::Null).is_err());
+    }
+
+    #[test]
+    fn test_deltas_with_prefix() {
+        let pools = deltas_with_prefix(pool_deltas(), "pool:");
+        assert_eq!(pools.len(), 3);
+        let keys: Vec<String> = pools
+            .iter()
+            .map(|delta| delta.get("key").to_string())
+            .collect();
+        assert_eq!(keys, ["0xa", "0xa", "0xc"]);
+
+        assert!(deltas_with_prefix(pool_deltas(), "pair:").is_empty());
+    }
+
+    #[test]
+    fn test_get_path() {
+        let swap = map_literal! { "amount"; SolidityType::Uint(U256::from(5)) };
+        let value = map_literal! {
+            "pool"; map_literal! { "token0"; map_literal! { "address"; SolidityType::Address(Address::ZERO) } },
+            "swaps"; SolidityType::List(vec![swap])
+        };
+
+        assert_eq!(value.get_path("pool.token0.address"), Address::ZERO);
+        assert_eq!(value.get_path("swaps.0.amount"), U256::from(5));
+        assert_eq!(value.get_path("/swaps/0/amount"), U256::from(5));
+        assert!(matches!(
+            value.get_path("swaps.1.amount"),
+            SolidityType::Null
+        ));
+        assert!(matches!(
+            value.get_path("pool.token1.address"),
+            SolidityType::Null
+        ));
+        assert!(matches!(
+            value.get_path("pool.token0.address.nested"),
+            SolidityType::Null
+        ));
+        // an empty path is the value itself
+        assert!(matches!(value.get_path(""), SolidityType::Struct(_)));
+    }
+
+    #[test]
+    fn test_set_path() {
+        let mut value = SolidityType::Null;
+        value.set_path("pool.token0.address", SolidityType::Address(Address::ZERO));
+        value.set_path("pool/fee", SolidityType::Uint(U256::from(3000)));
+        assert_eq!(value.get_path("pool.token0.address"), Address::ZERO);
+        assert_eq!(value.get("pool").get("fee"), U256::from(3000));
+
+        value.set_path("swaps", SolidityType::List(vec![SolidityType::Null]));
+        value.set_path("swaps.0.amount", SolidityType::Uint(U256::from(5)));
+        assert_eq!(value.get_path("swaps.0.amount"), U256::from(5));
+    }
+
+    #[test]
+    #[should_panic(expected = "length 1")]
+    fn test_set_path_out_of_range() {
+        let mut value = map_literal! { "swaps"; SolidityType::List(vec![SolidityType::Null]) };
+        value.set_path("swaps.1.amount", SolidityType::Uint(U256::from(5)));
+    }
+
+    #[test]
+    fn test_guess_numbers() {
+        let guess = |raw: &str| SolidityType::guess_json_value(raw).unwrap();
+
+        assert_eq!(guess("18446744073709551615"), U256::from(u64::MAX));
+        assert_eq!(guess("-5"), I256::try_from(-5).unwrap());
+        assert!(matches!(guess("1.5"), SolidityType::String(val) if val == "1.5"));
+
+        // a float nested in a value doesn't fail the rest of it
+        let value = guess(r#"{"price": [1, 1.5]}"#);
+        assert_eq!(value.get("price").get("0"), U256::from(1));
+        assert!(matches!(
+            value.get("price").get("1"),
+            SolidityType::String(_)
+        ));
+    }
+
+    #[test]
+    fn test_guess_strings() {
+        let address = "0x1f98431c8ad98523631ae4a59f267346ea31f984";
+        let raw = format!(r#"{{"pool": "{address}", "fee": 3000, "pair": ["1INCH", null]}}"#);
+        let guess = SolidityType::guess_json_value(raw.as_str()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&guess).unwrap(),
+            serde_json::to_value(SolidityType::guess_json_value(
+                serde_json::from_str::<Value>(&raw).unwrap()
+            ))
+            .unwrap()
+        );
+        assert_eq!(guess.get("fee"), U256::from(3000));
+        // the null in the pair is dropped, so the guess serializes
+        assert!(matches!(guess.get("pair"), SolidityType::List(pair) if pair.len() == 1));
+
+        // not valid json, so it's guessed as the string it is
+        assert!(matches!(
+            SolidityType::guess_json_value(address.to_string()),
+            Some(SolidityType::Address(_))
+        ));
+        assert!(matches!(
+            SolidityType::guess_json_value("1INCH"),
+            Some(SolidityType::String(val)) if val == "1INCH"
+        ));
+    }
+
+    #[test]
+    fn test_guess_single_tuples() {
+        let single = serde_json::json!({ "_0": "0x01" });
+
+        // a function returning `(uint256)` keeps its tuple by default
+        let guess = SolidityType::guess_json_value(&single).unwrap();
+        assert!(matches!(&guess, SolidityType::Tuple(vals) if vals.len() == 1));
+        assert_eq!(guess.get("0"), U256::from(1));
+
+        let collapse = GuessConfig {
+            collapse_single_tuples: true,
+            ..Default::default()
+        };
+        let guess = SolidityType::guess_json_value_with(&single, &collapse).unwrap();
+        assert_eq!(guess, U256::from(1));
+
+        // members are ordered by their index, even past `_9` where the keys don't sort the same way
+        let mut members = Map::new();
+        for index in (0..12).rev() {
+            members.insert(format!("_{index}"), Value::from(index));
+        }
+        let guess = SolidityType::guess_json_value(Value::Object(members)).unwrap();
+        assert!(matches!(&guess, SolidityType::Tuple(vals) if vals.len() == 12));
+        for index in 0..12 {
+            assert_eq!(guess.get(&index.to_string()), U256::from(index));
+        }
+
+        // an empty object is an empty struct, not an empty tuple
+        let empty = serde_json::json!({});
+        for config in [GuessConfig::default(), collapse] {
+            let guess = SolidityType::guess_json_value_with(&empty, &config).unwrap();
+            assert!(matches!(&guess, SolidityType::Struct(map) if map.is_empty()));
+        }
+    }
+
+    #[test]
+    fn test_uniswap_v3_path() {
+        let token_in = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let token_mid = address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+        let token_out = address!("6B175474E89094C44Da98b954EedeAC495271d0F");
+        let fee = SolidityType::ByteArray(Bytes::copy_from_slice(&[0x00, 0x0b, 0xb8]));
+
+        let path = SolidityType::from(token_in)
+            .concat(&fee)
+            .concat(&SolidityType::from(token_mid))
+            .concat(&SolidityType::from(Bytes::copy_from_slice(&[
+                0x00, 0x01, 0xf4,
+            ])))
+            .concat(&SolidityType::from(token_out));
+        assert_eq!(path.len_bytes(), 66);
+
+        assert_eq!(path.take_address_at(0), token_in);
+        assert_eq!(path.take_address_at(23), token_mid);
+        assert_eq!(path.take_address_at(46), token_out);
+        assert!(matches!(path.take_address_at(47), SolidityType::Null));
+
+        assert_eq!(
+            crate::type_casts::uint(path.slice(20, 23)),
+            U256::from(3000)
+        );
+        assert!(path.slice(20, 23).strict_eq(&fee));
+        assert!(matches!(path.slice(60, 70), SolidityType::Null));
+    }
+
+    #[test]
+    fn test_address_cast_from_bytes() {
+        let token = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let packed = SolidityType::from(Bytes::copy_from_slice(token.as_slice()));
+        assert_eq!(crate::type_casts::address(packed), token);
+
+        let word = SolidityType::from(Bytes::copy_from_slice(token.into_word().as_slice()));
+        assert_eq!(word.len_bytes(), 32);
+        assert_eq!(crate::type_casts::address(word), token);
+
+        let short = SolidityType::from(Bytes::copy_from_slice(&[1; 4]));
+        assert!(matches!(
+            crate::type_casts::address(short),
+            SolidityType::Null
+        ));
+    }
+
+    #[test]
+    fn test_iterators() {
+        let list = SolidityType::List(vec![
+            SolidityType::Uint(U256::from(1)),
+            SolidityType::Uint(U256::from(2)),
+            SolidityType::Uint(U256::from(3)),
+        ]);
+
+        let mut total = U256::ZERO;
+        for value in &list {
+            if let SolidityType::Uint(value) = value {
+                total += *value;
+            }
+        }
+        assert_eq!(total, U256::from(6));
+
+        let doubled: SolidityType = list
+            .iter()
+            .map(|value| value.clone() * U256::from(2))
+            .collect();
+        assert!(doubled.strict_eq(&SolidityType::List(vec![
+            SolidityType::Uint(U256::from(2)),
+            SolidityType::Uint(U256::from(4)),
+            SolidityType::Uint(U256::from(6)),
+        ])));
+        assert_eq!(list.clone().into_iter().count(), 3);
+
+        let scalar = SolidityType::Uint(U256::from(7));
+        assert_eq!(scalar.iter().count(), 1);
+        assert_eq!(SolidityType::Null.iter().count(), 0);
+        assert_eq!(SolidityType::Null.into_iter().count(), 0);
+
+        let object: SolidityType = vec![
+            ("a".to_string(), SolidityType::Uint(U256::from(1))),
+            ("b".to_string(), SolidityType::Uint(U256::from(2))),
+        ]
+        .into_iter()
+        .collect();
+        let mut keys: Vec<&String> = object.entries().map(|(key, _)| key).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["a", "b"]);
+        assert_eq!(SolidityType::Null.entries().count(), 0);
+    }
+
+    #[test]
+    fn test_guess_opt_in_strings() {
+        let guess = |val: &str, config: &GuessConfig| {
+            SolidityType::guess_json_value_with(Value::String(val.to_string()), config).unwrap()
+        };
+        let conservative = GuessConfig::default();
+        let eager = GuessConfig {
+            numeric_strings: true,
+            rfc3339_timestamps: true,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            guess("123", &conservative),
+            SolidityType::String(_)
+        ));
+        assert_eq!(guess("123", &eager), U256::from(123));
+        // negative ones follow the same setting, so the type doesn't depend on the sign
+        assert!(matches!(
+            guess("-123", &conservative),
+            SolidityType::String(_)
+        ));
+        assert_eq!(guess("-123", &eager), I256::try_from(-123).unwrap());
+        assert!(matches!(guess("-", &eager), SolidityType::String(_)));
+
+        assert!(matches!(guess("1INCH", &eager), SolidityType::String(_)));
+
+        let timestamp = "2023-04-12T22:27:35Z";
+        assert!(matches!(
+            guess(timestamp, &conservative),
+            SolidityType::String(_)
+        ));
+        assert_eq!(guess(timestamp, &eager), U256::from(1_681_338_455));
+
+        let big = "123456789012345678901234567890";
+        assert_eq!(guess(big, &eager), U256::from_str(big).unwrap());
+    }
+
+    fn merge_fixture() -> (SolidityType, SolidityType) {
+        let lh = map_literal! {
+            "a"; SolidityType::Uint(U256::from(1)),
+            "nested"; map_literal! {
+                "x"; SolidityType::Uint(U256::from(1)),
+                "y"; SolidityType::Uint(U256::from(1))
+            }
+        };
+        let rh = map_literal! {
+            "b"; SolidityType::Uint(U256::from(2)),
+            "nested"; map_literal! { "y"; SolidityType::Uint(U256::from(2)) }
+        };
+        (lh, rh)
+    }
+
+    #[test]
+    fn test_merge() {
+        let (mut merged, rh) = merge_fixture();
+        merged.merge(rh);
+        assert_eq!(merged.get("a"), U256::from(1));
+        assert_eq!(merged.get("b"), U256::from(2));
+        assert_eq!(merged.get("nested").len(), 1);
+        assert_eq!(merged.get("nested").get("y"), U256::from(2));
+
+        let (mut merged, rh) = merge_fixture();
+        merged.merge_deep(rh);
+        assert_eq!(merged.get("nested").get("x"), U256::from(1));
+        assert_eq!(merged.get("nested").get("y"), U256::from(2));
+
+        let mut merged = SolidityType::Null;
+        merged.merge(merge_fixture().1);
+        assert_eq!(merged.get("b"), U256::from(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Tried to merge a uint into a list!")]
+    fn test_merge_non_struct() {
+        SolidityType::List(vec![]).merge(SolidityType::Uint(U256::from(1)));
+    }
+
+    #[test]
+    fn test_map_literal_spread() {
+        let base = merge_fixture().0;
+        let extended = map_literal! {
+            ..base,
+            "a"; SolidityType::Uint(U256::from(3)),
+            1; SolidityType::Uint(U256::from(4))
+        };
+        assert_eq!(extended.len(), 3);
+        assert_eq!(extended.get("a"), U256::from(3));
+        assert_eq!(extended.get("1"), U256::from(4));
+        // the base is cloned, not moved or changed
+        assert_eq!(base.get("a"), U256::from(1));
+
+        let copy = map_literal! { ..base };
+        assert_eq!(copy.len(), 2);
+    }
+
+    crate::loose_sol! {
+        struct Position {
+            address owner;
+            uint256 liquidity;
+            (uint8, address) tick;
+        }
+    }
+
+    #[test]
+    fn test_to_sol_struct() {
+        let owner = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let position = Position {
+            owner,
+            liquidity: U256::from(10).pow(U256::from(30)),
+            tick: (7, owner),
+        };
+
+        let json = serde_json::to_value(&position).unwrap();
+        let value = SolidityType::guess_json_value(&json).unwrap();
+        let round_trip: Position = value.to_sol_struct().unwrap();
+        assert_eq!(round_trip.owner, position.owner);
+        assert_eq!(round_trip.liquidity, position.liquidity);
+        assert_eq!(round_trip.tick, position.tick);
+
+        let mut bad = value.clone();
+        bad.insert("owner", SolidityType::String("not an address".to_string()));
+        let err = bad.to_sol_struct::<Position>().unwrap_err();
+        assert_eq!(err.field.as_deref(), Some("owner"));
+
+        let mut bad = value;
+        bad.insert(
+            "tick",
+            SolidityType::List(vec![
+                SolidityType::Uint(U256::from(256)),
+                SolidityType::Address(owner),
+            ]),
+        );
+        let err = bad.to_sol_struct::<Position>().unwrap_err();
+        assert_eq!(err.field.as_deref(), Some("tick[0]"));
+    }
+
+    crate::loose_sol! {
+        struct Payment {
+            uint256 amount;
+            bytes data;
+            address to;
+        }
+    }
 
     #[test]
-    fn test_serializations() {
-        let solidity_value = SolidityType::from(false);
-        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
-        println!("Bool: {}", &as_value);
-        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
-        println!("Bool Deserialized: {:?}", &from_value);
+    fn test_abi_round_trip() {
+        use alloy_sol_types::SolValue;
 
-        let solidity_value = SolidityType::Enum(U8::from(2));
-        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
-        println!("Enum: {}", &as_value);
-        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
-        println!("Enum Deserialized: {:?}", &from_value);
+        let to = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let payment = Payment {
+            amount: U256::from(10).pow(U256::from(18)),
+            data: vec![0xde, 0xad, 0xbe, 0xef].into(),
+            to,
+        };
+        let value = map_literal! {
+            "to"; SolidityType::Address(to),
+            "amount"; SolidityType::Uint(payment.amount),
+            "data"; SolidityType::ByteArray(payment.data.clone())
+        };
 
-        let solidity_value = SolidityType::Uint(U256::from(42069));
-        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
-        println!("Uint: {}", &as_value);
-        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
-        println!("Uint Deserialized: {:?}", &from_value);
+        let encoded = value.abi_encode();
+        assert_eq!(encoded, payment.abi_encode());
 
-        let solidity_value =
-            SolidityType::Address(address!("000000000000Ad05Ccc4F10045630fb830B95127"));
-        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
-        println!("Address: {}", &as_value);
-        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
-        println!("Address Deserialized: {:?}", &from_value);
+        let decoded = SolidityType::abi_decode(&encoded, "(uint256,bytes,address)").unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(SolidityType::Tuple(vec![
+                value.get("amount"),
+                value.get("data"),
+                value.get("to"),
+            ]))
+            .unwrap()
+        );
+        assert_eq!(decoded.abi_encode(), encoded);
 
-        // TODO BYTES!
-        // TODO FIXEDARRAY!
+        let selector = SolidityType::FixedBytes(4, Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbb]));
+        let list = SolidityType::List(vec![selector.clone(), selector]);
+        let decoded = SolidityType::abi_decode(&list.abi_encode(), "bytes4[]").unwrap();
+        assert_eq!(decoded.to_string(), list.to_string());
 
-        let solidity_value = SolidityType::from("Hello World!".to_string());
-        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
-        println!("String: {}", &as_value);
-        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
-        println!("String Deserialized: {:?}", &from_value);
+        assert!(matches!(
+            SolidityType::Null.try_abi_encode(),
+            Err(SolError::WrongVariant { .. })
+        ));
+        assert!(matches!(
+            SolidityType::abi_decode(&encoded, "(uint256,"),
+            Err(SolError::ParseFailure { .. })
+        ));
+        assert!(matches!(
+            SolidityType::abi_decode(&encoded[..40], "(uint256,bytes,address)"),
+            Err(SolError::EncodingError { .. })
+        ));
+    }
 
-        let solidity_value = SolidityType::Tuple(vec![
-            SolidityType::from(false),
-            SolidityType::Address(address!("000000000000Ad05Ccc4F10045630fb830B95127")),
+    #[test]
+    fn test_index() {
+        let mut value = map_literal! {
+            "pool"; map_literal! {
+                "reserves"; SolidityType::List(vec![
+                    SolidityType::Uint(U256::from(1)),
+                    SolidityType::Uint(U256::from(2)),
+                ])
+            }
+        };
+
+        assert_eq!(value["pool"]["reserves"][1], U256::from(2));
+        assert_eq!(value["pool"]["reserves"]["0"], U256::from(1));
+        assert!(matches!(value["pool"]["missing"][3], SolidityType::Null));
+
+        value["pool"]["reserves"][0] = SolidityType::Uint(U256::from(5));
+        value["pool"]["fee"] = SolidityType::Uint(U256::from(3000));
+        value["token"]["decimals"] = SolidityType::Uint(U256::from(18));
+        assert_eq!(value["pool"]["reserves"][0], U256::from(5));
+        assert_eq!(value["pool"]["fee"], U256::from(3000));
+        assert_eq!(value["token"]["decimals"], U256::from(18));
+
+        // goes through the &SolidityType impl, which used to look like it recursed into itself
+        let borrowed = &value;
+        assert_eq!(
+            MapLike::get(&borrowed, "token").get("decimals"),
+            U256::from(18)
+        );
+    }
+
+    #[test]
+    fn test_map_like() {
+        use serde_json::json;
+
+        // only goes through the trait, so the SolidityType impls would overflow the stack if they recursed
+        fn round_trip<M: MapLike>(mut map: M) -> M {
+            map.insert("fee", SolidityType::Uint(U256::from(3000)));
+            map.insert("name", SolidityType::String("WETH/USDC".to_string()));
+            assert_eq!(map.get("fee"), U256::from(3000));
+            assert!(matches!(map.get("name"), SolidityType::String(name) if name == "WETH/USDC"));
+            assert!(matches!(map.get("missing"), SolidityType::Null));
+            map
+        }
+
+        let value = round_trip(SolidityType::Struct(IndexMap::new()));
+        assert_eq!(MapLike::get(&&value, "fee"), U256::from(3000));
+        let proto = round_trip(Struct::default());
+        assert!(matches!(
+            proto.fields["fee"].kind,
+            Some(Kind::StructValue(_))
+        ));
+        let json = round_trip(Map::new());
+        assert_eq!(json["fee"], json!("0xbb8"));
+
+        // plain values are guessed when they're read
+        let plain = json!({ "owner": "0x1f98431c8ad98523631ae4a59f267346ea31f984", "count": 2 });
+        let proto: Struct = serde_json::from_value(plain.clone()).unwrap();
+        assert!(matches!(
+            MapLike::get(&proto, "owner"),
+            SolidityType::Address(_)
+        ));
+        assert_eq!(MapLike::get(&proto, "count"), U256::from(2));
+        let json = plain.as_object().unwrap();
+        assert!(matches!(
+            MapLike::get(json, "owner"),
+            SolidityType::Address(_)
+        ));
+        assert_eq!(MapLike::get(json, "count"), U256::from(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Tried to index 2 into a list of length 2!")]
+    fn test_index_mut_out_of_range() {
+        let mut value = SolidityType::List(vec![SolidityType::Null, SolidityType::Null]);
+        value[2] = SolidityType::Uint(U256::from(1));
+    }
+
+    #[test]
+    fn test_address_formatting() {
+        let checksummed = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+        let lowercase = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+        let value = SolidityType::Address(Address::from_str(checksummed).unwrap());
+
+        assert_eq!(value.to_lowercase_string(), lowercase);
+        assert_eq!(value.to_checksum_string(None), checksummed);
+        assert_eq!(value.to_string(), lowercase);
+        assert_eq!(
+            crate::type_casts::string(value.clone()).to_string(),
+            lowercase
+        );
+
+        let rsk = value.to_checksum_string(Some(30));
+        assert_eq!(rsk.to_lowercase(), lowercase);
+        assert!(Address::parse_checksummed(&rsk, Some(30)).is_ok());
+
+        assert!(validate_checksum(checksummed));
+        assert!(!validate_checksum(lowercase));
+        assert!(!validate_checksum(&checksummed.replace('C', "c")));
+        assert!(!validate_checksum("not an address"));
+    }
+
+    #[test]
+    fn test_decimal_uints() {
+        use serde_json::json;
+
+        let value = map_literal! {
+            "amount"; SolidityType::Uint(U256::MAX),
+            "fees"; SolidityType::List(vec![SolidityType::Uint(U256::from(3000))]),
+            "pair"; SolidityType::Tuple(vec![
+                SolidityType::Uint(U256::from(16)),
+                SolidityType::from("sixteen".to_string()),
+            ])
+        };
+
+        let decimal = value.to_tagged_value_decimal();
+        assert_eq!(decimal["value"]["fees"]["value"][0]["value"], json!("3000"));
+        assert_eq!(
+            decimal["value"]["amount"]["value"],
+            json!(U256::MAX.to_string())
+        );
+        assert_eq!(
+            serde_json::to_value(DecimalUints(value.clone())).unwrap(),
+            decimal
+        );
+
+        // both modes read back into the same value
+        let from_decimal: SolidityType = serde_json::from_value(decimal).unwrap();
+        let from_hex: SolidityType =
+            serde_json::from_value(serde_json::to_value(&value).unwrap()).unwrap();
+        assert!(from_decimal.strict_eq(&value));
+        assert!(from_hex.strict_eq(&value));
+
+        assert_eq!(SolidityType::Null.to_tagged_value_decimal(), Value::Null);
+        let nested =
+            SolidityType::List(vec![SolidityType::Uint(U256::from(1)), SolidityType::Null]);
+        assert_eq!(
+            nested.to_tagged_value_decimal()["value"],
+            json!([{"type": "uint", "value": "1"}, null])
+        );
+
+        let mixed: SolidityType = serde_json::from_value(json!({
+            "type": "list",
+            "value": [
+                {"type": "uint", "value": "0x10"},
+                {"type": "uint", "value": "16"}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(mixed[0], U256::from(16));
+        assert_eq!(mixed[1], U256::from(16));
+    }
+
+    #[test]
+    fn test_insert_out_of_range() {
+        let mut list = SolidityType::List(vec![SolidityType::Uint(U256::from(1))]);
+        list.insert("3", SolidityType::Uint(U256::from(4)));
+        assert_eq!(list.len(), 4);
+        assert!(matches!(list.get("1"), SolidityType::Null));
+        assert!(matches!(list.get("2"), SolidityType::Null));
+        assert_eq!(list.get("3"), U256::from(4));
+
+        // within range still shifts the rest along
+        list.insert("0", SolidityType::Uint(U256::from(0)));
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.get("1"), U256::from(1));
+    }
+
+    #[test]
+    fn test_negative_get() {
+        let list = SolidityType::List(vec![
+            SolidityType::Uint(U256::from(1)),
+            SolidityType::Uint(U256::from(2)),
+            SolidityType::Uint(U256::from(3)),
         ]);
-        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
-        println!("Tuple: {}", &as_value);
-        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
-        println!("Tuple Deserialized: {:?}", &from_value);
+        assert_eq!(list.get("-1"), U256::from(3));
+        assert_eq!(list.get("-3"), U256::from(1));
+        assert!(matches!(list.get("-4"), SolidityType::Null));
+        assert!(matches!(list.get("3"), SolidityType::Null));
 
-        let solidity_value = SolidityType::List(vec![
-            SolidityType::from(false),
-            SolidityType::Address(address!("000000000000Ad05Ccc4F10045630fb830B95127")),
+        // Index and get_path resolve keys the same way get does
+        assert_eq!(list["-1"], U256::from(3));
+        assert!(matches!(list["-4"], SolidityType::Null));
+        let pool = map_literal! { "ticks"; list.clone() };
+        assert_eq!(pool.get_path("ticks.-2"), U256::from(2));
+    }
+
+    #[test]
+    fn test_non_numeric_list_key() {
+        let mut list = SolidityType::List(vec![SolidityType::Uint(U256::from(1))]);
+        assert!(matches!(list.get("pool"), SolidityType::Null));
+        assert!(matches!(
+            list.try_get("pool"),
+            Err(SolError::ParseFailure { input, .. }) if input == "pool"
+        ));
+
+        list.insert("pool", SolidityType::Uint(U256::from(2)));
+        assert_eq!(list.len(), 1);
+        assert!(matches!(
+            list.try_insert("pool", SolidityType::Null),
+            Err(SolError::ParseFailure { .. })
+        ));
+        assert!(matches!(
+            SolidityType::Uint(U256::from(1)).try_get("0"),
+            Err(SolError::WrongVariant { found: "uint", .. })
+        ));
+    }
+
+    #[test]
+    fn test_loose_eq() {
+        let weth = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let word = alloy_primitives::B256::from_slice(&[7; 32]);
+        let string = |val: &str| SolidityType::String(val.to_string());
+
+        let equal = vec![
+            (
+                SolidityType::Address(weth),
+                string("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+            ),
+            (
+                SolidityType::Address(weth),
+                string("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+            ),
+            (
+                SolidityType::Address(weth),
+                SolidityType::ByteArray(Bytes::copy_from_slice(weth.as_slice())),
+            ),
+            (
+                SolidityType::FixedArray(word),
+                SolidityType::ByteArray(Bytes::copy_from_slice(&[7; 32])),
+            ),
+            (
+                SolidityType::FixedArray(word),
+                string(&format_hex(&[7; 32])),
+            ),
+            (SolidityType::Uint(U256::from(1000)), string("1000")),
+            (SolidityType::Uint(U256::from(16)), string("0x10")),
+            (
+                SolidityType::List(vec![SolidityType::Address(weth)]),
+                SolidityType::Tuple(vec![string("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2")]),
+            ),
+            (
+                map_literal! { "amount"; SolidityType::Uint(U256::from(5)) },
+                map_literal! { "amount"; string("5") },
+            ),
+        ];
+        for (lh, rh) in equal {
+            assert!(lh.loose_eq(&rh), "{lh:?} should loosely equal {rh:?}");
+            assert!(rh.loose_eq(&lh), "{rh:?} should loosely equal {lh:?}");
+        }
+
+        let unequal = vec![
+            (
+                SolidityType::Address(weth),
+                string("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc3"),
+            ),
+            (SolidityType::Address(weth), string("weth")),
+            (
+                SolidityType::FixedArray(word),
+                SolidityType::ByteArray(Bytes::copy_from_slice(&[7; 31])),
+            ),
+            (SolidityType::Uint(U256::from(1000)), string("1001")),
+            (SolidityType::Uint(U256::from(1)), SolidityType::from(true)),
+            (string("abc"), string("ABC")),
+        ];
+        for (lh, rh) in unequal {
+            assert!(!lh.loose_eq(&rh), "{lh:?} shouldn't loosely equal {rh:?}");
+        }
+    }
+
+    #[test]
+    fn test_eq_address_filter() {
+        let weth = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let list = SolidityType::List(vec![
+            SolidityType::Address(weth),
+            SolidityType::String("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2".to_string()),
+            SolidityType::Address(Address::ZERO),
         ]);
-        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
-        println!("List: {}", &as_value);
-        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
-        println!("List Deserialized: {:?}", &from_value);
 
-        let mut struct_map: HashMap<String, SolidityType> = HashMap::new();
-        struct_map.insert("bool".to_string(), SolidityType::from(false));
-        struct_map.insert(
-            "addr".to_string(),
-            SolidityType::Address(address!("000000000000Ad05Ccc4F10045630fb830B95127")),
+        let filtered = list.filter(eq_address(weth));
+        assert_eq!(filtered.len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_utf8_strings() {
+        let mut mkr = [0u8; 32];
+        mkr[..3].copy_from_slice(b"MKR");
+        let symbol = SolidityType::from_bytes32_string(&alloy_primitives::B256::from(mkr));
+        assert!(matches!(&symbol, SolidityType::String(symbol) if symbol == "MKR"));
+        assert!(symbol.is_valid_utf8());
+        assert_eq!(
+            SolidityType::FixedArray(alloy_primitives::B256::from(mkr)).to_lossy_string(),
+            "MKR"
         );
-        struct_map.insert(
-            "foo".to_string(),
-            SolidityType::List(vec![
-                SolidityType::from(false),
-                SolidityType::Address(address!("000000000000Ad05Ccc4F10045630fb830B95127")),
-            ]),
+
+        let raw = [b'B', b'A', b'D', 0xff, 0xfe];
+        let symbol = SolidityType::from_string_bytes(&raw);
+        assert!(matches!(symbol, SolidityType::ByteArray(_)));
+        assert!(!symbol.is_valid_utf8());
+        assert_eq!(symbol.as_bytes(), Some(&raw[..]));
+        assert!(symbol.to_lossy_string().starts_with("BAD"));
+
+        let lossy = SolidityType::String(String::from_utf8_lossy(&raw).into_owned());
+        assert!(!lossy.is_valid_utf8());
+
+        let token = map_literal! {
+            "symbol"; symbol,
+            "name"; SolidityType::from_string_bytes(b"Bad Token")
+        };
+        assert!(token.to_proto_struct().is_some());
+    }
+
+    #[test]
+    fn test_guess_unparseable_hex() {
+        let fake_address = format!("0x{}", "z".repeat(40));
+        let guessed = SolidityType::guess_json_value(Value::String(fake_address.clone())).unwrap();
+        assert!(matches!(guessed, SolidityType::String(val) if val == fake_address));
+
+        let guessed = SolidityType::guess_json_value(Value::String("0xINCH".to_string())).unwrap();
+        assert!(matches!(guessed, SolidityType::String(_)));
+    }
+
+    #[test]
+    fn test_guess_hex_config() {
+        let guess = |val: &str, config: &GuessConfig| {
+            SolidityType::guess_json_value_with(Value::String(val.to_string()), config).unwrap()
+        };
+        let defaults = GuessConfig::default();
+        let bytes = GuessConfig {
+            prefer_bytes_over_uint: true,
+            ..Default::default()
+        };
+        let no_addresses = GuessConfig {
+            treat_40_hex_as_address: false,
+            ..bytes.clone()
+        };
+
+        // the short hex alloy writes for small uints
+        assert_eq!(guess("0x01", &defaults), U256::from(1));
+        assert!(matches!(guess("0x01", &bytes), SolidityType::ByteArray(val) if val[..] == [1]));
+        assert_eq!(guess("0x1", &bytes), U256::from(1));
+
+        let address = "0x1f98431c8ad98523631ae4a59f267346ea31f984";
+        assert_eq!(
+            guess(address, &defaults),
+            address.parse::<Address>().unwrap()
+        );
+        assert!(matches!(guess(address, &bytes), SolidityType::Address(_)));
+        assert!(
+            matches!(guess(address, &no_addresses), SolidityType::ByteArray(val) if val.len() == 20)
         );
 
-        let solidity_value = SolidityType::Struct(struct_map);
-        let as_value = serde_json::to_string_pretty(&solidity_value).unwrap();
-        println!("Map: {}", &as_value);
-        let from_value: SolidityType = serde_json::from_str(&&as_value).unwrap();
-        println!("Map Deserialized: {:?}", &from_value);
+        let hash = format!("0x{}", "ab".repeat(32));
+        assert!(matches!(guess(&hash, &defaults), SolidityType::Uint(_)));
+        assert!(matches!(guess(&hash, &bytes), SolidityType::FixedArray(_)));
+
+        // not hex, or too long and odd to be bytes or a uint
+        for val in ["0xzz".to_string(), format!("0x{}", "a".repeat(65))] {
+            assert!(
+                matches!(guess(&val, &defaults), SolidityType::String(ref string) if *string == val)
+            );
+            assert!(
+                matches!(guess(&val, &bytes), SolidityType::String(ref string) if *string == val)
+            );
+        }
+    }
+
+    #[test]
+    fn test_to_maybe_value_all_null() {
+        let nulls = SolidityType::List(vec![SolidityType::Null, SolidityType::Null]);
+        assert!(nulls.to_maybe_value().is_none());
+        assert!(SolidityType::Tuple(vec![SolidityType::Null])
+            .to_maybe_value()
+            .is_none());
+
+        let nested = map_literal! { "transfers"; nulls.clone() };
+        assert!(nested.to_maybe_value().is_none());
+        assert!(matches!(
+            nested.prune(EmptyPolicy::WriteEmpty),
+            Some(SolidityType::Struct(ref map)) if matches!(&map["transfers"], SolidityType::List(vals) if vals.is_empty())
+        ));
+    }
+
+    #[test]
+    fn test_to_maybe_value_mixed() {
+        let uint = || SolidityType::Uint(U256::from(1));
+        // lists, tuples and structs all drop their Null members the same way
+        for container in [
+            SolidityType::List(vec![uint(), SolidityType::Null]),
+            SolidityType::Tuple(vec![uint(), SolidityType::Null]),
+            SolidityType::SizedList(2, vec![uint(), SolidityType::Null]),
+        ] {
+            let Some(pruned) = container.to_maybe_value() else {
+                panic!("Expected {container:?} to keep its uint");
+            };
+            assert_eq!(pruned.get("0"), U256::from(1));
+            assert!(matches!(pruned.get("1"), SolidityType::Null));
+            assert!(container.to_maybe_value_strict().is_none());
+        }
+
+        let mixed = map_literal! {
+            "amount"; uint(),
+            "owner"; SolidityType::Null,
+            "empty"; SolidityType::Tuple(vec![SolidityType::Null, SolidityType::List(vec![SolidityType::Null])]),
+            "nested"; map_literal! {
+                "ticks"; SolidityType::List(vec![uint(), SolidityType::Null]),
+                "pool"; map_literal! { "fee"; SolidityType::Null }
+            }
+        };
+        let pruned = mixed.to_maybe_value().unwrap();
+        assert_eq!(pruned.changed_keys(), ["amount", "nested.ticks"]);
+        assert!(
+            matches!(pruned.get("nested").get("ticks"), SolidityType::List(ref ticks) if ticks.len() == 1)
+        );
+        assert!(mixed.to_maybe_value_strict().is_none());
+        assert!(mixed.get("nested").to_maybe_value_strict().is_none());
+
+        // a fully populated value comes back untouched, empties included
+        let full = map_literal! {
+            "amount"; uint(),
+            "ticks"; SolidityType::List(vec![uint(), uint()]),
+            "hooks"; SolidityType::List(vec![])
+        };
+        assert_eq!(full.to_maybe_value_strict(), Some(full.clone()));
+        assert!(SolidityType::Null.to_maybe_value_strict().is_none());
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = map_literal! {
+            "owner"; sol_type!(Address, "0x0000000000000000000000000000000000000001"),
+            "reserves"; map_literal! {
+                "token0"; SolidityType::Uint(U256::from(100)),
+                "token1"; SolidityType::Uint(U256::from(200))
+            },
+            "fee"; SolidityType::Uint(U256::from(3000))
+        };
+        // only one leaf changed, and the fee is written differently but means the same thing
+        let new = map_literal! {
+            "owner"; sol_type!(Address, "0x0000000000000000000000000000000000000001"),
+            "reserves"; map_literal! {
+                "token0"; SolidityType::Uint(U256::from(150)),
+                "token1"; SolidityType::Uint(U256::from(200))
+            },
+            "fee"; SolidityType::String("3000".to_string())
+        };
+
+        let changes = diff(&old, &new);
+        assert_eq!(changes.changed_keys(), ["reserves.token0"]);
+        assert_eq!(changes.get("reserves").get("token0"), U256::from(150));
+
+        // removed keys are left out of the diff and listed on their own
+        let removed = map_literal! {
+            "reserves"; map_literal! {
+                "token0"; SolidityType::Uint(U256::from(100))
+            },
+            "fee"; SolidityType::Uint(U256::from(3000))
+        };
+        assert!(diff(&old, &removed).changed_keys().is_empty());
+        assert_eq!(removed_keys(&old, &removed), ["owner", "reserves.token1"]);
+
+        assert!(diff(&old, &old).changed_keys().is_empty());
+    }
+
+    #[test]
+    fn test_eq_and_ord_every_variant() {
+        let values = [
+            SolidityType::from(true),
+            SolidityType::Enum(U8::from(1)),
+            SolidityType::Uint(U256::from(1)),
+            SolidityType::Int(I256::ONE),
+            SolidityType::Address(Address::ZERO),
+            SolidityType::ByteArray(Bytes::from(vec![1])),
+            SolidityType::FixedArray(alloy_primitives::B256::ZERO),
+            SolidityType::FixedBytes(4, Bytes::from(vec![1; 4])),
+            SolidityType::String("1".to_string()),
+            SolidityType::Tuple(vec![SolidityType::Uint(U256::from(1))]),
+            SolidityType::List(vec![SolidityType::Uint(U256::from(1))]),
+            SolidityType::SizedList(1, vec![SolidityType::Uint(U256::from(1))]),
+            map_literal! { "a"; SolidityType::Uint(U256::from(1)) },
+            SolidityType::Null,
+        ];
+        let orderable = |value: &SolidityType| match value {
+            SolidityType::Uint(_) | SolidityType::Int(_) => Some("number"),
+            SolidityType::String(_) => Some("string"),
+            SolidityType::Address(_) => Some("address"),
+            _ => None,
+        };
+
+        for (i, lhs) in values.iter().enumerate() {
+            for (j, rhs) in values.iter().enumerate() {
+                assert_eq!(lhs == rhs, i == j, "{lhs:?} == {rhs:?}");
+                assert_eq!(lhs != rhs, i != j, "{lhs:?} != {rhs:?}");
+
+                // the int and the uint are both 1, so they aren't equal and can't be ordered either
+                let comparable = orderable(lhs).is_some()
+                    && orderable(lhs) == orderable(rhs)
+                    && (i == j || orderable(lhs) != Some("number"));
+                assert_eq!(
+                    lhs.partial_cmp(rhs).is_some(),
+                    comparable,
+                    "{lhs:?} cmp {rhs:?}"
+                );
+            }
+        }
+
+        // containers compare deeply
+        let nested = |val: u64| {
+            map_literal! { "reserves"; SolidityType::List(vec![SolidityType::Uint(U256::from(val))]) }
+        };
+        assert!(nested(1) == nested(1));
+        assert!(nested(1) != nested(2));
+        assert!(SolidityType::String("gm".to_string()) == "gm".to_string());
+        assert!(SolidityType::from(true) != false);
+
+        // different numbers still order across ints and uints
+        let minus_one = SolidityType::Int(I256::MINUS_ONE);
+        assert!(minus_one < SolidityType::Uint(U256::from(1)));
+        assert!(SolidityType::Uint(U256::from(2)) > SolidityType::Int(I256::ONE));
+        assert!(SolidityType::Uint(U256::from(1))
+            .partial_cmp(&SolidityType::Int(I256::ONE))
+            .is_none());
+    }
+
+    #[test]
+    fn test_null_arithmetic() {
+        let uint = |val: u64| SolidityType::Uint(U256::from(val));
+        let null = || SolidityType::Null;
+
+        // Null is the identity of add, so a missing store value can start an accumulator
+        assert!((null() + uint(5)).strict_eq(&uint(5)));
+        assert!((uint(5) + null()).strict_eq(&uint(5)));
+
+        // but it isn't zero on the left of a sub
+        assert!(matches!(null() - uint(5), SolidityType::Null));
+        assert!((uint(5) - null()).strict_eq(&uint(5)));
+        assert!(matches!(
+            null().checked_sub(uint(5)),
+            Some(SolidityType::Null)
+        ));
+        assert!(matches!(null().saturating_sub(uint(5)), SolidityType::Null));
+
+        // and spreads through mul and div from either side
+        assert!(matches!(null() * uint(5), SolidityType::Null));
+        assert!(matches!(uint(5) * null(), SolidityType::Null));
+        assert!(matches!(null() / uint(5), SolidityType::Null));
+        assert!(matches!(uint(5) / null(), SolidityType::Null));
+    }
+
+    #[test]
+    fn test_checked_and_saturating() {
+        let int = |val: i64| SolidityType::Int(I256::try_from(val).unwrap());
+        let uint = |val: u64| SolidityType::Uint(U256::from(val));
+        let max = SolidityType::Uint(U256::MAX);
+
+        // a balance diff with the old and new balances mixed up
+        assert!(uint(5).checked_sub(uint(8)).is_none());
+        assert_eq!(uint(8).checked_sub(uint(5)).unwrap(), U256::from(3));
+        assert!(uint(5).saturating_sub(uint(8)).is_zero());
+
+        assert!(max.clone().checked_add(uint(1)).is_none());
+        assert_eq!(max.clone().saturating_add(uint(1)), U256::MAX);
+        assert!(max.clone().checked_mul(uint(2)).is_none());
+        assert_eq!(max.saturating_mul(uint(2)), U256::MAX);
+
+        assert!(uint(5).checked_div(uint(0)).is_none());
+        assert!(int(-5).checked_div(int(0)).is_none());
+        assert!(SolidityType::Int(I256::MIN).checked_div(int(-1)).is_none());
+        assert!(uint(7).checked_div(uint(2)).unwrap().strict_eq(&uint(3)));
+
+        assert!(int(-5).checked_sub(uint(3)).unwrap().strict_eq(&int(-8)));
+        assert!(SolidityType::Int(I256::MIN)
+            .saturating_sub(int(1))
+            .strict_eq(&SolidityType::Int(I256::MIN)));
+        assert!(SolidityType::Int(I256::MAX).checked_add(int(1)).is_none());
+
+        // Nulls and errors behave as the try_ versions
+        assert!(SolidityType::Null
+            .checked_add(uint(1))
+            .unwrap()
+            .strict_eq(&uint(1)));
+        assert!(uint(1).checked_add(Address::ZERO).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Tried to divide by zero!")]
+    fn test_div_by_zero_panics() {
+        let _ = SolidityType::Uint(U256::from(1)) / U256::ZERO;
+    }
+
+    #[test]
+    fn test_int() {
+        let int = |val: i64| SolidityType::Int(I256::try_from(val).unwrap());
+        let uint = |val: u64| SolidityType::Uint(U256::from(val));
+
+        let json = serde_json::to_value(int(-5)).unwrap();
+        assert_eq!(json, serde_json::json!({"type": "int", "value": "-5"}));
+        let round_trip: SolidityType = serde_json::from_value(json).unwrap();
+        assert!(round_trip.strict_eq(&int(-5)));
+
+        assert!((int(-5) + int(3)).strict_eq(&int(-2)));
+        assert!((int(-5) - uint(3)).strict_eq(&int(-8)));
+        assert!((uint(3) * int(-2)).strict_eq(&int(-6)));
+        assert!((int(-7) / int(2)).strict_eq(&int(-3)));
+        assert_eq!(
+            int(-5).try_div(int(0)).unwrap_err(),
+            SolError::DivisionByZero
+        );
+        assert_eq!(
+            int(-5).try_add(SolidityType::Uint(U256::MAX)).unwrap_err(),
+            SolError::Overflow { operation: "add" }
+        );
+        // uints on their own still wrap as uints
+        assert!((uint(3) - uint(1)).strict_eq(&uint(2)));
+
+        assert!(int(-1) < uint(0));
+        assert!(uint(5) > int(4));
+        assert!(int(5).loose_eq(&uint(5)));
+        assert!(int(-5).loose_eq(&SolidityType::String("-5".to_string())));
+        assert_eq!(int(-5).to_string(), "-5");
+        assert_eq!(int(-5).to_untagged_value(), serde_json::json!("-5"));
+
+        let tick: I256 = int(-5).try_into().unwrap();
+        assert_eq!(tick, I256::try_from(-5i64).unwrap());
+        let dyn_value: SolidityType = DynSolValue::Int(tick, 24).into();
+        assert!(dyn_value.strict_eq(&int(-5)));
+    }
+
+    #[test]
+    fn test_try_from() {
+        let uint = SolidityType::Uint(U256::from(1));
+
+        let value: U256 = uint.clone().try_into().unwrap();
+        assert_eq!(value, U256::from(1));
+        let address: Address = SolidityType::Address(Address::ZERO).try_into().unwrap();
+        assert_eq!(address, Address::ZERO);
+        let flag: bool = SolidityType::from(true).try_into().unwrap();
+        assert!(flag);
+        let string: String = SolidityType::String("gm".to_string()).try_into().unwrap();
+        assert_eq!(string, "gm");
+
+        let err = Address::try_from(uint.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            SolError::WrongVariant {
+                operation: "make an address out of",
+                found: "uint"
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Tried to make an address out of a uint value!"
+        );
+        assert!(bool::try_from(uint.clone()).is_err());
+        assert!(Bytes::try_from(uint.clone()).is_err());
+        assert!(alloy_primitives::B256::try_from(SolidityType::Null).is_err());
+        assert!(String::try_from(uint).is_err());
+    }
+
+    #[test]
+    fn test_struct_map_filter() {
+        let balances = map_literal! {
+            "alice"; SolidityType::Uint(U256::from(5)),
+            "bob"; SolidityType::Uint(U256::from(0)),
+            "carol"; SolidityType::Uint(U256::from(12))
+        };
+
+        let doubled = balances.map(|value| value.clone() * U256::from(2));
+        assert_eq!(doubled.len(), 3);
+        assert_eq!(doubled.get("carol"), U256::from(24));
+
+        let non_zero = balances.map(|value| {
+            if value.is_zero() {
+                SolidityType::Null
+            } else {
+                value.clone()
+            }
+        });
+        assert_eq!(non_zero.len(), 2);
+        let strict = balances.strict_map(|value| {
+            if value.is_zero() {
+                SolidityType::Null
+            } else {
+                value.clone()
+            }
+        });
+        assert_eq!(strict.len(), 3);
+        assert!(matches!(strict.get("bob"), SolidityType::Null));
+
+        let big = balances.filter(|value| (value.clone() > U256::from(4)).into());
+        assert_eq!(big.len(), 2);
+        assert_eq!(big.get("alice"), U256::from(5));
+        assert!(matches!(
+            balances.filter(|_| false.into()),
+            SolidityType::Null
+        ));
+
+        let named = balances.filter_entries(|key, _| key.starts_with('c').into());
+        assert_eq!(named.len(), 1);
+        assert_eq!(named.get("carol"), U256::from(12));
+        assert!(matches!(
+            SolidityType::Uint(U256::from(1)).try_filter_entries(|_, _| true.into()),
+            Err(SolError::WrongVariant { found: "uint", .. })
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Tried to map over a uint")]
+    fn test_map_scalar_panics() {
+        SolidityType::Uint(U256::from(1)).map(|value| value.clone());
+    }
+
+    #[test]
+    fn test_try_errors() {
+        let list = SolidityType::List(vec![SolidityType::Uint(U256::from(1))]);
+        let uint = SolidityType::Uint(U256::from(1));
+        let pool = map_literal! { "pool"; SolidityType::Address(Address::ZERO) };
+
+        assert!(matches!(
+            uint.try_map(|item| item.clone()),
+            Err(SolError::WrongVariant {
+                operation: "map over",
+                found: "uint"
+            })
+        ));
+        assert!(matches!(
+            list.try_filter(|item| item.clone()),
+            Err(SolError::WrongVariant {
+                operation: "filter by",
+                found: "uint"
+            })
+        ));
+        assert!(matches!(
+            list.try_to_string(),
+            Err(SolError::WrongVariant { found: "list", .. })
+        ));
+        assert!(matches!(
+            list.try_get("one"),
+            Err(SolError::ParseFailure {
+                expected: "a list index",
+                ..
+            })
+        ));
+        assert!(matches!(
+            list.try_get("-2"),
+            Err(SolError::IndexOutOfRange { index: -2, len: 1 })
+        ));
+        assert!(matches!(
+            uint.clone().try_add(SolidityType::Address(Address::ZERO)),
+            Err(SolError::NotComparable {
+                operation: "add",
+                lhs: "uint",
+                rhs: "address"
+            })
+        ));
+        assert!(matches!(
+            uint.try_cmp(&pool),
+            Err(SolError::NotComparable {
+                operation: "compare",
+                ..
+            })
+        ));
+        assert!(matches!(
+            uint.clone().try_div(U256::ZERO),
+            Err(SolError::DivisionByZero)
+        ));
+
+        // the happy paths match the panicking versions
+        assert_eq!(uint.clone().try_mul(U256::from(3)).unwrap(), U256::from(3));
+        assert!(matches!(uint.try_to_string(), Ok(SolidityType::String(val)) if val == "1"));
+        assert_eq!(
+            list.try_map(|item| item.clone() + U256::from(1))
+                .unwrap()
+                .get("0"),
+            U256::from(2)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't subtract a uint and a string!")]
+    fn test_sub_panics_with_context() {
+        let _ = SolidityType::Uint(U256::from(1)) - SolidityType::String("1".to_string());
+    }
+
+    #[test]
+    fn test_fixed_bytes_and_sized_lists() {
+        let selector = SolidityType::from(alloy_primitives::FixedBytes::<4>::from([
+            0xa9, 0x05, 0x9c, 0xbb,
+        ]));
+        assert!(matches!(&selector, SolidityType::FixedBytes(4, val) if val.len() == 4));
+        assert_eq!(selector.to_string(), "0xa9059cbb");
+
+        let balances = SolidityType::from(DynSolValue::FixedArray(vec![
+            DynSolValue::Uint(U256::from(1), 256),
+            DynSolValue::Uint(U256::from(2), 256),
+        ]));
+        assert!(matches!(&balances, SolidityType::SizedList(2, vals) if vals.len() == 2));
+        assert_eq!(balances.get("1"), U256::from(2));
+
+        // mapping keeps the size, filtering anything out doesn't
+        let doubled = balances.map(|val| val.clone() * U256::from(2));
+        assert!(matches!(doubled, SolidityType::SizedList(2, _)));
+        let filtered = balances.filter(|val| SolidityType::from(*val > U256::from(1)));
+        assert!(matches!(filtered, SolidityType::List(vals) if vals.len() == 1));
+
+        for value in [selector, balances] {
+            let json = serde_json::to_string(&value).unwrap();
+            let round_trip: SolidityType = serde_json::from_str(&json).unwrap();
+            assert!(round_trip.strict_eq(&value), "{json} didn't round trip");
+        }
     }
 }
@@ -1,18 +1,32 @@
 pub mod aliases;
 pub mod block_helpers;
+#[cfg(feature = "entity_changes")]
+pub mod entity_changes;
+pub mod errors;
 pub mod json_values;
 pub mod local_variables;
 pub mod macros;
+pub mod params;
+pub mod schema;
+pub mod signatures;
 pub mod store_helpers;
+pub mod time_buckets;
 pub mod type_casts;
 
 pub mod prelude {
     pub use crate::aliases::*;
     pub use crate::block_helpers::*;
+    #[cfg(feature = "entity_changes")]
+    pub use crate::entity_changes::*;
+    pub use crate::errors::*;
     pub use crate::json_values::*;
     pub use crate::local_variables::*;
     pub use crate::macros::*;
+    pub use crate::params::*;
+    pub use crate::schema::*;
+    pub use crate::signatures::*;
     pub use crate::store_helpers::*;
+    pub use crate::time_buckets::*;
     pub use crate::type_casts::*;
 
     pub use alloy_sol_macro::sol;
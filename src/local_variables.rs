@@ -1,35 +1,43 @@
 //! The purpose of this module is to allow us to have dynamically typed local variables in our substreams modules
 
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use crate::json_values::*;
 use crate::prelude::GenericStoreGet;
 use prost_wkt_types::Struct;
-use substreams::store::StoreGetProto;
+use substreams::store::{
+    DeltaProto, Deltas, StoreGetBigInt, StoreGetProto, StoreGetRaw, StoreGetString,
+};
 
 macro_rules! only {
-    ($value: expr, $variant: ident, $error_msg: expr) => {
-        if let LocalVar::$variant(val) = $value {
-            val
-        } else {
-            panic!($error_msg)
+    ($value: expr, $variant: ident, $error_msg: literal) => {
+        match $value {
+            LocalVar::$variant(val) => val,
+            other => panic!($error_msg, other.kind()),
         }
     };
 }
 
+/// Runs `$body` with `$store` bound to whichever store the var holds.
 macro_rules! only_store {
-    ($value: expr) => {
-        only!(
-            $value,
-            StoreGet,
-            "Tried to use a solidity type as a store module in get mode. Please don't do this!"
-        )
+    ($value: expr, $store: ident => $body: expr) => {
+        match $value {
+            LocalVar::StoreGet($store) => $body,
+            LocalVar::StoreGetBigInt($store) => $body,
+            LocalVar::StoreGetString($store) => $body,
+            LocalVar::StoreGetRaw($store) => $body,
+            other => panic!(
+                "Tried to use {} as a store module in get mode. Please don't do this!",
+                other.kind()
+            ),
+        }
     };
 }
 
 macro_rules! only_sol {
     ($value: expr) => {
-        only!($value, SolidityType, "Tried to use a store in get mode as a solidity value. Please don't do this! Use the get function to access values inside!")
+        only!($value, SolidityType, "Tried to use {} as a solidity value. Please don't do this! Use the get function to access values inside!")
     };
 }
 
@@ -38,6 +46,14 @@ pub enum LocalVar {
     SolidityType(SolidityType),
     /// A local variable of a store
     StoreGet(StoreGetProto<Struct>),
+    /// A store of counters, values are read as a Uint, or an Int if they're negative
+    StoreGetBigInt(StoreGetBigInt),
+    /// A store of strings, values are read as a String
+    StoreGetString(StoreGetString),
+    /// A store of bytes, values are read as a ByteArray
+    StoreGetRaw(StoreGetRaw),
+    /// The deltas of a store, see `LocalVar::deltas`
+    Deltas(Deltas<DeltaProto<Struct>>),
 }
 
 impl From<SolidityType> for LocalVar {
@@ -52,21 +68,65 @@ impl From<StoreGetProto<Struct>> for LocalVar {
     }
 }
 
+impl From<StoreGetBigInt> for LocalVar {
+    fn from(value: StoreGetBigInt) -> Self {
+        LocalVar::StoreGetBigInt(value)
+    }
+}
+
+impl From<StoreGetString> for LocalVar {
+    fn from(value: StoreGetString) -> Self {
+        LocalVar::StoreGetString(value)
+    }
+}
+
+impl From<StoreGetRaw> for LocalVar {
+    fn from(value: StoreGetRaw) -> Self {
+        LocalVar::StoreGetRaw(value)
+    }
+}
+
+impl From<Struct> for LocalVar {
+    fn from(value: Struct) -> Self {
+        LocalVar::SolidityType(value.into())
+    }
+}
+
+impl From<Deltas<DeltaProto<Struct>>> for LocalVar {
+    fn from(value: Deltas<DeltaProto<Struct>>) -> Self {
+        LocalVar::Deltas(value)
+    }
+}
+
+impl From<String> for LocalVar {
+    fn from(value: String) -> Self {
+        LocalVar::SolidityType(value.into())
+    }
+}
+
 impl From<Rc<LocalVar>> for LocalVar {
     fn from(value: Rc<LocalVar>) -> Self {
         Rc::into_inner(value).unwrap()
     }
 }
 
+/// Deltas are converted into their list of delta structs, see `LocalVar::deltas`.
 impl From<LocalVar> for SolidityType {
     fn from(value: LocalVar) -> Self {
-        only_sol!(value)
+        match value {
+            LocalVar::Deltas(deltas) => deltas.into(),
+            value => only_sol!(value),
+        }
     }
 }
 
 impl From<LocalVar> for StoreGetProto<Struct> {
     fn from(value: LocalVar) -> Self {
-        only_store!(value)
+        only!(
+            value,
+            StoreGet,
+            "Tried to use {} as a proto store module in get mode. Please don't do this!"
+        )
     }
 }
 
@@ -75,7 +135,226 @@ where
     K: AsRef<SolidityType> + ToString,
 {
     fn generic_get(&self, key: K) -> SolidityType {
-        let value = only_store!(&self);
-        value.generic_get(key)
+        only_store!(self, store => store.generic_get(key))
+    }
+
+    fn generic_get_first(&self, key: K) -> SolidityType {
+        only_store!(self, store => store.generic_get_first(key))
+    }
+
+    fn generic_get_at(&self, ordinal: u64, key: K) -> SolidityType {
+        only_store!(self, store => store.generic_get_at(ordinal, key))
+    }
+
+    fn generic_has(&self, key: K) -> bool {
+        only_store!(self, store => store.generic_has(key))
+    }
+
+    fn generic_has_at(&self, ordinal: u64, key: K) -> bool {
+        only_store!(self, store => store.generic_has_at(ordinal, key))
+    }
+}
+
+impl LocalVar {
+    pub fn into_sol(self) -> SolidityType {
+        self.into()
+    }
+
+    /// The deltas as a list of delta structs, the same as converting them into a SolidityType.
+    pub fn deltas(&self) -> SolidityType {
+        let deltas = only!(
+            self,
+            Deltas,
+            "Tried to use {} as the deltas of a store. Please don't do this!"
+        );
+        SolidityType::List(
+            deltas
+                .deltas
+                .iter()
+                .cloned()
+                .map(SolidityType::from)
+                .collect(),
+        )
+    }
+
+    /// What the var holds, for error messages.
+    fn kind(&self) -> &'static str {
+        match self {
+            LocalVar::SolidityType(_) => "a solidity type",
+            LocalVar::StoreGet(_) => "a proto store",
+            LocalVar::StoreGetBigInt(_) => "a bigint store",
+            LocalVar::StoreGetString(_) => "a string store",
+            LocalVar::StoreGetRaw(_) => "a raw store",
+            LocalVar::Deltas(_) => "store deltas",
+        }
+    }
+}
+
+/// The inputs of a module handler by name, built with the `locals!` macro.
+/// Vars are also kept in the order they were passed in, for indexed access.
+#[derive(Default)]
+pub struct LocalScope {
+    names: Vec<String>,
+    vars: HashMap<String, LocalVar>,
+}
+
+impl LocalScope {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a var to the end of the scope, replacing any var with the same name in place.
+    pub fn insert(&mut self, name: &str, var: impl Into<LocalVar>) {
+        if self.vars.insert(name.to_string(), var.into()).is_none() {
+            self.names.push(name.to_string());
+        }
+    }
+
+    pub fn get_var(&self, name: &str) -> &LocalVar {
+        self.vars
+            .get(name)
+            .unwrap_or_else(|| panic!("No local variable named {name}!"))
+    }
+
+    pub fn var_at(&self, index: usize) -> &LocalVar {
+        let name = self.names.get(index).unwrap_or_else(|| {
+            panic!(
+                "Tried to get local variable {index} of a scope with {} variables!",
+                self.names.len()
+            )
+        });
+        self.get_var(name)
+    }
+
+    /// The names of the vars, in the order they were passed in.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Looks up `key` in the store var `name`.
+    pub fn generic_get<K>(&self, name: &str, key: K) -> SolidityType
+    where
+        K: AsRef<SolidityType> + ToString,
+    {
+        self.get_var(name).generic_get(key)
+    }
+
+    /// Checks whether the store var `name` has `key`, without decoding its value.
+    pub fn generic_has<K>(&self, name: &str, key: K) -> bool
+    where
+        K: AsRef<SolidityType> + ToString,
+    {
+        self.get_var(name).generic_has(key)
+    }
+
+    /// Takes the solidity value var `name` out of the scope.
+    pub fn take_sol(&mut self, name: &str) -> SolidityType {
+        let var = self
+            .vars
+            .remove(name)
+            .unwrap_or_else(|| panic!("No local variable named {name}!"));
+        self.names.retain(|existing| existing != name);
+        var.into_sol()
+    }
+
+    /// The vars in the order they were passed in.
+    pub fn into_vec(mut self) -> Vec<LocalVar> {
+        self.names
+            .iter()
+            .filter_map(|name| self.vars.remove(name))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aliases::*, locals};
+    use substreams::pb::substreams::store_delta::Operation;
+
+    #[test]
+    fn test_locals() {
+        let amount = SolidityType::Uint(U256::from(7));
+        let params = "0xabc".to_string();
+        let prior = Struct::default();
+
+        let mut scope = locals![amount, params, prior];
+        assert_eq!(scope.names(), ["amount", "params", "prior"]);
+        assert!(matches!(
+            scope.get_var("params"),
+            LocalVar::SolidityType(SolidityType::String(val)) if val == "0xabc"
+        ));
+        assert!(matches!(
+            scope.var_at(0),
+            LocalVar::SolidityType(SolidityType::Uint(val)) if *val == U256::from(7)
+        ));
+
+        assert!(matches!(scope.take_sol("amount"), SolidityType::Uint(_)));
+        assert_eq!(scope.names(), ["params", "prior"]);
+        assert_eq!(scope.into_vec().len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Tried to use a solidity type as a store")]
+    fn test_get_first_on_value() {
+        let value = LocalVar::from(SolidityType::Null);
+        value.generic_get_first(SolidityType::String("pool".to_string()));
+    }
+
+    #[test]
+    fn test_deltas_var() {
+        let value =
+            crate::store_helpers::encode_stored_value(&SolidityType::Uint(U256::from(5))).unwrap();
+        let deltas = Deltas {
+            deltas: vec![DeltaProto {
+                operation: Operation::Create,
+                ordinal: 0,
+                key: "pool:0xa".to_string(),
+                old_value: Struct::default(),
+                new_value: value,
+            }],
+        };
+
+        let var = LocalVar::from(deltas);
+        let list = var.deltas();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.get("0").get("new_value"), U256::from(5));
+        assert!(matches!(list.get("0").get("key"), SolidityType::String(key) if key == "pool:0xa"));
+        assert_eq!(var.into_sol(), list);
+    }
+
+    #[test]
+    #[should_panic(expected = "Tried to use store deltas as a store module")]
+    fn test_get_on_deltas() {
+        let var = LocalVar::from(Deltas::<DeltaProto<Struct>> { deltas: vec![] });
+        var.generic_get(SolidityType::String("pool".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Tried to use a solidity type as a store")]
+    fn test_has_on_value() {
+        let value = LocalVar::from(SolidityType::Null);
+        value.generic_has(SolidityType::String("pool".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Tried to use a solidity type as the deltas of a store")]
+    fn test_deltas_on_value() {
+        LocalVar::from(SolidityType::Null).deltas();
+    }
+
+    #[test]
+    #[should_panic(expected = "No local variable named missing!")]
+    fn test_locals_missing() {
+        let amount = SolidityType::Null;
+        locals![amount].get_var("missing");
     }
 }
@@ -10,28 +10,57 @@ macro_rules! loose_sol {
     };
 }
 
+/// Builds a struct named `$map_ident` in the body, and evaluates to it as an `Option<Struct>`, see `SolidityType::to_proto_struct`.
+/// A handler returning `Result<Option<Struct>, substreams::errors::Error>` wraps it, ie `Ok(with_map!(map, ...))`,
+/// and `?` in the body returns from the handler.
 #[macro_export]
 macro_rules! with_map {
-    ($map_ident: ident ,$($body:tt)*) => {
-        let mut $map_ident: SolidityType = SolidityType::Struct(HashMap::new());
+    ($map_ident: ident ,$($body:tt)*) => {{
+        let mut $map_ident: SolidityType = SolidityType::Struct($crate::json_values::IndexMap::new());
 
         $($body)*
 
-        if let Some(val) = $map_ident.to_maybe_value() {
-            let val = serde_json::to_value(val).unwrap();
-            serde_json::from_value(val).unwrap()
-        } else {
-            None
-        }
-    };
+        $map_ident.to_proto_struct()
+    }};
+}
+
+/// Same as `with_map!`, but logs the finished struct with `substreams::log::println`, which is too slow to leave on in hot handlers.
+#[macro_export]
+macro_rules! with_map_logged {
+    ($map_ident: ident ,$($body:tt)*) => {{
+        let mut $map_ident: SolidityType = SolidityType::Struct($crate::json_values::IndexMap::new());
+
+        $($body)*
+
+        ::substreams::log::println(format!("{:?}", $map_ident));
+        $map_ident.to_proto_struct()
+    }};
 }
 
+/// Builds a struct from `key; value` pairs, where keys can be anything with a ToString impl.
+/// A leading `..base` spread clones the entries of `base` first, ie `map_literal!{ ..event, "block"; block_number }`.
 #[macro_export]
 macro_rules! map_literal {
+    (..$base: expr $(, $key: expr; $val: expr)* $(,)?) => {{
+        let mut map: SolidityType = SolidityType::Struct($crate::json_values::IndexMap::new());
+        map.merge(::std::clone::Clone::clone(&$base));
+
+        $(map.insert(&::std::string::ToString::to_string(&$key), $val.into());)*
+
+        if let SolidityType::Struct(ref values) = map {
+            if values.is_empty() {
+                SolidityType::Null
+            } else {
+                map
+            }
+        } else {
+            panic!("Solidity Struct Magically Switched to Something not a struct!")
+        }
+    }};
     ($($key: expr; $val: expr),*) => {{
-        let mut map: SolidityType = SolidityType::Struct(HashMap::new());
+        let mut map: SolidityType = SolidityType::Struct($crate::json_values::IndexMap::new());
 
-        $(map.insert($key, $val.into());)*
+        $(map.insert(&::std::string::ToString::to_string(&$key), $val.into());)*
 
         if let SolidityType::Struct(ref values) = map {
             if values.is_empty() {
@@ -68,37 +97,272 @@ macro_rules! filter {
     };
 }
 
+#[macro_export]
+macro_rules! reduce {
+    ($value: expr, $init: expr, $callback: expr) => {
+        $value.fold($init, $callback)
+    };
+}
+
 /// A helper macro that allows us to convert any map into an array
+/// A serialized SolidityType list or tuple gives back the untyped json of its values, use `SolidityType::into_vec` to keep their types.
+#[deprecated(note = "use SolidityType::into_vec, which keeps the element types")]
 #[macro_export]
 macro_rules! to_array {
     ($value: expr) => {
-        if let Some(value) = $value {
-            let as_value: serde_json::Value = serde_json::to_value(value).expect(
-                "Couldn't convert value into serde_json Value in as_array! macro invocation",
-            );
-            match as_value {
+        // a value that can't be serialized, like a Null, isn't an array either
+        match $value.and_then(|value| serde_json::to_value(value).ok()) {
+            Some(as_value) => match as_value {
                 serde_json::Value::Array(arr) => Some(arr),
+                // the tagged form of a SolidityType, a sized list's value is [size, values]
+                serde_json::Value::Object(mut map) => {
+                    match (map.remove("type"), map.remove("value")) {
+                        (Some(kind), Some(serde_json::Value::Array(arr)))
+                            if kind == "list" || kind == "tuple" =>
+                        {
+                            Some(arr)
+                        }
+                        (Some(kind), Some(serde_json::Value::Array(mut sized)))
+                            if kind == "sizedList" && sized.len() == 2 =>
+                        {
+                            match sized.pop() {
+                                Some(serde_json::Value::Array(arr)) => Some(arr),
+                                _ => None,
+                            }
+                        }
+                        _ => None,
+                    }
+                }
                 _ => None,
-            }
-        } else {
-            None
+            },
+            None => None,
         }
     };
 }
 
 /// converts all inputs to a module that are a Struct protobuf into a Map with the same ident.
+/// Other inputs take an annotation, ie `format_inputs!(prior, params: string, pools: store, transfers: deltas, config: optional)`.
+/// `string` wraps a String param, `store` makes a `LocalVar` of a store, `deltas` converts deltas into their list of delta structs,
+/// and `optional` converts an `Option<Struct>`, with None as Null.
 #[macro_export]
 macro_rules! format_inputs {
-    ($($input: ident),*) => {
-        $(let $input = SolidityType::from($input);)*
+    ($($input: ident $(: $kind: ident)?),* $(,)?) => {
+        $($crate::format_inputs!(@input $input $(: $kind)?);)*
+    };
+    (@input $input: ident) => {
+        let $input = $crate::json_values::SolidityType::from($input);
+    };
+    (@input $input: ident: string) => {
+        let $input = $crate::json_values::SolidityType::String($input);
+    };
+    (@input $input: ident: store) => {
+        let $input = $crate::local_variables::LocalVar::from($input);
+    };
+    (@input $input: ident: deltas) => {
+        let $input = $crate::json_values::SolidityType::from($input);
+    };
+    (@input $input: ident: optional) => {
+        let $input = $input.map_or($crate::json_values::SolidityType::Null, $crate::json_values::SolidityType::from);
     };
 }
 
 /// A macro that allows us to convert a string, to a particular solidity type.
-/// This is syntax sugar for parsing the string and wrapping in appropriate types
+/// This is syntax sugar for `parse_sol_type`, panicking with the string and the variant if it doesn't parse.
 #[macro_export]
 macro_rules! sol_type {
     ($variant: ident, $str: expr) => {
-        SolidityType::$variant($str.parse().unwrap())
+        $crate::json_values::parse_sol_type(
+            stringify!($variant),
+            ::std::convert::AsRef::<str>::as_ref(&$str),
+        )
+        .unwrap_or_else(|err| panic!("{err}"))
+    };
+}
+
+/// Same as `sol_type!`, but None if the string doesn't parse.
+#[macro_export]
+macro_rules! try_sol_type {
+    ($variant: ident, $str: expr) => {
+        $crate::json_values::parse_sol_type(
+            stringify!($variant),
+            ::std::convert::AsRef::<str>::as_ref(&$str),
+        )
+        .ok()
+    };
+}
+
+/// Wraps a handler's inputs into a `LocalScope`, named after their idents, ie `locals![blk_meta, my_store, prior_map]`.
+/// Each input is converted with its `From` impl for `LocalVar`, so an unsupported type is a compile error.
+/// ```compile_fail,E0277
+/// use substreams_alloy_helpers::locals;
+///
+/// let count: u32 = 1;
+/// let _scope = locals![count];
+/// ```
+#[macro_export]
+macro_rules! locals {
+    ($($input: ident),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut scope = $crate::local_variables::LocalScope::new();
+        $(scope.insert(stringify!($input), $input);)*
+        scope
+    }};
+}
+
+/// Decodes several event types from a block in a single pass, see `EventSet`.
+/// Evaluates to a struct keyed by event name, ie `events_from_block!(blk, [POOL], Swap, Mint, Burn).get("Swap")`.
+/// An empty address list decodes the events of every contract.
+#[macro_export]
+macro_rules! events_from_block {
+    ($blk: expr, [$($address: expr),* $(,)?], $($event: ty),+ $(,)?) => {{
+        let addresses: &[&$crate::aliases::Address] = &[$(&$address),*];
+        $crate::block_helpers::EventSet::new()
+            $(.with::<$event>())+
+            .get_events(&$blk, addresses)
+    }};
+}
+
+/// Issues several calls as one eth_call batch, see `RpcBatchHelper`. Failed calls are Null.
+/// With names it's a struct, ie `rpc_batch! { token0: pool => token0Call {}, fee: pool => feeCall {} }.get("fee")`,
+/// without them it's a list in the order of the calls, ie `rpc_batch![pool => token0Call {}, pool => token1Call {}]`.
+#[macro_export]
+macro_rules! rpc_batch {
+    ($($name: ident: $to: expr => $call: expr),+ $(,)?) => {{
+        let results = $crate::block_helpers::RpcBatchHelper::new()
+            $(.add(&$to, &$call))+
+            .execute();
+        let names = [$(stringify!($name)),+];
+        $crate::json_values::SolidityType::Struct(
+            names
+                .iter()
+                .enumerate()
+                .map(|(index, name)| (name.to_string(), results.get(&index.to_string())))
+                .collect(),
+        )
+    }};
+    ($($to: expr => $call: expr),+ $(,)?) => {
+        $crate::block_helpers::RpcBatchHelper::new()
+            $(.add(&$to, &$call))+
+            .execute()
+    };
+}
+
+/// Writes the events of a block to a store, one `event: ...` group per event type.
+/// Each event is written under the key returned by `key`, and events with a Null key are skipped.
+/// The groups are decoded one after another with `EventHelpers::get_events`, and a block without any events writes nothing.
+/// Evaluates to the total number of writes.
+/// ```ignore
+/// event_store! {
+///     block: blk,
+///     store: my_store,
+///     event: Transfer, addresses: [POOL], key: |e| e.get("to"), value: |e| e;
+///     event: Approval, addresses: [], key: |e| e.get("owner"), value: |e| e.get("value");
+/// }
+/// ```
+#[macro_export]
+macro_rules! event_store {
+    (
+        block: $blk: expr,
+        store: $store: expr,
+        $(event: $event: ty, addresses: [$($address: expr),* $(,)?], key: $key: expr, value: $value: expr);+ $(;)?
+    ) => {{
+        let blk = &$blk;
+        let store = &$store;
+        let mut writes = 0usize;
+        $(
+            let addresses: &[&$crate::aliases::Address] = &[$(&$address),*];
+            let events = <$event as $crate::block_helpers::EventHelpers>::get_events(blk, addresses);
+            writes += $crate::store_helpers::write_events(store, events, $key, $value);
+        )+
+        writes
+    }};
+}
+
+/// Declares a full substreams map module.
+/// Each input is annotated with its kind, which picks both its type in the handler signature and how it's converted:
+/// - `block`: the `Block`, left as is
+/// - `store`: a `StoreGetProto<Struct>`, wrapped into a `LocalVar`
+/// - `deltas`: a `Deltas<DeltaProto<Struct>>`, converted into a `SolidityType::List`
+/// - `map`: a prior module's `Struct` output, converted into a `SolidityType`
+/// - `params`: the module's `String` params, converted into a `SolidityType::String`
+///
+/// The output ident is bound to an empty `SolidityType::Struct`, which is returned as an `Option<Struct>`.
+/// If the output ends up empty, the module returns None.
+/// The body is also declared as `$name::handler`, so the module can be called outside of the substreams runtime.
+/// ```ignore
+/// sol_module! {
+///     fn map_pools(blk: block, pools: store) -> output {
+///         output.insert("number", SolidityType::Uint(U256::from(blk.number)));
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! sol_module {
+    (fn $name: ident($($inputs: tt)*) -> $output: ident $body: block) => {
+        $crate::sol_module!(@munch $name, $output, $body, [], [], [], $($inputs)*);
+    };
+
+    (@munch $name: ident, $output: ident, $body: block, [$($params: tt)*], [$($convert: tt)*], [$($args: ident,)*], $input: ident: block $(, $($rest: tt)*)?) => {
+        $crate::sol_module!(@munch $name, $output, $body,
+            [$($params)* $input: ::substreams_ethereum::pb::eth::v2::Block,],
+            [$($convert)*],
+            [$($args,)* $input,],
+            $($($rest)*)?);
+    };
+
+    (@munch $name: ident, $output: ident, $body: block, [$($params: tt)*], [$($convert: tt)*], [$($args: ident,)*], $input: ident: store $(, $($rest: tt)*)?) => {
+        $crate::sol_module!(@munch $name, $output, $body,
+            [$($params)* $input: ::substreams::store::StoreGetProto<::prost_wkt_types::Struct>,],
+            [$($convert)* let $input = $crate::local_variables::LocalVar::from($input);],
+            [$($args,)* $input,],
+            $($($rest)*)?);
+    };
+
+    (@munch $name: ident, $output: ident, $body: block, [$($params: tt)*], [$($convert: tt)*], [$($args: ident,)*], $input: ident: deltas $(, $($rest: tt)*)?) => {
+        $crate::sol_module!(@munch $name, $output, $body,
+            [$($params)* $input: ::substreams::store::Deltas<::substreams::store::DeltaProto<::prost_wkt_types::Struct>>,],
+            [$($convert)* let $input = $crate::json_values::SolidityType::from($input);],
+            [$($args,)* $input,],
+            $($($rest)*)?);
+    };
+
+    (@munch $name: ident, $output: ident, $body: block, [$($params: tt)*], [$($convert: tt)*], [$($args: ident,)*], $input: ident: map $(, $($rest: tt)*)?) => {
+        $crate::sol_module!(@munch $name, $output, $body,
+            [$($params)* $input: ::prost_wkt_types::Struct,],
+            [$($convert)* let $input = $crate::json_values::SolidityType::from($input);],
+            [$($args,)* $input,],
+            $($($rest)*)?);
+    };
+
+    (@munch $name: ident, $output: ident, $body: block, [$($params: tt)*], [$($convert: tt)*], [$($args: ident,)*], $input: ident: params $(, $($rest: tt)*)?) => {
+        $crate::sol_module!(@munch $name, $output, $body,
+            [$($params)* $input: String,],
+            [$($convert)* let $input = $crate::json_values::SolidityType::String($input);],
+            [$($args,)* $input,],
+            $($($rest)*)?);
+    };
+
+    (@munch $name: ident, $output: ident, $body: block, [$($params: tt)*], [$($convert: tt)*], [$($args: ident,)*], ) => {
+        pub mod $name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            /// The module's body, callable outside of the substreams runtime, ie from tests.
+            pub fn handler($($params)*) -> Option<::prost_wkt_types::Struct> {
+                $($convert)*
+
+                #[allow(unused_mut)]
+                let mut $output = $crate::json_values::SolidityType::Struct($crate::json_values::IndexMap::new());
+
+                $body
+
+                $output.to_proto_struct()
+            }
+        }
+
+        #[substreams::handlers::map]
+        fn $name($($params)*) -> Option<::prost_wkt_types::Struct> {
+            $name::handler($($args),*)
+        }
     };
 }
@@ -0,0 +1,159 @@
+//! Parses a module's params string, ie "factory=0xabc...&start=12000000", into typed values.
+
+use crate::{
+    aliases::*,
+    errors::ConvertError,
+    json_values::{GuessConfig, GuessValue, IndexMap, SolidityType},
+};
+use serde_json::Value;
+
+/// Splits `raw` on '&' and '=' into a struct keyed by param name, guessing the type of each value.
+/// Addresses, digit strings as uints and "true" / "false" as bools are recognized, anything else stays a string.
+/// Repeated keys and comma separated values, ie "pools=0xabc...,0xdef...", become lists, and a key without a value is true.
+/// The params are in the order their key first appears.
+pub fn parse_params(raw: &str) -> SolidityType {
+    let mut params: IndexMap<String, Vec<SolidityType>> = IndexMap::new();
+    for pair in raw
+        .split('&')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+    {
+        let (key, value) = match pair.split_once('=') {
+            Some((key, value)) => (key.trim(), Some(value)),
+            None => (pair, None),
+        };
+
+        let values = params.entry(key.to_string()).or_default();
+        match value {
+            Some(value) => values.extend(value.split(',').map(|value| guess_param(value.trim()))),
+            None => values.push(true.into()),
+        }
+    }
+
+    SolidityType::Struct(
+        params
+            .into_iter()
+            .map(|(key, mut values)| {
+                let value = if values.len() == 1 {
+                    values.pop().unwrap()
+                } else {
+                    SolidityType::List(values)
+                };
+                (key, value)
+            })
+            .collect(),
+    )
+}
+
+fn guess_param(value: &str) -> SolidityType {
+    match value {
+        "true" => true.into(),
+        "false" => false.into(),
+        _ => {
+            let config = GuessConfig {
+                numeric_strings: true,
+                ..Default::default()
+            };
+            SolidityType::guess_json_value_with(Value::String(value.to_string()), &config).unwrap()
+        }
+    }
+}
+
+/// Reads the address param `name` out of the output of `parse_params`.
+pub fn get_address_param(params: &SolidityType, name: &str) -> Result<Address, ConvertError> {
+    match params.get(name) {
+        SolidityType::Address(address) => Ok(address),
+        SolidityType::Null => Err(missing_param(name)),
+        other => Err(invalid_param(name, "an address", &other)),
+    }
+}
+
+/// Reads the uint param `name` out of the output of `parse_params`.
+pub fn get_uint_param(params: &SolidityType, name: &str) -> Result<U256, ConvertError> {
+    match params.get(name) {
+        SolidityType::Uint(uint) => Ok(uint),
+        SolidityType::Null => Err(missing_param(name)),
+        other => Err(invalid_param(name, "a uint", &other)),
+    }
+}
+
+fn missing_param(name: &str) -> ConvertError {
+    ConvertError::new(Some(name.to_string()), "the param is missing".to_string())
+}
+
+fn invalid_param(name: &str, expected: &str, found: &SolidityType) -> ConvertError {
+    ConvertError::new(
+        Some(name.to_string()),
+        format!("expected {expected}, found `{}`", found.to_string()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FACTORY: &str = "0x1f98431c8ad98523631ae4a59f267346ea31f984";
+    const POOL: &str = "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640";
+
+    #[test]
+    fn test_parse_params() {
+        let params = parse_params(&format!(
+            "factory={FACTORY}&start=12000000&debug=true&name=pools"
+        ));
+
+        let factory: Address = params.get("factory").try_into().unwrap();
+        assert_eq!(factory, FACTORY.parse::<Address>().unwrap());
+        assert_eq!(get_address_param(&params, "factory").unwrap(), factory);
+        assert_eq!(
+            get_uint_param(&params, "start").unwrap(),
+            U256::from(12_000_000)
+        );
+        assert!(matches!(params.get("debug"), SolidityType::Boolean(val) if val == U1::from(1)));
+        assert!(matches!(params.get("name"), SolidityType::String(name) if name == "pools"));
+
+        assert!(matches!(parse_params(""), SolidityType::Struct(map) if map.is_empty()));
+    }
+
+    #[test]
+    fn test_param_lists() {
+        let params = parse_params(&format!(
+            "pools={FACTORY},{POOL}&token={POOL}&token={FACTORY}"
+        ));
+
+        for key in ["pools", "token"] {
+            let SolidityType::List(addresses) = params.get(key) else {
+                panic!("Expected {key} to be a list");
+            };
+            assert_eq!(addresses.len(), 2);
+            assert!(addresses
+                .iter()
+                .all(|address| matches!(address, SolidityType::Address(_))));
+        }
+        assert_eq!(
+            params.get("pools").get("1"),
+            POOL.parse::<Address>().unwrap()
+        );
+        assert_eq!(
+            params.get("token").get("0"),
+            POOL.parse::<Address>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_param_errors() {
+        let params = parse_params("factory=0x1f98431c8ad98523631&start=soon");
+
+        let err = get_address_param(&params, "factory").unwrap_err();
+        assert_eq!(err.field.as_deref(), Some("factory"));
+
+        let err = get_uint_param(&params, "start").unwrap_err();
+        assert_eq!(err.field.as_deref(), Some("start"));
+        assert!(err.to_string().contains("soon"), "{err}");
+
+        let err = get_address_param(&params, "pool").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Couldn't convert field `pool`: the param is missing"
+        );
+    }
+}
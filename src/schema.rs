@@ -0,0 +1,229 @@
+//! Describes the shape of a SolidityType, ie to see what get_events produces or to write a subgraph schema for it.
+
+use crate::json_values::SolidityType;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schema {
+    Boolean,
+    Enum,
+    Uint,
+    Int,
+    Address,
+    Bytes,
+    Bytes32,
+    String,
+    Tuple(Vec<Schema>),
+    /// The unified schema of every element in the list.
+    List(Box<Schema>),
+    Struct(BTreeMap<String, Schema>),
+    /// Null, an empty list, or list elements whose schemas disagree.
+    Unknown,
+}
+
+impl SolidityType {
+    /// Records the variant kinds and struct keys of the value recursively.
+    pub fn schema(&self) -> Schema {
+        match self {
+            SolidityType::Boolean(_) => Schema::Boolean,
+            SolidityType::Enum(_) => Schema::Enum,
+            SolidityType::Uint(_) => Schema::Uint,
+            SolidityType::Int(_) => Schema::Int,
+            SolidityType::Address(_) => Schema::Address,
+            SolidityType::ByteArray(_) | SolidityType::FixedBytes(..) => Schema::Bytes,
+            SolidityType::FixedArray(_) => Schema::Bytes32,
+            SolidityType::String(_) => Schema::String,
+            SolidityType::Tuple(vals) => {
+                Schema::Tuple(vals.iter().map(|val| val.schema()).collect())
+            }
+            SolidityType::List(vals) | SolidityType::SizedList(_, vals) => {
+                let item = vals
+                    .iter()
+                    .map(|val| val.schema())
+                    .reduce(Schema::unify)
+                    .unwrap_or(Schema::Unknown);
+                Schema::List(Box::new(item))
+            }
+            SolidityType::Struct(map) => Schema::Struct(
+                map.iter()
+                    .map(|(key, val)| (key.clone(), val.schema()))
+                    .collect(),
+            ),
+            SolidityType::Null => Schema::Unknown,
+        }
+    }
+
+    /// Renders the schema of the value as GraphQL type definitions, see `Schema::to_graphql_type`.
+    pub fn to_graphql_type(&self, name: &str) -> String {
+        self.schema().to_graphql_type(name)
+    }
+}
+
+impl Schema {
+    /// Merges two schemas of the same shape, so a list with some Null or partially filled elements still gets a full schema.
+    /// Struct keys are unioned, and anything that really disagrees becomes Unknown.
+    fn unify(self, other: Schema) -> Schema {
+        match (self, other) {
+            (Schema::Unknown, other) | (other, Schema::Unknown) => other,
+            (Schema::Tuple(lh), Schema::Tuple(rh)) if lh.len() == rh.len() => {
+                Schema::Tuple(lh.into_iter().zip(rh).map(|(l, r)| l.unify(r)).collect())
+            }
+            (Schema::List(lh), Schema::List(rh)) => Schema::List(Box::new(lh.unify(*rh))),
+            (Schema::Struct(mut lh), Schema::Struct(rh)) => {
+                for (key, r) in rh {
+                    let unified = match lh.remove(&key) {
+                        Some(l) => l.unify(r),
+                        None => r,
+                    };
+                    lh.insert(key, unified);
+                }
+                Schema::Struct(lh)
+            }
+            (lh, rh) if lh == rh => lh,
+            _ => Schema::Unknown,
+        }
+    }
+
+    /// Renders GraphQL type definitions using The Graph's scalars, uints are BigInt and every kind of bytes is Bytes.
+    /// Nested structs and tuples get their own type named after the field, ie `SwapMeta` for the `meta` field of `Swap`,
+    /// and tuple members are named `_0`, `_1`, etc.
+    pub fn to_graphql_type(&self, name: &str) -> String {
+        let mut types = Vec::new();
+        self.graphql_object(name, &mut types);
+        types.join("\n\n")
+    }
+
+    fn graphql_object(&self, name: &str, types: &mut Vec<String>) {
+        let fields: Vec<(String, &Schema)> = match self {
+            Schema::Struct(fields) => fields.iter().map(|(key, val)| (key.clone(), val)).collect(),
+            Schema::Tuple(members) => members
+                .iter()
+                .enumerate()
+                .map(|(index, val)| (format!("_{index}"), val))
+                .collect(),
+            other => vec![("value".to_string(), other)],
+        };
+
+        // reserve our slot so the parent type is rendered before its children
+        let index = types.len();
+        types.push(String::new());
+
+        let lines: Vec<String> = fields
+            .iter()
+            .map(|(key, val)| {
+                let field_name = format!("{name}{}", pascal_case(key));
+                format!("  {key}: {}", val.graphql_field_type(&field_name, types))
+            })
+            .collect();
+        types[index] = format!("type {name} {{\n{}\n}}", lines.join("\n"));
+    }
+
+    fn graphql_field_type(&self, name: &str, types: &mut Vec<String>) -> String {
+        match self {
+            Schema::Boolean => "Boolean!".to_string(),
+            Schema::Enum | Schema::Uint | Schema::Int => "BigInt!".to_string(),
+            Schema::Address | Schema::Bytes | Schema::Bytes32 => "Bytes!".to_string(),
+            Schema::String => "String!".to_string(),
+            // a Null, an empty list, or list elements that disagree, ie a uint next to a string,
+            // so there's no one type to give it and it's left a nullable String
+            Schema::Unknown => "String".to_string(),
+            Schema::List(item) => format!("[{}]!", item.graphql_field_type(name, types)),
+            Schema::Tuple(_) | Schema::Struct(_) => {
+                self.graphql_object(name, types);
+                format!("{name}!")
+            }
+        }
+    }
+}
+
+/// ie `block_number` becomes `BlockNumber`
+fn pascal_case(key: &str) -> String {
+    key.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aliases::*, map_literal};
+
+    fn swap(amount: u64, with_meta: bool) -> SolidityType {
+        let meta = if with_meta {
+            map_literal! { "block_number"; SolidityType::Uint(U256::from(42)) }
+        } else {
+            SolidityType::Null
+        };
+
+        map_literal! {
+            "pool"; SolidityType::Address(Address::ZERO),
+            "swaps"; SolidityType::List(vec![
+                SolidityType::Tuple(vec![
+                    SolidityType::Uint(U256::from(amount)),
+                    SolidityType::Address(Address::ZERO),
+                ]),
+                SolidityType::Tuple(vec![
+                    SolidityType::Uint(U256::from(amount + 1)),
+                    SolidityType::Address(Address::ZERO),
+                ]),
+            ]),
+            "meta"; meta
+        }
+    }
+
+    #[test]
+    fn test_schema() {
+        let schema = swap(1, true).schema();
+        let Schema::Struct(fields) = &schema else {
+            panic!("Expected a struct schema, got {schema:?}");
+        };
+        assert_eq!(fields["pool"], Schema::Address);
+        assert_eq!(
+            fields["swaps"],
+            Schema::List(Box::new(Schema::Tuple(vec![Schema::Uint, Schema::Address])))
+        );
+
+        // a list of events where only some have meta still gets the full shape
+        let events = SolidityType::List(vec![swap(1, false), swap(2, true)]);
+        let Schema::List(item) = events.schema() else {
+            panic!("Expected a list schema");
+        };
+        assert_eq!(*item, schema);
+    }
+
+    #[test]
+    fn test_to_graphql_type() {
+        let expected = "type Swap {
+  meta: SwapMeta!
+  pool: Bytes!
+  swaps: [SwapSwaps!]!
+}
+
+type SwapMeta {
+  block_number: BigInt!
+}
+
+type SwapSwaps {
+  _0: BigInt!
+  _1: Bytes!
+}";
+        assert_eq!(swap(1, true).to_graphql_type("Swap"), expected);
+
+        let mixed = map_literal! {
+            "values"; SolidityType::List(vec![
+                SolidityType::Uint(U256::from(1)),
+                SolidityType::String("one".to_string()),
+            ])
+        };
+        assert_eq!(
+            mixed.to_graphql_type("Mixed"),
+            "type Mixed {\n  values: [String]!\n}"
+        );
+    }
+}
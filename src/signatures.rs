@@ -0,0 +1,82 @@
+//! Helpers for matching raw logs and calls by their signatures, without needing to fully decode them.
+
+use crate::{aliases::*, json_values::SolidityType, map_literal};
+use alloy_primitives::B256;
+use alloy_sol_types::{SolCall, SolEvent};
+use substreams_ethereum::pb::eth::v2::Log;
+
+/// The topic0 of an event, ie keccak256("Transfer(address,address,uint256)")
+pub fn event_signature_hash<T: SolEvent>() -> B256 {
+    T::SIGNATURE_HASH
+}
+
+/// The 4 byte selector of a function call
+pub fn call_selector<T: SolCall>() -> [u8; 4] {
+    T::SELECTOR
+}
+
+/// Converts a log into a struct with the "address", "topics" and "data" of the log, without any ABI knowledge.
+/// This is useful as a catch all for logs that none of the known events matched.
+pub fn raw_log_to_sol(log: &Log) -> SolidityType {
+    let address = if log.address.len() == 20 {
+        SolidityType::Address(Address::from_slice(&log.address))
+    } else {
+        SolidityType::Null
+    };
+
+    let topics = log
+        .topics
+        .iter()
+        .filter(|topic| topic.len() == 32)
+        .map(|topic| SolidityType::FixedArray(B256::from_slice(topic)))
+        .collect();
+
+    map_literal!(
+        "address"; address,
+        "topics"; SolidityType::List(topics),
+        "data"; SolidityType::ByteArray(Bytes::copy_from_slice(&log.data))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::b256;
+    use alloy_sol_macro::sol;
+
+    sol! {
+        event Transfer(address indexed from, address indexed to, uint256 value);
+        function transfer(address to, uint256 amount) returns (bool);
+    }
+
+    #[test]
+    fn test_signatures() {
+        assert_eq!(
+            event_signature_hash::<Transfer>(),
+            b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef")
+        );
+        assert_eq!(call_selector::<transferCall>(), [0xa9, 0x05, 0x9c, 0xbb]);
+    }
+
+    #[test]
+    fn test_raw_log_to_sol() {
+        let log = Log {
+            address: vec![1; 20],
+            topics: vec![vec![2; 32], vec![3; 32]],
+            data: vec![4, 5],
+            ..Default::default()
+        };
+
+        let value = raw_log_to_sol(&log);
+        assert_eq!(value.get("address"), Address::from_slice(&[1; 20]));
+        assert_eq!(value.get("topics").len(), 2);
+        assert!(matches!(
+            value.get("topics").first(),
+            SolidityType::FixedArray(topic) if topic == B256::from_slice(&[2; 32])
+        ));
+        assert!(matches!(
+            value.get("data"),
+            SolidityType::ByteArray(data) if data.to_vec() == vec![4, 5]
+        ));
+    }
+}
@@ -1,28 +1,123 @@
-use crate::prelude::SolidityType;
-use prost_wkt_types::Struct as ProtoStruct;
+use crate::{
+    block_helpers::event_ordinal,
+    map_literal,
+    prelude::{
+        ConvertError, DecimalUints, EmptyPolicy, GuessValue, IndexMap, SolError, SolidityType,
+    },
+};
+use prost_wkt_types::{value::Kind, Struct as ProtoStruct, Value as ProtoValue};
 use serde::Serialize;
+use serde_json::Value;
+use std::{
+    any::TypeId,
+    cell::{Cell, OnceCell, RefCell},
+    collections::HashMap,
+};
 use substreams::prelude::*;
+use substreams::scalar::BigInt;
 
 pub trait GenericStore<K, V> {
+    /// Sets the value at ordinal 0. When a key is set more than once in a block, whichever set happens to run last wins,
+    /// regardless of the order of the logs it came from. Use `generic_set_at` with the log ordinal instead.
     fn generic_set(&self, key: K, value: V);
 
+    /// Same as `generic_set`, at `ordinal`, ie the "ordinal" of an event's tx_meta, see `event_ordinal`.
+    /// The substreams set stores write at the ordinal. Stores that don't track ordinals, like mocks, fall back to `generic_set`
+    /// and ignore it, so for them whichever set runs last wins.
+    fn generic_set_at(&self, ordinal: u64, key: K, value: V) {
+        let _ = ordinal;
+        self.generic_set(key, value);
+    }
+
+    /// Flattens the value with `separator` before setting it, see `SolidityType::flatten`.
+    fn generic_set_flattened(&self, key: K, value: V, separator: &str)
+    where
+        V: AsRef<SolidityType>,
+        Self: GenericStore<K, SolidityType>,
+    {
+        let value = value.as_ref().flatten(separator);
+        GenericStore::<K, SolidityType>::generic_set(self, key, value);
+    }
+
+    /// Sets the value with its uints written as decimal strings rather than hex, see `DecimalUints`.
+    fn generic_set_decimal(&self, key: K, value: V)
+    where
+        V: AsRef<SolidityType>,
+        Self: GenericStore<K, DecimalUints>,
+    {
+        let value = DecimalUints(value.as_ref().clone());
+        GenericStore::<K, DecimalUints>::generic_set(self, key, value);
+    }
+
+    /// Prunes the value with `EmptyPolicy::Skip` before setting it, see `SolidityType::to_maybe_value`.
+    /// Nothing is written if there's nothing left.
+    fn generic_set_pruned(&self, key: K, value: V)
+    where
+        V: AsRef<SolidityType>,
+        Self: GenericStore<K, SolidityType>,
+    {
+        GenericStore::<K, V>::generic_set_pruned_with(self, key, value, EmptyPolicy::Skip);
+    }
+
+    /// Prunes the value before setting it, see `SolidityType::prune`.
+    fn generic_set_pruned_with(&self, key: K, value: V, policy: EmptyPolicy)
+    where
+        V: AsRef<SolidityType>,
+        Self: GenericStore<K, SolidityType>,
+    {
+        if let Some(value) = value.as_ref().prune(policy) {
+            GenericStore::<K, SolidityType>::generic_set(self, key, value);
+        }
+    }
+
     fn generic_delete_prefix(&self, prefix: K);
 }
 
 pub trait GenericStoreGet<K> {
+    /// The value at the end of the block so far, as of the last write. A missing key is Null.
     fn generic_get(&self, key: K) -> SolidityType;
+
+    /// The value at the start of the block, before any of its writes. A missing key is Null.
+    /// Stores without a history, like mocks, fall back to `generic_get`.
+    fn generic_get_first(&self, key: K) -> SolidityType {
+        self.generic_get(key)
+    }
+
+    /// The value as of `ordinal` in the block. A missing key is Null.
+    /// Stores without a history, like mocks, fall back to `generic_get`.
+    fn generic_get_at(&self, ordinal: u64, key: K) -> SolidityType {
+        let _ = ordinal;
+        self.generic_get(key)
+    }
+
+    /// Whether `key` has a value at the end of the block so far, without decoding it.
+    /// Stores without a cheaper check, like mocks, fall back to `generic_get`.
+    fn generic_has(&self, key: K) -> bool {
+        !matches!(self.generic_get(key), SolidityType::Null)
+    }
+
+    /// Whether `key` has a value as of `ordinal` in the block, without decoding it.
+    /// Stores without a cheaper check, like mocks, fall back to `generic_get_at`.
+    fn generic_has_at(&self, ordinal: u64, key: K) -> bool {
+        !matches!(self.generic_get_at(ordinal, key), SolidityType::Null)
+    }
 }
 
 impl<K, V> GenericStore<K, V> for StoreSetProto<ProtoStruct>
 where
     K: AsRef<SolidityType> + ToString,
-    V: AsRef<SolidityType> + Serialize,
+    V: AsRef<SolidityType> + StoredValue,
 {
     fn generic_set(&self, key: K, value: V) {
+        self.generic_set_at(0, key, value);
+    }
+
+    fn generic_set_at(&self, ordinal: u64, key: K, value: V) {
         let key = key.to_string();
-        let as_string = serde_json::to_string(&value).unwrap();
-        let as_value: ProtoStruct = serde_json::from_str(&as_string).unwrap();
-        self.set(0, &key, &as_value);
+        let as_value = value
+            .to_stored_struct()
+            .unwrap_or_else(|err| panic!("{err}"));
+        self.set(ordinal, &key, &as_value);
     }
 
     fn generic_delete_prefix(&self, prefix: K) {
@@ -38,23 +133,50 @@ where
     fn generic_get(&self, key: K) -> SolidityType {
         let key = key.to_string();
         if let Some(val) = self.get_last(&key) {
-            SolidityType::from(val)
+            decode_stored_value(val)
         } else {
             SolidityType::Null
         }
     }
+
+    fn generic_get_first(&self, key: K) -> SolidityType {
+        match self.get_first(key.to_string()) {
+            Some(val) => decode_stored_value(val),
+            None => SolidityType::Null,
+        }
+    }
+
+    fn generic_get_at(&self, ordinal: u64, key: K) -> SolidityType {
+        match self.get_at(ordinal, key.to_string()) {
+            Some(val) => decode_stored_value(val),
+            None => SolidityType::Null,
+        }
+    }
+
+    fn generic_has(&self, key: K) -> bool {
+        self.has_last(key.to_string())
+    }
+
+    fn generic_has_at(&self, ordinal: u64, key: K) -> bool {
+        self.has_at(ordinal, key.to_string())
+    }
 }
 
 impl<K, V> GenericStore<K, V> for StoreSetIfNotExistsProto<ProtoStruct>
 where
     K: AsRef<SolidityType> + ToString,
-    V: AsRef<SolidityType> + Serialize,
+    V: AsRef<SolidityType> + StoredValue,
 {
     fn generic_set(&self, key: K, value: V) {
+        self.generic_set_at(0, key, value);
+    }
+
+    fn generic_set_at(&self, ordinal: u64, key: K, value: V) {
         let key = key.to_string();
-        let as_string = serde_json::to_string(&value).unwrap();
-        let as_value: ProtoStruct = serde_json::from_str(&as_string).unwrap();
-        self.set_if_not_exists(0, &key, &as_value);
+        let as_value = value
+            .to_stored_struct()
+            .unwrap_or_else(|err| panic!("{err}"));
+        self.set_if_not_exists(ordinal, &key, &as_value);
     }
 
     fn generic_delete_prefix(&self, prefix: K) {
@@ -62,3 +184,1470 @@ where
         self.delete_prefix(0, &prefix);
     }
 }
+
+/// The same key ergonomics as `GenericStore`, for accumulating counters and volumes in a StoreAddBigInt.
+pub trait GenericStoreAdd<K> {
+    /// Adds a Uint or Int to the value under `key`, panics for any other value. See `try_generic_add`.
+    fn generic_add(&self, key: K, value: SolidityType) {
+        self.try_generic_add(key, value)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as `generic_add`, but errors for a value that isn't numeric rather than panicking.
+    fn try_generic_add(&self, key: K, value: SolidityType) -> Result<(), SolError>;
+}
+
+impl<K> GenericStoreAdd<K> for StoreAddBigInt
+where
+    K: AsRef<SolidityType> + ToString,
+{
+    fn try_generic_add(&self, key: K, value: SolidityType) -> Result<(), SolError> {
+        let key = key.to_string();
+        let value = encode_added(&key, value)?;
+        self.add(0, &key, value);
+        Ok(())
+    }
+}
+
+/// Converts a value for a StoreAddBigInt, only uints and ints can be added.
+fn encode_added(key: &str, value: SolidityType) -> Result<BigInt, SolError> {
+    BigInt::try_from(value).map_err(|err| SolError::EncodingError {
+        context: format!("add_bigint store key {key}"),
+        message: err.to_string(),
+    })
+}
+
+/// Reads the counter under `key` as a Uint, or an Int if it's negative. A missing key is Null.
+impl<K> GenericStoreGet<K> for StoreGetBigInt
+where
+    K: AsRef<SolidityType> + ToString,
+{
+    fn generic_get(&self, key: K) -> SolidityType {
+        match self.get_last(key.to_string()) {
+            Some(value) => SolidityType::from(value),
+            None => SolidityType::Null,
+        }
+    }
+
+    fn generic_get_first(&self, key: K) -> SolidityType {
+        match self.get_first(key.to_string()) {
+            Some(value) => SolidityType::from(value),
+            None => SolidityType::Null,
+        }
+    }
+
+    fn generic_get_at(&self, ordinal: u64, key: K) -> SolidityType {
+        match self.get_at(ordinal, key.to_string()) {
+            Some(value) => SolidityType::from(value),
+            None => SolidityType::Null,
+        }
+    }
+
+    fn generic_has(&self, key: K) -> bool {
+        self.has_last(key.to_string())
+    }
+
+    fn generic_has_at(&self, ordinal: u64, key: K) -> bool {
+        self.has_at(ordinal, key.to_string())
+    }
+}
+
+/// Reads the string under `key` as is, as a String. A missing key is Null.
+impl<K> GenericStoreGet<K> for StoreGetString
+where
+    K: AsRef<SolidityType> + ToString,
+{
+    fn generic_get(&self, key: K) -> SolidityType {
+        match self.get_last(key.to_string()) {
+            Some(value) => SolidityType::String(value),
+            None => SolidityType::Null,
+        }
+    }
+
+    fn generic_get_first(&self, key: K) -> SolidityType {
+        match self.get_first(key.to_string()) {
+            Some(value) => SolidityType::String(value),
+            None => SolidityType::Null,
+        }
+    }
+
+    fn generic_get_at(&self, ordinal: u64, key: K) -> SolidityType {
+        match self.get_at(ordinal, key.to_string()) {
+            Some(value) => SolidityType::String(value),
+            None => SolidityType::Null,
+        }
+    }
+
+    fn generic_has(&self, key: K) -> bool {
+        self.has_last(key.to_string())
+    }
+
+    fn generic_has_at(&self, ordinal: u64, key: K) -> bool {
+        self.has_at(ordinal, key.to_string())
+    }
+}
+
+/// Reads the bytes under `key` as a ByteArray. A missing key is Null.
+/// Use `generic_get_appended` for values written by `generic_append`.
+impl<K> GenericStoreGet<K> for StoreGetRaw
+where
+    K: AsRef<SolidityType> + ToString,
+{
+    fn generic_get(&self, key: K) -> SolidityType {
+        match self.get_last(key.to_string()) {
+            Some(value) => SolidityType::ByteArray(value.into()),
+            None => SolidityType::Null,
+        }
+    }
+
+    fn generic_get_first(&self, key: K) -> SolidityType {
+        match self.get_first(key.to_string()) {
+            Some(value) => SolidityType::ByteArray(value.into()),
+            None => SolidityType::Null,
+        }
+    }
+
+    fn generic_get_at(&self, ordinal: u64, key: K) -> SolidityType {
+        match self.get_at(ordinal, key.to_string()) {
+            Some(value) => SolidityType::ByteArray(value.into()),
+            None => SolidityType::Null,
+        }
+    }
+
+    fn generic_has(&self, key: K) -> bool {
+        self.has_last(key.to_string())
+    }
+
+    fn generic_has_at(&self, ordinal: u64, key: K) -> bool {
+        self.has_at(ordinal, key.to_string())
+    }
+}
+
+/// The same key ergonomics as `GenericStore`, for stores of any protobuf message rather than a Struct.
+/// The message is written as is, without going through json.
+/// Stores only provide the writes by string key, the generic methods build the key out of `K`.
+pub trait GenericMessageStore<K, M>
+where
+    K: AsRef<SolidityType> + ToString,
+{
+    /// Writes `value` under the already built `key`.
+    fn set_message(&self, key: &str, value: &M);
+
+    /// Deletes every key starting with the already built `prefix`.
+    fn delete_message_prefix(&self, prefix: &str);
+
+    fn generic_set_message(&self, key: K, value: &M) {
+        self.set_message(&key.to_string(), value);
+    }
+
+    fn generic_delete_prefix_message(&self, prefix: K) {
+        self.delete_message_prefix(&prefix.to_string());
+    }
+}
+
+/// The same key ergonomics as `GenericStoreGet`, for stores of any protobuf message rather than a Struct.
+pub trait GenericMessageStoreGet<K, M>
+where
+    K: AsRef<SolidityType> + ToString,
+{
+    /// The message under the already built `key`, as of the last write.
+    fn get_message(&self, key: &str) -> Option<M>;
+
+    fn generic_get_message(&self, key: K) -> Option<M> {
+        self.get_message(&key.to_string())
+    }
+}
+
+impl<K, M> GenericMessageStore<K, M> for StoreSetProto<M>
+where
+    K: AsRef<SolidityType> + ToString,
+    M: prost::Message + Default,
+{
+    fn set_message(&self, key: &str, value: &M) {
+        self.set(0, key, value);
+    }
+
+    fn delete_message_prefix(&self, prefix: &str) {
+        self.delete_prefix(0, &prefix.to_string());
+    }
+}
+
+impl<K, M> GenericMessageStore<K, M> for StoreSetIfNotExistsProto<M>
+where
+    K: AsRef<SolidityType> + ToString,
+    M: prost::Message + Default,
+{
+    fn set_message(&self, key: &str, value: &M) {
+        self.set_if_not_exists(0, key, value);
+    }
+
+    fn delete_message_prefix(&self, prefix: &str) {
+        self.delete_prefix(0, &prefix.to_string());
+    }
+}
+
+impl<K, M> GenericMessageStoreGet<K, M> for StoreGetProto<M>
+where
+    K: AsRef<SolidityType> + ToString,
+    M: prost::Message + Default,
+{
+    fn get_message(&self, key: &str) -> Option<M> {
+        self.get_last(key)
+    }
+}
+
+/// The version of the layout `generic_set` writes, stored under "v" next to the "type" and "value" of the tagged form.
+/// Values written before there was a version are read as version 0.
+pub const STORED_VERSION: u32 = 1;
+
+/// Upgrades stored values written with an older layout as they're read, see `register_migrator`.
+pub trait StoredValueMigrator {
+    /// Gets the version the value was written with and its tagged json form without the "v",
+    /// and returns the value in the current layout. Values that don't need migrating should be returned as is.
+    fn migrate(version: u32, value: Value) -> Value;
+}
+
+thread_local! {
+    static MIGRATORS: RefCell<Vec<(TypeId, fn(u32, Value) -> Value)>> = RefCell::new(Vec::new());
+}
+
+/// Registers a migrator to run on every value read by `generic_get`, migrators run in the order they were registered.
+/// Registering the same migrator again is a no-op, so it's safe to call from every handler.
+pub fn register_migrator<M: StoredValueMigrator + 'static>() {
+    MIGRATORS.with(|migrators| {
+        let mut migrators = migrators.borrow_mut();
+        if migrators.iter().all(|(id, _)| *id != TypeId::of::<M>()) {
+            migrators.push((TypeId::of::<M>(), M::migrate));
+        }
+    });
+}
+
+/// A value `generic_set` can write to a proto store, converted straight into the versioned tagged form.
+pub trait StoredValue {
+    /// The tagged form of the value with its "v", errors if the value holds a Null.
+    fn to_stored_struct(&self) -> Result<ProtoStruct, SolError>;
+}
+
+impl StoredValue for SolidityType {
+    fn to_stored_struct(&self) -> Result<ProtoStruct, SolError> {
+        Ok(with_version(self.tagged_struct(false)?))
+    }
+}
+
+impl StoredValue for DecimalUints {
+    fn to_stored_struct(&self) -> Result<ProtoStruct, SolError> {
+        Ok(with_version(self.0.tagged_struct(true)?))
+    }
+}
+
+impl<T: StoredValue + ?Sized> StoredValue for &T {
+    fn to_stored_struct(&self) -> Result<ProtoStruct, SolError> {
+        (**self).to_stored_struct()
+    }
+}
+
+fn with_version(mut value: ProtoStruct) -> ProtoStruct {
+    let version = ProtoValue {
+        kind: Some(Kind::NumberValue(STORED_VERSION as f64)),
+    };
+    value.fields.insert("v".to_string(), version);
+    value
+}
+
+/// Converts any serializable value into the versioned form `generic_set` writes, going through serde_json.
+/// `StoredValue::to_stored_struct` does the same for SolidityType without the json round trip.
+pub fn encode_stored_value(value: &impl Serialize) -> serde_json::Result<ProtoStruct> {
+    let mut value = serde_json::to_value(value)?;
+    if let Value::Object(map) = &mut value {
+        map.insert("v".to_string(), Value::from(STORED_VERSION));
+    }
+    serde_json::from_value(value)
+}
+
+/// Reads a stored value, enveloped or not, running the registered migrators before converting it.
+/// Without any migrators registered the struct is converted directly, without going through serde_json.
+/// Panics if a migrator returns json that can't be converted, see `try_decode_stored_value`.
+pub fn decode_stored_value(value: ProtoStruct) -> SolidityType {
+    try_decode_stored_value(value).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Same as `decode_stored_value`, but returns an error instead of panicking when the migrated json can't be converted.
+pub fn try_decode_stored_value(mut value: ProtoStruct) -> Result<SolidityType, ConvertError> {
+    if MIGRATORS.with(|migrators| migrators.borrow().is_empty()) {
+        if value.fields.contains_key("type") {
+            value.fields.remove("v");
+        }
+        return Ok(SolidityType::from(value));
+    }
+
+    let mut value = serde_json::to_value(value).map_err(|err| {
+        ConvertError::new(None, format!("the stored value isn't valid json, {err}"))
+    })?;
+
+    // proto structs only have floats, so the version comes back as 1.0
+    let version = match &mut value {
+        Value::Object(map) if map.contains_key("type") => map
+            .remove("v")
+            .and_then(|version| version.as_f64())
+            .map_or(0, |version| version as u32),
+        _ => 0,
+    };
+
+    let value = MIGRATORS.with(|migrators| {
+        migrators
+            .borrow()
+            .iter()
+            .fold(value, |value, (_, migrate)| migrate(version, value))
+    });
+
+    if let Ok(value) = serde_json::from_value(value.clone()) {
+        return Ok(value);
+    }
+    SolidityType::guess_json_value(value.clone()).ok_or_else(|| {
+        ConvertError::new(
+            None,
+            format!("couldn't convert the migrated value `{value}` from version {version}"),
+        )
+    })
+}
+
+pub trait GenericStoreAppend<K> {
+    /// Appends the tagged json form of the value under `key`. Null values can't be stored, so they're skipped.
+    /// Read everything appended back as a list with `generic_get_appended`. Panics if the value can't be encoded, see `try_generic_append`.
+    fn generic_append(&self, key: K, value: SolidityType) {
+        self.try_generic_append(key, value)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as `generic_append`, but errors for a value that can't be encoded, ie one holding a nested Null.
+    fn try_generic_append(&self, key: K, value: SolidityType) -> Result<(), SolError>;
+}
+
+impl<K> GenericStoreAppend<K> for StoreAppend<String>
+where
+    K: AsRef<SolidityType> + ToString,
+{
+    fn try_generic_append(&self, key: K, value: SolidityType) -> Result<(), SolError> {
+        if let SolidityType::Null = value {
+            return Ok(());
+        }
+        let key = key.to_string();
+        let value = encode_appended(&key, &value)?;
+        self.append(0, &key, value);
+        Ok(())
+    }
+}
+
+/// StoreAppend puts a `;` after every item, which could just as well show up inside the json.
+/// So each item is prefixed with its length, ie `5:"abc";`, and the delimiter is only ever looked for after that many bytes.
+fn encode_appended(key: &str, value: &SolidityType) -> Result<String, SolError> {
+    let json = serde_json::to_string(value).map_err(|err| SolError::EncodingError {
+        context: format!("append store key {key}"),
+        message: err.to_string(),
+    })?;
+    Ok(format!("{}:{}", json.len(), json))
+}
+
+/// Rebuilds a list from the raw bytes of a StoreAppend, written by `generic_append`.
+/// Returns Null if nothing has been appended. Panics if the bytes weren't written by `generic_append`, see `try_decode_appended`.
+pub fn decode_appended(raw: &[u8]) -> SolidityType {
+    try_decode_appended(raw).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Same as `decode_appended`, but errors on bytes that weren't written by `generic_append` rather than panicking.
+pub fn try_decode_appended(raw: &[u8]) -> Result<SolidityType, ConvertError> {
+    let malformed = |message: &str| ConvertError::new(None, format!("appended value {message}"));
+
+    let mut rest = std::str::from_utf8(raw).map_err(|_| malformed("isn't valid utf8"))?;
+    let mut values = Vec::new();
+    while !rest.is_empty() {
+        let (len, tail) = rest
+            .split_once(':')
+            .ok_or_else(|| malformed("is missing its length prefix"))?;
+        let len: usize = len
+            .parse()
+            .map_err(|_| malformed(&format!("has an invalid length prefix `{len}`")))?;
+        let json = tail
+            .get(..len)
+            .ok_or_else(|| malformed("is shorter than its length prefix"))?;
+        let value = serde_json::from_str(json)
+            .map_err(|err| malformed(&format!("couldn't be deserialized: {err}")))?;
+        values.push(value);
+        rest = tail[len..]
+            .strip_prefix(';')
+            .ok_or_else(|| malformed("is missing its delimiter"))?;
+    }
+
+    if values.is_empty() {
+        Ok(SolidityType::Null)
+    } else {
+        Ok(SolidityType::List(values))
+    }
+}
+
+/// Reads every value appended under `key` with `generic_append` back as a list.
+/// Panics if the value wasn't written by `generic_append`, see `try_generic_get_appended`.
+pub fn generic_get_appended<K: ToString>(store: &StoreGetRaw, key: K) -> SolidityType {
+    try_generic_get_appended(store, key).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Same as `generic_get_appended`, but errors rather than panicking.
+pub fn try_generic_get_appended<K: ToString>(
+    store: &StoreGetRaw,
+    key: K,
+) -> Result<SolidityType, ConvertError> {
+    match store.get_last(key.to_string()) {
+        Some(raw) => try_decode_appended(&raw),
+        None => Ok(SolidityType::Null),
+    }
+}
+
+/// Anything a `StoreBatch` can write a proto struct to, implemented for the proto set stores.
+pub trait ProtoWrite {
+    fn write_proto(&self, ordinal: u64, key: &str, value: &ProtoStruct);
+}
+
+impl ProtoWrite for StoreSetProto<ProtoStruct> {
+    fn write_proto(&self, ordinal: u64, key: &str, value: &ProtoStruct) {
+        self.set(ordinal, key, value);
+    }
+}
+
+impl ProtoWrite for StoreSetIfNotExistsProto<ProtoStruct> {
+    fn write_proto(&self, ordinal: u64, key: &str, value: &ProtoStruct) {
+        self.set_if_not_exists(ordinal, key, value);
+    }
+}
+
+/// The stores a `StoreBatch` can write to.
+pub enum BatchTarget<'a> {
+    SetProto(&'a StoreSetProto<ProtoStruct>),
+    SetIfNotExists(&'a StoreSetIfNotExistsProto<ProtoStruct>),
+    AddBigInt(&'a StoreAddBigInt),
+    /// Any other store that takes proto structs, ie a wrapper or a mock.
+    Custom(&'a dyn ProtoWrite),
+}
+
+impl BatchTarget<'_> {
+    fn kind(&self) -> &'static str {
+        match self {
+            BatchTarget::SetProto(_) => "set_proto",
+            BatchTarget::SetIfNotExists(_) => "set_if_not_exists",
+            BatchTarget::AddBigInt(_) => "add_bigint",
+            BatchTarget::Custom(_) => "custom",
+        }
+    }
+}
+
+impl<'a> From<&'a StoreSetProto<ProtoStruct>> for BatchTarget<'a> {
+    fn from(value: &'a StoreSetProto<ProtoStruct>) -> Self {
+        BatchTarget::SetProto(value)
+    }
+}
+
+impl<'a> From<&'a StoreSetIfNotExistsProto<ProtoStruct>> for BatchTarget<'a> {
+    fn from(value: &'a StoreSetIfNotExistsProto<ProtoStruct>) -> Self {
+        BatchTarget::SetIfNotExists(value)
+    }
+}
+
+impl<'a> From<&'a StoreAddBigInt> for BatchTarget<'a> {
+    fn from(value: &'a StoreAddBigInt) -> Self {
+        BatchTarget::AddBigInt(value)
+    }
+}
+
+struct BatchWrite<'a> {
+    target: BatchTarget<'a>,
+    key: String,
+    value: SolidityType,
+    ordinal: u64,
+}
+
+/// A value that's already been converted into what its store takes.
+enum Encoded {
+    Proto(ProtoStruct),
+    BigInt(BigInt),
+}
+
+/// Collects writes across several stores, so one event can update all of them with a single `flush`.
+/// Every value is converted up front, so a value that can't be stored fails the whole batch before anything is written.
+///
+/// ```ignore
+/// StoreBatch::new()
+///     .set(&volume_store, &pool, volume, 0)
+///     .set(&price_store, &pool, price, 0)
+///     .flush()?;
+/// ```
+#[derive(Default)]
+pub struct StoreBatch<'a> {
+    writes: Vec<BatchWrite<'a>>,
+}
+
+impl<'a> StoreBatch<'a> {
+    pub fn new() -> Self {
+        StoreBatch { writes: Vec::new() }
+    }
+
+    /// Queues a write, writes are flushed in the order they were queued.
+    pub fn set(
+        mut self,
+        target: impl Into<BatchTarget<'a>>,
+        key: impl ToString,
+        value: SolidityType,
+        ordinal: u64,
+    ) -> Self {
+        self.writes.push(BatchWrite {
+            target: target.into(),
+            key: key.to_string(),
+            value,
+            ordinal,
+        });
+        self
+    }
+
+    /// The planned writes as a list of structs with "store", "key", "ordinal" and "value", for logging.
+    pub fn dry_run(&self) -> SolidityType {
+        let writes: Vec<SolidityType> = self
+            .writes
+            .iter()
+            .map(|write| {
+                map_literal! {
+                    "store"; SolidityType::String(write.target.kind().to_string()),
+                    "key"; SolidityType::String(write.key.clone()),
+                    "ordinal"; SolidityType::Uint(alloy_primitives::U256::from(write.ordinal)),
+                    "value"; write.value.clone()
+                }
+            })
+            .collect();
+
+        if writes.is_empty() {
+            SolidityType::Null
+        } else {
+            SolidityType::List(writes)
+        }
+    }
+
+    /// Converts every value, and only if they all convert writes them in order.
+    pub fn flush(self) -> Result<(), SolError> {
+        let encoded = self
+            .writes
+            .iter()
+            .map(|write| write.encode())
+            .collect::<Result<Vec<Encoded>, SolError>>()?;
+
+        for (write, value) in self.writes.iter().zip(encoded) {
+            let key = write.key.as_str();
+            match (&write.target, value) {
+                (BatchTarget::SetProto(store), Encoded::Proto(value)) => {
+                    store.write_proto(write.ordinal, key, &value)
+                }
+                (BatchTarget::SetIfNotExists(store), Encoded::Proto(value)) => {
+                    store.write_proto(write.ordinal, key, &value)
+                }
+                (BatchTarget::Custom(store), Encoded::Proto(value)) => {
+                    store.write_proto(write.ordinal, key, &value)
+                }
+                (BatchTarget::AddBigInt(store), Encoded::BigInt(value)) => {
+                    store.add(write.ordinal, key, value)
+                }
+                _ => unreachable!("Values are encoded for their own target"),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl BatchWrite<'_> {
+    fn encode(&self) -> Result<Encoded, SolError> {
+        let encoding_error = |message: String| SolError::EncodingError {
+            context: format!("{} store key {}", self.target.kind(), self.key),
+            message,
+        };
+
+        match self.target {
+            BatchTarget::AddBigInt(_) => {
+                encode_added(&self.key, self.value.clone()).map(Encoded::BigInt)
+            }
+            _ => self
+                .value
+                .to_stored_struct()
+                .map(Encoded::Proto)
+                .map_err(|err| encoding_error(err.to_string())),
+        }
+    }
+}
+
+/// Returns the value stored under `key`, or computes it with `init` and writes it to `set_store`.
+/// A Null from the store is treated as a miss, and Null results from `init` aren't written,
+/// use `get_or_set_caching_null` if those are worth caching too.
+pub fn get_or_set<K, F>(
+    get_store: &impl GenericStoreGet<K>,
+    set_store: &impl GenericStore<K, SolidityType>,
+    key: K,
+    init: F,
+) -> SolidityType
+where
+    K: Clone,
+    F: FnOnce() -> SolidityType,
+{
+    match get_store.generic_get(key.clone()) {
+        SolidityType::Null => {}
+        value => return value,
+    }
+
+    let value = init();
+    if !matches!(value, SolidityType::Null) {
+        set_store.generic_set(key, value.clone());
+    }
+    value
+}
+
+/// Same as `get_or_set`, but Null results from `init` are cached as well,
+/// ie when a contract has no symbol there's no point asking again.
+/// Since a Null can't be stored, the result is cached as an `Option`, a tuple of the value or an empty tuple for a Null,
+/// so a key should only ever be read and written by one of the two.
+pub fn get_or_set_caching_null<K, F>(
+    get_store: &impl GenericStoreGet<K>,
+    set_store: &impl GenericStore<K, SolidityType>,
+    key: K,
+    init: F,
+) -> SolidityType
+where
+    K: Clone,
+    F: FnOnce() -> SolidityType,
+{
+    if let Some(cached) = from_cached(get_store.generic_get(key.clone())) {
+        return cached.unwrap_or(SolidityType::Null);
+    }
+
+    let value = init();
+    let cached = (!matches!(value, SolidityType::Null)).then(|| value.clone());
+    set_store.generic_set(key, to_cached(cached));
+    value
+}
+
+fn to_cached(value: Option<SolidityType>) -> SolidityType {
+    SolidityType::Tuple(value.into_iter().collect())
+}
+
+/// None on a miss, otherwise the cached result of `init`.
+fn from_cached(stored: SolidityType) -> Option<Option<SolidityType>> {
+    match stored {
+        SolidityType::Tuple(mut values) if values.len() <= 1 => Some(values.pop()),
+        _ => None,
+    }
+}
+
+/// Writes each event in `events` to `store`, under the key and value picked out by `key` and `value`,
+/// at the ordinal of the event's log so later logs win. A Null `events` writes nothing, as do events whose key is Null.
+/// Returns the number of writes, see `event_store!`.
+/// NOTE Only stores overriding `generic_set_at`, like the substreams set stores, honor the ordinal.
+/// With the default the ordinal is ignored, and later logs only win if they come later in `events`.
+pub fn write_events<S, K, V>(
+    store: &S,
+    events: SolidityType,
+    key: impl Fn(&SolidityType) -> K,
+    value: impl Fn(SolidityType) -> V,
+) -> usize
+where
+    S: GenericStore<K, V>,
+    K: AsRef<SolidityType>,
+{
+    let mut writes = 0;
+    for event in events {
+        let key = key(&event);
+        if let SolidityType::Null = key.as_ref() {
+            continue;
+        }
+        let ordinal = event_ordinal(&event);
+        store.generic_set_at(ordinal, key, value(event));
+        writes += 1;
+    }
+    writes
+}
+
+/// How `StoreKey` writes address segments.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AddressFormat {
+    /// Lowercase 0x hex, the way `to_string` and most substreams write address keys.
+    #[default]
+    Lowercase,
+    /// EIP-55 checksummed hex, for keys shared with sinks that write addresses checksummed.
+    Checksum,
+}
+
+/// Builds a store key out of segments joined by a delimiter, ':' by default, ie `pool:0xabc...:token:0xdef...`.
+/// Segments are the `to_string` of scalar values and can't contain the delimiter.
+/// Addresses are lowercase hex unless set otherwise with `with_address_format`.
+/// It's a key like any other for the generic stores, see `prefix` for deleting everything under a key.
+#[derive(Debug, Clone)]
+pub struct StoreKey {
+    delimiter: char,
+    address_format: AddressFormat,
+    key: String,
+    /// The key as a SolidityType::String, built the first time it's needed by `as_ref`.
+    value: OnceCell<SolidityType>,
+}
+
+impl StoreKey {
+    pub fn new(prefix: &str) -> Self {
+        Self::with_delimiter(prefix, ':')
+    }
+
+    pub fn with_delimiter(prefix: &str, delimiter: char) -> Self {
+        StoreKey {
+            delimiter,
+            address_format: AddressFormat::default(),
+            key: prefix.to_string(),
+            value: OnceCell::new(),
+        }
+    }
+
+    /// Writes the address segments pushed from here on in `format`, ie `AddressFormat::Checksum`.
+    pub fn with_address_format(mut self, format: AddressFormat) -> Self {
+        self.address_format = format;
+        self
+    }
+
+    /// Appends `value` as the next segment, panics if it can't be one. See `try_push`.
+    pub fn push<T: Into<SolidityType>>(self, value: T) -> Self {
+        self.try_push(value).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Appends `value` as the next segment, erroring for containers, Null, and values containing the delimiter.
+    pub fn try_push<T: Into<SolidityType>>(mut self, value: T) -> Result<Self, SolError> {
+        let value: SolidityType = value.into();
+        let segment = match value {
+            SolidityType::Tuple(_)
+            | SolidityType::List(_)
+            | SolidityType::SizedList(..)
+            | SolidityType::Struct(_)
+            | SolidityType::Null => {
+                return Err(SolError::WrongVariant {
+                    operation: "use as a key segment",
+                    found: value.kind(),
+                })
+            }
+            SolidityType::Address(_) if self.address_format == AddressFormat::Checksum => {
+                value.to_checksum_string(None)
+            }
+            _ => value.to_string(),
+        };
+        if segment.contains(self.delimiter) {
+            return Err(SolError::ParseFailure {
+                input: segment,
+                expected: "a key segment without the delimiter",
+            });
+        }
+
+        if !self.key.is_empty() && !self.key.ends_with(self.delimiter) {
+            self.key.push(self.delimiter);
+        }
+        self.key.push_str(&segment);
+        self.value = OnceCell::new();
+        Ok(self)
+    }
+
+    /// The key with a trailing delimiter, to pass to `generic_delete_prefix` so deleting `pool:1` leaves `pool:10` alone.
+    pub fn prefix(&self) -> StoreKey {
+        let mut prefix = StoreKey::with_delimiter(&self.key, self.delimiter)
+            .with_address_format(self.address_format);
+        if !prefix.key.ends_with(self.delimiter) {
+            prefix.key.push(self.delimiter);
+        }
+        prefix
+    }
+}
+
+/// Keys are equal if they're the same string with the same delimiter.
+impl PartialEq for StoreKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.delimiter == other.delimiter && self.key == other.key
+    }
+}
+
+impl AsRef<SolidityType> for StoreKey {
+    fn as_ref(&self) -> &SolidityType {
+        self.value
+            .get_or_init(|| SolidityType::String(self.key.clone()))
+    }
+}
+
+impl From<StoreKey> for SolidityType {
+    fn from(value: StoreKey) -> Self {
+        SolidityType::String(value.key)
+    }
+}
+
+impl std::fmt::Display for StoreKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.key)
+    }
+}
+
+/// Splits `key` back into the named segments of `pattern`, ie `parse_key("pool:0xabc...:fee:3000", "pool:{pool}:fee:{fee}")`
+/// is a struct with an address "pool" and a uint "fee". Segment values are guessed,
+/// and segments that can't be, like an empty one, are kept as a String. A key that doesn't match the pattern is Null.
+pub fn parse_key(key: &str, pattern: &str) -> SolidityType {
+    parse_key_with(key, pattern, ':')
+}
+
+/// Same as `parse_key`, for keys built with `StoreKey::with_delimiter`.
+pub fn parse_key_with(key: &str, pattern: &str, delimiter: char) -> SolidityType {
+    let segments: Vec<&str> = key.split(delimiter).collect();
+    let expected: Vec<&str> = pattern.split(delimiter).collect();
+    if segments.len() != expected.len() {
+        return SolidityType::Null;
+    }
+
+    let mut parsed = IndexMap::new();
+    for (segment, expected) in segments.into_iter().zip(expected) {
+        match expected
+            .strip_prefix('{')
+            .and_then(|name| name.strip_suffix('}'))
+        {
+            Some(name) => {
+                let value = match SolidityType::guess_json_value(segment) {
+                    Some(SolidityType::Null) | None => SolidityType::String(segment.to_string()),
+                    Some(value) => value,
+                };
+                parsed.insert(name.to_string(), value);
+            }
+            None if segment == expected => {}
+            None => return SolidityType::Null,
+        }
+    }
+    SolidityType::Struct(parsed)
+}
+
+/// An in memory store, for running handlers and helpers outside of the substreams runtime, ie in tests.
+/// It keeps the latest value of each key, and records the ordinal and key of every write in order.
+/// Values go through the stored form like a real store's, so whatever can't be stored panics here too.
+/// It has no history, so reads at an ordinal and the existence checks fall back to `generic_get`.
+#[derive(Debug, Default)]
+pub struct MockStore {
+    values: RefCell<HashMap<String, SolidityType>>,
+    writes: RefCell<Vec<(u64, String)>>,
+    reads: Cell<usize>,
+}
+
+impl MockStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every write so far as its ordinal and key, in the order they were made.
+    pub fn writes(&self) -> Vec<(u64, String)> {
+        self.writes.borrow().clone()
+    }
+
+    /// The number of `generic_get` calls so far, including the ones made by the trait's fallbacks.
+    pub fn reads(&self) -> usize {
+        self.reads.get()
+    }
+
+    /// The keys currently holding a value, sorted.
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.values.borrow().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+impl<K: ToString> GenericStore<K, SolidityType> for MockStore {
+    fn generic_set(&self, key: K, value: SolidityType) {
+        self.generic_set_at(0, key, value);
+    }
+
+    /// Encodes the value like `StoreSetProto` does, so it panics on the same values, ie a Null.
+    fn generic_set_at(&self, ordinal: u64, key: K, value: SolidityType) {
+        let key = key.to_string();
+        let stored = value
+            .to_stored_struct()
+            .unwrap_or_else(|err| panic!("{err}"));
+        self.writes.borrow_mut().push((ordinal, key.clone()));
+        self.values
+            .borrow_mut()
+            .insert(key, decode_stored_value(stored));
+    }
+
+    fn generic_delete_prefix(&self, prefix: K) {
+        let prefix = prefix.to_string();
+        self.values
+            .borrow_mut()
+            .retain(|key, _| !key.starts_with(&prefix));
+    }
+}
+
+impl<K: ToString> GenericStoreGet<K> for MockStore {
+    fn generic_get(&self, key: K) -> SolidityType {
+        self.reads.set(self.reads.get() + 1);
+        self.values
+            .borrow()
+            .get(&key.to_string())
+            .cloned()
+            .unwrap_or(SolidityType::Null)
+    }
+}
+
+/// Reads the struct back like a real store would, so a `StoreBatch` can write to it through `BatchTarget::Custom`.
+impl ProtoWrite for MockStore {
+    fn write_proto(&self, ordinal: u64, key: &str, value: &ProtoStruct) {
+        self.writes.borrow_mut().push((ordinal, key.to_string()));
+        self.values
+            .borrow_mut()
+            .insert(key.to_string(), decode_stored_value(value.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+
+    #[test]
+    fn test_get_or_set() {
+        let store = MockStore::default();
+        let calls = Cell::new(0);
+        let init = || {
+            calls.set(calls.get() + 1);
+            SolidityType::Uint(U256::from(18))
+        };
+
+        let first = get_or_set(&store, &store, "decimals".to_string(), init);
+        let second = get_or_set(&store, &store, "decimals".to_string(), init);
+
+        assert_eq!(first, U256::from(18));
+        assert_eq!(second, U256::from(18));
+        assert_eq!(calls.get(), 1);
+        assert_eq!(store.writes().len(), 1);
+    }
+
+    #[test]
+    fn test_get_first_fallback() {
+        let store = MockStore::default();
+        store.generic_set("price".to_string(), SolidityType::Uint(U256::from(5)));
+
+        // the mock has no history, so every read is its latest value
+        assert_eq!(store.generic_get_first("price".to_string()), U256::from(5));
+        assert_eq!(store.generic_get_at(3, "price".to_string()), U256::from(5));
+        assert!(matches!(
+            store.generic_get_at(3, "missing".to_string()),
+            SolidityType::Null
+        ));
+    }
+
+    #[test]
+    fn test_get_or_set_null() {
+        let store = MockStore::default();
+        let calls = Cell::new(0);
+        let init = || {
+            calls.set(calls.get() + 1);
+            SolidityType::Null
+        };
+
+        get_or_set(&store, &store, "symbol".to_string(), init);
+        get_or_set(&store, &store, "symbol".to_string(), init);
+        assert_eq!(calls.get(), 2);
+        assert_eq!(store.writes().len(), 0);
+
+        let first = get_or_set_caching_null(&store, &store, "symbol".to_string(), init);
+        let second = get_or_set_caching_null(&store, &store, "symbol".to_string(), init);
+        assert!(matches!(first, SolidityType::Null));
+        assert!(matches!(second, SolidityType::Null));
+        assert_eq!(calls.get(), 3);
+        assert_eq!(store.writes().len(), 1);
+
+        let name = || SolidityType::String("Wrapped Ether".to_string());
+        let first = get_or_set_caching_null(&store, &store, "name".to_string(), name);
+        let second = get_or_set_caching_null(&store, &store, "name".to_string(), || {
+            panic!("Expected a cached name")
+        });
+        assert_eq!(first, second);
+        assert_eq!(store.writes().len(), 2);
+
+        // the cached Options survive being stored
+        for cached in [None, Some(name())] {
+            let stored = to_cached(cached.clone()).to_stored_struct().unwrap();
+            assert_eq!(from_cached(decode_stored_value(stored)), Some(cached));
+        }
+    }
+
+    #[test]
+    fn test_append_round_trip() {
+        let swaps = vec![
+            map_literal! {
+                "amount"; SolidityType::Uint(U256::from(1)),
+                "memo"; SolidityType::String("a;b".to_string())
+            },
+            map_literal! {
+                "amount"; SolidityType::Uint(U256::from(2)),
+                "memo"; SolidityType::String("12:{;".to_string())
+            },
+            map_literal! {
+                "amount"; SolidityType::Uint(U256::from(3)),
+                "memo"; SolidityType::String("".to_string())
+            },
+        ];
+
+        // what StoreAppend ends up holding after three appends
+        let raw: String = swaps
+            .iter()
+            .map(|swap| format!("{};", encode_appended("swaps", swap).unwrap()))
+            .collect();
+
+        let list = decode_appended(raw.as_bytes());
+        assert!(matches!(list, SolidityType::List(_)));
+        assert_eq!(list.len(), 3);
+        for (read, written) in list.iter().zip(&swaps) {
+            assert_eq!(read.get("amount"), written.get("amount"));
+            assert!(matches!(
+                (read.get("memo"), written.get("memo")),
+                (SolidityType::String(read), SolidityType::String(written)) if read == written
+            ));
+        }
+
+        assert!(matches!(decode_appended(&[]), SolidityType::Null));
+
+        for raw in ["5", "3:\"a\"", "9:\"a\";", "x:\"a\";", "3:abc;"] {
+            assert!(try_decode_appended(raw.as_bytes()).is_err(), "{raw}");
+        }
+        assert!(try_decode_appended(&[0xff]).is_err());
+
+        let nested_null = SolidityType::List(vec![SolidityType::Null]);
+        assert!(encode_appended("swaps", &nested_null).is_err());
+    }
+
+    #[test]
+    fn test_write_events_ordinals() {
+        let event = |to: &str, ordinal: Option<u64>| {
+            let mut event = map_literal! { "to"; SolidityType::String(to.to_string()) };
+            if let Some(ordinal) = ordinal {
+                event.insert(
+                    "tx_meta",
+                    map_literal! { "ordinal"; SolidityType::Uint(U256::from(ordinal)) },
+                );
+            }
+            event
+        };
+        let events = SolidityType::List(vec![
+            event("a", Some(7)),
+            event("a", Some(3)),
+            event("b", None),
+        ]);
+
+        let store = MockStore::default();
+        let writes = write_events(&store, events, |event| event.get("to"), |event| event);
+        assert_eq!(writes, 3);
+        assert_eq!(
+            store.writes(),
+            vec![
+                (7, "a".to_string()),
+                (3, "a".to_string()),
+                (0, "b".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_encode_added() {
+        let volume = encode_added("volume", SolidityType::Uint(U256::from(100))).unwrap();
+        assert_eq!(volume.to_string(), "100");
+        let net = SolidityType::Int(alloy_primitives::I256::try_from(-5).unwrap());
+        assert_eq!(encode_added("net", net).unwrap().to_string(), "-5");
+
+        let err = encode_added("symbol", SolidityType::String("WETH".to_string())).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Couldn't encode the value for add_bigint store key symbol"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_store_batch_order() {
+        let volume = MockStore::default();
+        let price = MockStore::default();
+
+        let batch = StoreBatch::new()
+            .set(
+                BatchTarget::Custom(&volume),
+                "pool:a",
+                SolidityType::Uint(U256::from(1)),
+                0,
+            )
+            .set(
+                BatchTarget::Custom(&price),
+                "pool:a",
+                SolidityType::Uint(U256::from(2)),
+                1,
+            )
+            .set(
+                BatchTarget::Custom(&volume),
+                "pool:b",
+                SolidityType::Uint(U256::from(3)),
+                2,
+            );
+
+        let planned = batch.dry_run();
+        assert_eq!(planned.len(), 3);
+        assert_eq!(planned.get("1").get("ordinal"), U256::from(1));
+        assert!(volume.writes().is_empty());
+
+        batch.flush().unwrap();
+        assert_eq!(
+            volume.writes(),
+            vec![(0, "pool:a".to_string()), (2, "pool:b".to_string())]
+        );
+        assert_eq!(price.writes(), vec![(1, "pool:a".to_string())]);
+        assert_eq!(price.generic_get("pool:a"), U256::from(2));
+    }
+
+    #[test]
+    fn test_store_batch_all_or_nothing() {
+        let volume = MockStore::default();
+
+        // Null can't be stored, so the whole batch fails before the first write
+        let result = StoreBatch::new()
+            .set(
+                BatchTarget::Custom(&volume),
+                "pool:a",
+                SolidityType::Uint(U256::from(1)),
+                0,
+            )
+            .set(
+                BatchTarget::Custom(&volume),
+                "pool:b",
+                SolidityType::Null,
+                1,
+            )
+            .flush();
+
+        assert!(matches!(
+            result,
+            Err(SolError::EncodingError { context, .. }) if context.contains("pool:b")
+        ));
+        assert!(volume.writes().is_empty());
+    }
+
+    #[test]
+    fn test_generic_set_pruned() {
+        let store = MockStore::default();
+        let nulls = SolidityType::List(vec![SolidityType::Null, SolidityType::Null]);
+
+        store.generic_set_pruned("nulls".to_string(), nulls.clone());
+        store.generic_set_pruned("null".to_string(), SolidityType::Null);
+        assert_eq!(store.writes().len(), 0);
+
+        store.generic_set_pruned_with("nulls".to_string(), nulls, EmptyPolicy::WriteEmpty);
+        assert_eq!(store.writes().len(), 1);
+        assert!(matches!(
+            store.generic_get("nulls".to_string()),
+            SolidityType::List(vals) if vals.is_empty()
+        ));
+
+        let pool = map_literal! {
+            "reserve"; SolidityType::Uint(U256::from(5)),
+            "owner"; SolidityType::Null
+        };
+        store.generic_set_pruned("pool".to_string(), pool);
+        let stored = store.generic_get("pool".to_string());
+        assert_eq!(stored.get("reserve"), U256::from(5));
+        assert!(matches!(stored.get("owner"), SolidityType::Null));
+        assert!(matches!(&stored, SolidityType::Struct(map) if map.len() == 1));
+
+        // the mock encodes like a real store, so the explicit empties have to survive being stored
+        let nested = map_literal! {
+            "transfers"; SolidityType::List(vec![SolidityType::Null]),
+            "fee"; SolidityType::Uint(U256::from(3000))
+        };
+        store.generic_set_pruned_with("nested".to_string(), nested, EmptyPolicy::WriteEmpty);
+        let stored = store.generic_get("nested".to_string());
+        assert_eq!(stored.get("fee"), U256::from(3000));
+        assert!(matches!(stored.get("transfers"), SolidityType::List(vals) if vals.is_empty()));
+    }
+
+    fn stored(json: &str) -> ProtoStruct {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_decode_legacy_values() {
+        let value = decode_stored_value(stored(r#"{"type":"uint","value":"0x2a"}"#));
+        assert_eq!(value, U256::from(42));
+
+        let value = decode_stored_value(stored(
+            r#"{"type":"struct","value":{"pool":{"type":"address","value":"0x0000000000000000000000000000000000000005"},"reserve":{"type":"uint","value":"0x64"}}}"#,
+        ));
+        assert!(matches!(value.get("pool"), SolidityType::Address(_)));
+        assert_eq!(value.get("reserve"), U256::from(100));
+    }
+
+    #[test]
+    fn test_stored_value_round_trip() {
+        let value = map_literal! { "reserve"; SolidityType::Uint(U256::from(100)) };
+        let encoded = encode_stored_value(&value).unwrap();
+        let json = serde_json::to_value(&encoded).unwrap();
+        assert_eq!(json["v"].as_f64(), Some(STORED_VERSION as f64));
+
+        let decoded = decode_stored_value(encoded);
+        assert_eq!(decoded.get("reserve"), U256::from(100));
+        assert!(matches!(decoded.get("v"), SolidityType::Null));
+    }
+
+    #[test]
+    fn test_stored_struct_matches_serde() {
+        let value = map_literal! {
+            "pools"; SolidityType::List(vec![map_literal! {
+                "reserve"; SolidityType::Uint(U256::from(100)),
+                "ticks"; SolidityType::SizedList(2, vec![SolidityType::Uint(U256::MAX); 2])
+            }]),
+            "name"; SolidityType::String("gm".to_string())
+        };
+
+        let as_json = |value: ProtoStruct| serde_json::to_value(value).unwrap();
+        assert_eq!(
+            as_json(value.to_stored_struct().unwrap()),
+            as_json(encode_stored_value(&value).unwrap())
+        );
+        let decimal = DecimalUints(value.clone());
+        assert_eq!(
+            as_json(decimal.to_stored_struct().unwrap()),
+            as_json(encode_stored_value(&decimal).unwrap())
+        );
+
+        let decoded = decode_stored_value(decimal.to_stored_struct().unwrap());
+        assert_eq!(decoded, value);
+        assert!(SolidityType::Null.to_stored_struct().is_err());
+    }
+
+    struct RenameAmount;
+
+    impl StoredValueMigrator for RenameAmount {
+        fn migrate(version: u32, mut value: Value) -> Value {
+            if version == 0 {
+                if let Some(Value::Object(fields)) = value.get_mut("value") {
+                    if let Some(amount) = fields.remove("amount") {
+                        fields.insert("reserve".to_string(), amount);
+                    }
+                }
+            }
+            value
+        }
+    }
+
+    #[test]
+    fn test_stored_value_migration() {
+        register_migrator::<RenameAmount>();
+        // a second registration doesn't run the migration twice
+        register_migrator::<RenameAmount>();
+        assert_eq!(MIGRATORS.with(|migrators| migrators.borrow().len()), 1);
+
+        let legacy = decode_stored_value(stored(
+            r#"{"type":"struct","value":{"amount":{"type":"uint","value":"0x64"}}}"#,
+        ));
+        assert_eq!(legacy.get("reserve"), U256::from(100));
+        assert!(matches!(legacy.get("amount"), SolidityType::Null));
+
+        // values already in the current layout are left alone
+        let current = decode_stored_value(stored(
+            r#"{"v":1,"type":"struct","value":{"amount":{"type":"uint","value":"0x64"}}}"#,
+        ));
+        assert_eq!(current.get("amount"), U256::from(100));
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct Pool {
+        #[prost(string, tag = "1")]
+        reserve: String,
+    }
+
+    /// Keeps messages encoded, the way a real store does.
+    /// Only the string keyed methods are implemented, so the generic ones are the trait's own.
+    #[derive(Default)]
+    struct MockMessageStore {
+        values: RefCell<HashMap<String, Vec<u8>>>,
+    }
+
+    impl GenericMessageStore<SolidityType, Pool> for MockMessageStore {
+        fn set_message(&self, key: &str, value: &Pool) {
+            use prost::Message;
+            self.values
+                .borrow_mut()
+                .insert(key.to_string(), value.encode_to_vec());
+        }
+
+        fn delete_message_prefix(&self, prefix: &str) {
+            self.values
+                .borrow_mut()
+                .retain(|key, _| !key.starts_with(prefix));
+        }
+    }
+
+    impl GenericMessageStoreGet<SolidityType, Pool> for MockMessageStore {
+        fn get_message(&self, key: &str) -> Option<Pool> {
+            use prost::Message;
+            let values = self.values.borrow();
+            Pool::decode(values.get(key)?.as_slice()).ok()
+        }
+    }
+
+    fn set_pool(
+        store: &impl GenericMessageStore<SolidityType, Pool>,
+        address: SolidityType,
+        reserve: &str,
+    ) {
+        let pool = Pool {
+            reserve: reserve.to_string(),
+        };
+        store.generic_set_message(address, &pool);
+    }
+
+    #[test]
+    fn test_generic_message_store() {
+        // the real stores are only reachable through the substreams runtime, so this just pins the impls
+        fn assert_message_store<S: GenericMessageStore<SolidityType, Pool>>() {}
+        fn assert_message_store_get<S: GenericMessageStoreGet<SolidityType, Pool>>() {}
+        assert_message_store::<StoreSetProto<Pool>>();
+        assert_message_store::<StoreSetIfNotExistsProto<Pool>>();
+        assert_message_store_get::<StoreGetProto<Pool>>();
+
+        let store = MockMessageStore::default();
+        let pool = SolidityType::Address(alloy_primitives::Address::ZERO);
+        set_pool(&store, pool.clone(), "100");
+
+        let stored = store.generic_get_message(pool.clone()).unwrap();
+        assert_eq!(stored.reserve, "100");
+        // the generic key is the same one the string keyed methods see
+        assert!(store.values.borrow().contains_key(&pool.to_string()));
+        assert_eq!(
+            store.get_message(&pool.to_string()),
+            Some(Pool {
+                reserve: "100".to_string()
+            })
+        );
+
+        store.generic_delete_prefix_message(pool.clone());
+        assert!(store.generic_get_message(pool).is_none());
+    }
+
+    #[test]
+    fn test_store_key() {
+        let pool = alloy_primitives::Address::from_slice(&[1; 20]);
+        let token = alloy_primitives::Address::from_slice(&[2; 20]);
+        let key = StoreKey::new("pool")
+            .push(pool)
+            .push("token".to_string())
+            .push(token);
+        let pool_hex = SolidityType::Address(pool).to_string();
+        let token_hex = SolidityType::Address(token).to_string();
+        assert_eq!(
+            key.to_string(),
+            format!("pool:{pool_hex}:token:{token_hex}")
+        );
+
+        let parsed = parse_key(&key.to_string(), "pool:{pool}:token:{token}");
+        assert_eq!(parsed.get("pool"), pool);
+        assert_eq!(parsed.get("token"), token);
+        assert!(matches!(
+            parse_key(&key.to_string(), "pool:{pool}:fee:{fee}"),
+            SolidityType::Null
+        ));
+        assert!(matches!(
+            parse_key("pool:1", "pool:{pool}:token:{token}"),
+            SolidityType::Null
+        ));
+        // segments that don't guess to anything keep what was written
+        let parsed = parse_key("name::null", "name:{first}:{last}");
+        assert!(matches!(parsed.get("first"), SolidityType::String(ref first) if first.is_empty()));
+        assert!(matches!(parsed.get("last"), SolidityType::String(ref last) if last == "null"));
+        assert!(
+            matches!(key.as_ref(), SolidityType::String(ref as_string) if *as_string == key.to_string())
+        );
+
+        // an address with hex letters in it, so the checksum differs from the lowercase form
+        let mixed = alloy_primitives::Address::from_slice(&[0xab; 20]);
+        let checksummed = StoreKey::new("pool")
+            .with_address_format(AddressFormat::Checksum)
+            .push(mixed);
+        assert_eq!(
+            checksummed.to_string(),
+            format!("pool:{}", mixed.to_checksum(None))
+        );
+        assert_ne!(
+            checksummed.to_string(),
+            StoreKey::new("pool").push(mixed).to_string()
+        );
+        // the format carries over to a prefix
+        assert_eq!(
+            checksummed.prefix().push(mixed).to_string(),
+            format!("pool:{0}:{0}", mixed.to_checksum(None))
+        );
+        assert_eq!(
+            parse_key(&checksummed.to_string(), "pool:{pool}").get("pool"),
+            mixed
+        );
+
+        let fee = StoreKey::with_delimiter("fee", '/').push(U256::from(3000));
+        assert_eq!(fee.to_string(), "fee/3000");
+        assert_eq!(
+            parse_key_with(&fee.to_string(), "fee/{fee}", '/').get("fee"),
+            U256::from(3000)
+        );
+
+        let err = StoreKey::new("name")
+            .try_push("a:b".to_string())
+            .unwrap_err();
+        assert!(err.to_string().contains("a:b"), "{err}");
+        assert!(StoreKey::new("pool").try_push(SolidityType::Null).is_err());
+        assert!(StoreKey::new("pool")
+            .try_push(SolidityType::List(vec![]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_store_key_prefix() {
+        fn assert_store<S: GenericStore<StoreKey, SolidityType>>() {}
+        assert_store::<StoreSetProto<ProtoStruct>>();
+        assert_store::<StoreSetIfNotExistsProto<ProtoStruct>>();
+
+        let store = MockStore::default();
+        for id in [1, 10] {
+            let key = StoreKey::new("pool")
+                .push(U256::from(id))
+                .push("fee".to_string());
+            store.generic_set(key.to_string(), SolidityType::Uint(U256::from(id)));
+        }
+
+        let prefix = StoreKey::new("pool").push(U256::from(1)).prefix();
+        assert_eq!(prefix.to_string(), "pool:1:");
+        // pushing onto a prefix doesn't double the delimiter
+        assert_eq!(
+            prefix.clone().push("fee".to_string()).to_string(),
+            "pool:1:fee"
+        );
+
+        store.generic_delete_prefix(prefix.to_string());
+        assert!(matches!(
+            store.generic_get("pool:1:fee".to_string()),
+            SolidityType::Null
+        ));
+        assert_eq!(store.generic_get("pool:10:fee".to_string()), U256::from(10));
+    }
+
+    #[test]
+    fn test_generic_has() {
+        // the mock only implements generic_get, so these are the trait's fallbacks
+        let store = MockStore::default();
+        store.generic_set("pool".to_string(), SolidityType::Uint(U256::from(1)));
+
+        assert!(store.generic_has("pool".to_string()));
+        assert!(!store.generic_has("token".to_string()));
+        assert!(store.generic_has_at(0, "pool".to_string()));
+        assert!(!store.generic_has_at(0, "token".to_string()));
+        // each check is a read, which real stores skip with has_last and has_at
+        assert_eq!(store.reads(), 4);
+    }
+}
@@ -0,0 +1,186 @@
+//! Helpers for bucketing events by their block timestamp, ie to build hourly or daily OHLC candles.
+
+use crate::{aliases::*, json_values::SolidityType, map_literal, store_helpers::StoreKey};
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+/// The unix epoch was a Thursday, so weeks are shifted by 3 days to start on a Monday.
+const WEEK_OFFSET: u64 = 3 * DAY;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    Hour,
+    Day,
+    /// Weeks start on Monday 00:00 UTC.
+    Week,
+    /// A window of this many seconds, aligned to the unix epoch.
+    Custom(u64),
+}
+
+impl Window {
+    pub fn seconds(&self) -> u64 {
+        match self {
+            Window::Hour => HOUR,
+            Window::Day => DAY,
+            Window::Week => WEEK,
+            Window::Custom(seconds) => *seconds,
+        }
+    }
+}
+
+/// The start of the window `ts` falls in, in unix seconds. A timestamp exactly on an edge starts a new window.
+pub fn bucket_timestamp(ts: u64, window: Window) -> u64 {
+    let size = window.seconds();
+    if size == 0 {
+        panic!("Tried to bucket a timestamp into a window of zero seconds!");
+    }
+
+    match window {
+        // the days before the first monday all land in the first bucket
+        Window::Week => ((ts + WEEK_OFFSET) / size * size).saturating_sub(WEEK_OFFSET),
+        _ => ts / size * size,
+    }
+}
+
+/// Builds a store key of the form `prefix:entity:bucket`, ie `candle:0xc02a...:1681336800`.
+/// It's a `StoreKey`, so it can be used as the key of `generic_set` as is, see `StoreKey::prefix` to delete a whole entity.
+/// Panics if `entity` can't be a key segment, see `StoreKey::push`.
+pub fn bucket_key(prefix: &str, entity: &SolidityType, ts: u64, window: Window) -> StoreKey {
+    StoreKey::new(prefix)
+        .push(entity.clone())
+        .push(U256::from(bucket_timestamp(ts, window)))
+}
+
+/// Accumulates the open, high, low, close and volume of a window.
+/// Updates made with `update` are ordered by when they arrive, use `update_at` with the log ordinal
+/// when the updates of a block might not arrive in order. Don't mix the two on the same candle.
+#[derive(Debug, Clone, Default)]
+pub struct Ohlc {
+    open: Option<(u64, U256)>,
+    close: Option<(u64, U256)>,
+    high: U256,
+    low: U256,
+    volume: U256,
+    updates: u64,
+}
+
+impl Ohlc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, price: U256, volume: U256) {
+        self.update_at(self.updates, price, volume);
+    }
+
+    /// Same as `update`, but the open and close are picked by the lowest and highest `ordinal` seen.
+    pub fn update_at(&mut self, ordinal: u64, price: U256, volume: U256) {
+        if self.open.map_or(true, |(first, _)| ordinal < first) {
+            self.open = Some((ordinal, price));
+        }
+        if self.close.map_or(true, |(last, _)| ordinal >= last) {
+            self.close = Some((ordinal, price));
+        }
+
+        if self.updates == 0 {
+            self.high = price;
+            self.low = price;
+        } else {
+            self.high = self.high.max(price);
+            self.low = self.low.min(price);
+        }
+        self.volume = self.volume.saturating_add(volume);
+        self.updates += 1;
+    }
+
+    /// The candle as a struct of uints with the keys "open", "high", "low", "close" and "volume".
+    /// A candle without any updates is Null.
+    pub fn to_sol(&self) -> SolidityType {
+        let (Some((_, open)), Some((_, close))) = (self.open, self.close) else {
+            return SolidityType::Null;
+        };
+
+        map_literal! {
+            "open"; SolidityType::Uint(open),
+            "high"; SolidityType::Uint(self.high),
+            "low"; SolidityType::Uint(self.low),
+            "close"; SolidityType::Uint(close),
+            "volume"; SolidityType::Uint(self.volume)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_timestamp() {
+        // 2023-04-12T22:27:35Z, a Wednesday
+        let ts = 1_681_338_455;
+        assert_eq!(bucket_timestamp(ts, Window::Hour), 1_681_336_800);
+        assert_eq!(bucket_timestamp(ts, Window::Day), 1_681_257_600);
+        // Monday 2023-04-10
+        assert_eq!(bucket_timestamp(ts, Window::Week), 1_681_084_800);
+        assert_eq!(bucket_timestamp(ts, Window::Custom(15 * 60)), 1_681_337_700);
+
+        // an exact edge starts its own window, one second before belongs to the previous one
+        let edge = 1_681_336_800;
+        assert_eq!(bucket_timestamp(edge, Window::Hour), edge);
+        assert_eq!(bucket_timestamp(edge - 1, Window::Hour), edge - HOUR);
+        let monday = 1_681_084_800;
+        assert_eq!(bucket_timestamp(monday, Window::Week), monday);
+        assert_eq!(bucket_timestamp(monday - 1, Window::Week), monday - WEEK);
+
+        assert_eq!(bucket_timestamp(0, Window::Day), 0);
+        assert_eq!(bucket_timestamp(0, Window::Week), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "zero seconds")]
+    fn test_bucket_zero_window() {
+        bucket_timestamp(1, Window::Custom(0));
+    }
+
+    #[test]
+    fn test_bucket_key() {
+        let pool = SolidityType::Address(Address::from_slice(&[1; 20]));
+        assert_eq!(
+            bucket_key("candle", &pool, 1_681_338_455, Window::Hour).to_string(),
+            format!("candle:{}:1681336800", pool.to_string())
+        );
+        // every window of the pool shares its prefix
+        let key = bucket_key("candle", &pool, 1_681_338_455, Window::Day);
+        assert!(key
+            .to_string()
+            .starts_with(&StoreKey::new("candle").push(pool).prefix().to_string()));
+    }
+
+    #[test]
+    fn test_ohlc() {
+        let uint = |val: u64| U256::from(val);
+        assert!(matches!(Ohlc::new().to_sol(), SolidityType::Null));
+
+        let mut candle = Ohlc::new();
+        for (price, volume) in [(10, 1), (15, 2), (5, 3), (12, 4)] {
+            candle.update(uint(price), uint(volume));
+        }
+        let sol = candle.to_sol();
+        assert_eq!(sol.get("open"), uint(10));
+        assert_eq!(sol.get("high"), uint(15));
+        assert_eq!(sol.get("low"), uint(5));
+        assert_eq!(sol.get("close"), uint(12));
+        assert_eq!(sol.get("volume"), uint(10));
+
+        // the same swaps, arriving out of order within the block
+        let mut candle = Ohlc::new();
+        for (ordinal, price, volume) in [(3, 5, 3), (1, 10, 1), (4, 12, 4), (2, 15, 2)] {
+            candle.update_at(ordinal, uint(price), uint(volume));
+        }
+        assert_eq!(
+            serde_json::to_value(candle.to_sol()).unwrap(),
+            serde_json::to_value(sol).unwrap()
+        );
+    }
+}
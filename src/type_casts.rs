@@ -19,30 +19,27 @@ pub fn address<T: Into<SolidityType>>(value: T) -> SolidityType {
             if val == 0 {
                 ZERO_ADDRESS
             } else {
-                Address::from_slice(&[1])
+                Address::with_last_byte(1)
             }
         }
         SolidityType::Uint(val) => {
             let word = FixedBytes::from(val);
             Address::from_word(word)
         }
+        SolidityType::Int(val) if val.is_negative() => return SolidityType::Null,
+        SolidityType::Int(val) => Address::from_word(FixedBytes::from(val.into_raw())),
         SolidityType::Enum(val) => {
-            let word = FixedBytes::with_last_byte(val.to());
-            Address::from(word)
+            let word = FixedBytes::from(U256::from(val.to::<u8>()));
+            Address::from_word(word)
         }
         SolidityType::Address(val) => val,
-        SolidityType::ByteArray(val) => {
-            // NOTE I am not fully sure if this is correct.
-            // The strings should be 0x prefixed. But not sure if that's always the case
-            if val.len() <= 22 {
-                let slice = &val[2..];
-                Address::from_slice(&slice)
-            } else {
-                let slice = &val[2..22];
-                Address::from_slice(&slice)
-            }
-        }
+        // a topic or abi encoded word is left padded, any length but those two isn't an address
+        SolidityType::ByteArray(val) if val.len() == 20 => Address::from_slice(&val),
+        SolidityType::ByteArray(val) if val.len() == 32 => Address::from_slice(&val[12..]),
+        SolidityType::ByteArray(_) => return SolidityType::Null,
         SolidityType::FixedArray(val) => Address::from_word(val),
+        SolidityType::FixedBytes(20, val) => Address::from_slice(&val),
+        SolidityType::FixedBytes(..) => return SolidityType::Null,
         SolidityType::String(val) => {
             if let Ok(address) = Address::from_str(&val) {
                 address
@@ -52,6 +49,7 @@ pub fn address<T: Into<SolidityType>>(value: T) -> SolidityType {
         }
         SolidityType::Tuple(_)
         | SolidityType::List(_)
+        | SolidityType::SizedList(..)
         | SolidityType::Struct(_)
         | SolidityType::Null => return SolidityType::Null,
     };
@@ -71,19 +69,23 @@ pub fn string<T: Into<SolidityType>>(value: T) -> SolidityType {
             }
         }
         SolidityType::Uint(val) => val.to_string(),
+        SolidityType::Int(val) => val.to_string(),
         SolidityType::Enum(val) => val.to_string(),
-        SolidityType::Address(val) => val.to_string(),
+        // lowercase to match the ToString impl, see SolidityType::to_checksum_string for display
+        SolidityType::Address(_) => value.to_lowercase_string(),
         SolidityType::ByteArray(val) => {
             // NOTE I am not fully sure if this is correct.
             // The strings should be 0x prefixed. But not sure if that's always the case
             val.to_string()
         }
         SolidityType::FixedArray(val) => val.to_string(),
+        SolidityType::FixedBytes(_, val) => val.to_string(),
         SolidityType::String(_) => {
             return value;
         }
         SolidityType::Tuple(_)
         | SolidityType::List(_)
+        | SolidityType::SizedList(..)
         | SolidityType::Struct(_)
         | SolidityType::Null => return SolidityType::Null,
     };
@@ -103,7 +105,10 @@ pub fn uint<T: Into<SolidityType>>(value: T) -> SolidityType {
             }
         }
         SolidityType::Uint(val) => val,
-        SolidityType::Enum(val) => Uint::from(val),
+        // a negative int has no uint to cast to
+        SolidityType::Int(val) if val.is_negative() => return SolidityType::Null,
+        SolidityType::Int(val) => val.into_raw(),
+        SolidityType::Enum(val) => Uint::from(val.to::<u8>()),
         SolidityType::Address(val) => {
             let value = val.into_array();
             Uint::from_be_slice(&value[..])
@@ -115,6 +120,7 @@ pub fn uint<T: Into<SolidityType>>(value: T) -> SolidityType {
             Uint::from_be_slice(&word[..])
         }
         SolidityType::FixedArray(val) => Uint::from_be_slice(&val.0),
+        SolidityType::FixedBytes(_, val) => Uint::from_be_slice(&val),
         SolidityType::String(val) => {
             if let Ok(val) = val.parse() {
                 val
@@ -124,6 +130,7 @@ pub fn uint<T: Into<SolidityType>>(value: T) -> SolidityType {
         }
         SolidityType::Tuple(_)
         | SolidityType::List(_)
+        | SolidityType::SizedList(..)
         | SolidityType::Struct(_)
         | SolidityType::Null => return SolidityType::Null,
     };
@@ -131,6 +138,98 @@ pub fn uint<T: Into<SolidityType>>(value: T) -> SolidityType {
     SolidityType::Uint(value)
 }
 
+/// Casts the value to a signed int. Uints past the int256 max, and bytes that aren't a full two's complement word are Null.
+pub fn int<T: Into<SolidityType>>(value: T) -> SolidityType {
+    let value: SolidityType = Into::into(value);
+    let value = match value {
+        SolidityType::Boolean(val) => I256::try_from(val.to::<u8>()).unwrap(),
+        SolidityType::Enum(val) => I256::try_from(val.to::<u8>()).unwrap(),
+        SolidityType::Uint(val) => match I256::try_from(val) {
+            Ok(val) => val,
+            Err(_) => return SolidityType::Null,
+        },
+        SolidityType::Int(val) => val,
+        SolidityType::FixedArray(val) => I256::from_raw(U256::from_be_bytes(val.0)),
+        SolidityType::String(val) => match val.trim().parse() {
+            Ok(val) => val,
+            Err(_) => return SolidityType::Null,
+        },
+        SolidityType::Address(_)
+        | SolidityType::ByteArray(_)
+        | SolidityType::FixedBytes(..)
+        | SolidityType::Tuple(_)
+        | SolidityType::List(_)
+        | SolidityType::SizedList(..)
+        | SolidityType::Struct(_)
+        | SolidityType::Null => return SolidityType::Null,
+    };
+
+    SolidityType::Int(value)
+}
+
+/// Casts the value to a bool. Numbers are true when they aren't zero, strings must be "true", "false", "1" or "0",
+/// and Null is false so a missing store value reads as unset.
+pub fn boolean<T: Into<SolidityType>>(value: T) -> SolidityType {
+    let value: SolidityType = Into::into(value);
+    let value = match value {
+        SolidityType::Boolean(_) => return value,
+        SolidityType::Uint(val) => !val.is_zero(),
+        SolidityType::Int(val) => !val.is_zero(),
+        SolidityType::Enum(val) => !val.is_zero(),
+        SolidityType::String(val) => match val.trim() {
+            "true" | "1" => true,
+            "false" | "0" => false,
+            _ => return SolidityType::Null,
+        },
+        SolidityType::Null => false,
+        SolidityType::Address(_)
+        | SolidityType::ByteArray(_)
+        | SolidityType::FixedArray(_)
+        | SolidityType::FixedBytes(..)
+        | SolidityType::Tuple(_)
+        | SolidityType::List(_)
+        | SolidityType::SizedList(..)
+        | SolidityType::Struct(_) => return SolidityType::Null,
+    };
+
+    SolidityType::from(value)
+}
+
+/// Casts the value to an enum whose last variant is `max`, ie 2 for `enum Side { Buy, Sell, Both }`.
+/// Anything `uint` can't cast, or past `max`, is Null.
+pub fn enum_of<T: Into<SolidityType>>(value: T, max: u8) -> SolidityType {
+    match uint(value) {
+        SolidityType::Uint(val) if val <= U256::from(max) => {
+            SolidityType::Enum(U8::from(val.to::<u8>()))
+        }
+        _ => SolidityType::Null,
+    }
+}
+
+/// Casts the value to a uint, returning Null if it doesn't fit in a `uint<bits>`.
+pub fn uint_sized<T: Into<SolidityType>>(value: T, bits: usize) -> SolidityType {
+    try_uint_sized(value, bits).unwrap_or(SolidityType::Null)
+}
+
+/// Casts the value to a uint, erroring if it can't be cast or doesn't fit in a `uint<bits>`.
+pub fn try_uint_sized<T: Into<SolidityType>>(
+    value: T,
+    bits: usize,
+) -> Result<SolidityType, ConvertError> {
+    match uint(value) {
+        SolidityType::Null => Err(ConvertError::new(
+            None,
+            "the value can't be cast to a uint".to_string(),
+        )),
+        value if value.fits_in_bits(bits) => Ok(value),
+        value => Err(ConvertError::new(
+            None,
+            format!("{} doesn't fit in a uint{bits}", value.to_string()),
+        )),
+    }
+}
+
+/// Strings starting with 0x are hex decoded, and are Null if they aren't valid hex. Any other string is taken as its utf8 bytes.
 pub fn bytes<T: Into<SolidityType>>(value: T) -> SolidityType {
     let value: SolidityType = Into::into(value);
     let value = match value {
@@ -146,6 +245,11 @@ pub fn bytes<T: Into<SolidityType>>(value: T) -> SolidityType {
             let bytes: [u8; 32] = val.to_be_bytes();
             Bytes::copy_from_slice(&bytes[..])
         }
+        // two's complement, same as an abi encoded int256
+        SolidityType::Int(val) => {
+            let bytes: [u8; 32] = val.to_be_bytes();
+            Bytes::copy_from_slice(&bytes[..])
+        }
         SolidityType::Enum(val) => {
             let byte: u8 = val.to::<u8>();
             Bytes::copy_from_slice(&[byte])
@@ -161,18 +265,372 @@ pub fn bytes<T: Into<SolidityType>>(value: T) -> SolidityType {
             Bytes::copy_from_slice(&word[..])
         }
         SolidityType::FixedArray(val) => Bytes::copy_from_slice(&val.0),
-        SolidityType::String(val) => {
-            if let Ok(val) = val.parse() {
-                val
-            } else {
-                return SolidityType::Null;
+        SolidityType::FixedBytes(_, val) => val,
+        SolidityType::String(val) => match val.strip_prefix("0x") {
+            Some(hex) => match hex::decode(hex) {
+                Ok(decoded) => Bytes::from(decoded),
+                Err(_) => return SolidityType::Null,
+            },
+            None => Bytes::copy_from_slice(val.as_bytes()),
+        },
+        SolidityType::Tuple(_)
+        | SolidityType::List(_)
+        | SolidityType::SizedList(..)
+        | SolidityType::Struct(_)
+        | SolidityType::Null => return SolidityType::Null,
+    };
+
+    SolidityType::ByteArray(value)
+}
+
+/// Same as `bytes`, but strings are always taken as their utf8 bytes, even when they look like hex.
+pub fn bytes_utf8<T: Into<SolidityType>>(value: T) -> SolidityType {
+    match Into::into(value) {
+        SolidityType::String(val) => SolidityType::ByteArray(Bytes::from(val.into_bytes())),
+        value => bytes(value),
+    }
+}
+
+/// Casts the value to a `bytes<size>`, keeping its size rather than padding it out to a bytes32.
+/// Bytes and hex strings must be exactly `size` bytes long. A bytes32 is truncated, as long as only zeroes are cut off,
+/// since that's how a smaller bytesN is laid out in an abi word.
+pub fn fixed_bytes<T: Into<SolidityType>>(value: T, size: usize) -> SolidityType {
+    let value: SolidityType = Into::into(value);
+    if size == 0 || size > 32 {
+        return SolidityType::Null;
+    }
+
+    let bytes = match &value {
+        SolidityType::FixedArray(val) if val[size..].iter().all(|byte| *byte == 0) => {
+            Bytes::copy_from_slice(&val[..size])
+        }
+        SolidityType::ByteArray(val) | SolidityType::FixedBytes(_, val) if val.len() == size => {
+            val.clone()
+        }
+        SolidityType::String(val) => match hex::decode(val) {
+            Ok(decoded) if decoded.len() == size => Bytes::from(decoded),
+            _ => return SolidityType::Null,
+        },
+        _ => return SolidityType::Null,
+    };
+
+    if size == 32 {
+        SolidityType::FixedArray(B256::from_slice(&bytes))
+    } else {
+        SolidityType::FixedBytes(size, bytes)
+    }
+}
+
+/// Casts the value to a bytes32, ie to compare a raw topic with an event's `SIGNATURE_HASH`.
+/// Padding follows the abi: numbers and addresses are left padded like a uint, bytes and 0x hex strings are right padded
+/// like a bytesN. Bytes longer than 32 are Null rather than truncated, so two different values can't cast to the same word.
+pub fn bytes32<T: Into<SolidityType>>(value: T) -> SolidityType {
+    let value: SolidityType = Into::into(value);
+    let right_padded = |val: &[u8]| {
+        if val.len() > 32 {
+            return SolidityType::Null;
+        }
+        let mut word = B256::ZERO;
+        word[..val.len()].copy_from_slice(val);
+        SolidityType::FixedArray(word)
+    };
+
+    let word = match value {
+        SolidityType::FixedArray(_) => return value,
+        SolidityType::Boolean(_) | SolidityType::Enum(_) | SolidityType::Uint(_) => {
+            match uint(value) {
+                SolidityType::Uint(val) => B256::from(val),
+                _ => return SolidityType::Null,
             }
         }
+        SolidityType::Int(val) => B256::from(val.into_raw()),
+        SolidityType::Address(val) => val.into_word(),
+        SolidityType::ByteArray(val) | SolidityType::FixedBytes(_, val) => {
+            return right_padded(&val)
+        }
+        SolidityType::String(val) => match val.strip_prefix("0x").map(hex::decode) {
+            Some(Ok(decoded)) => return right_padded(&decoded),
+            _ => return SolidityType::Null,
+        },
         SolidityType::Tuple(_)
         | SolidityType::List(_)
+        | SolidityType::SizedList(..)
         | SolidityType::Struct(_)
         | SolidityType::Null => return SolidityType::Null,
     };
 
-    SolidityType::ByteArray(value)
+    SolidityType::FixedArray(word)
+}
+
+/// The keccak256 hash of the value as a bytes32, ie `keccak("Transfer(address,address,uint256)".to_string())` is the topic0 of a Transfer.
+/// Strings are hashed as their utf8 bytes, anything else as the bytes `bytes` casts it to, see `bytes_utf8`. Containers and Null hash to Null.
+pub fn keccak<T: Into<SolidityType>>(value: T) -> SolidityType {
+    match bytes_utf8(value) {
+        SolidityType::ByteArray(val) => SolidityType::FixedArray(keccak256(&val)),
+        _ => SolidityType::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn max_for(bits: usize) -> U256 {
+        (U256::from(1) << bits) - U256::from(1)
+    }
+
+    #[test]
+    fn test_uint_sized_boundaries() {
+        for bits in [8, 128, 160] {
+            let max = max_for(bits);
+            assert_eq!(uint_sized(max, bits), max);
+            assert!(matches!(
+                uint_sized(max + U256::from(1), bits),
+                SolidityType::Null
+            ));
+            let err = try_uint_sized(max + U256::from(1), bits).unwrap_err();
+            assert!(err.message.contains(&format!("uint{bits}")), "{err}");
+        }
+
+        assert_eq!(uint_sized(U256::ZERO, 8), U256::ZERO);
+        assert!(try_uint_sized("not a number".to_string(), 8).is_err());
+    }
+
+    #[test]
+    fn test_fits_in_bits() {
+        let max = SolidityType::Uint(max_for(160));
+        assert!(max.fits_in_bits(160));
+        assert!(!max.fits_in_bits(159));
+        assert!(!max.is_zero());
+
+        // an address is exactly 160 bits, anything wider gets truncated when cast
+        assert_eq!(uint(address(max.clone())), max_for(160));
+        let too_wide = SolidityType::Uint(max_for(160) + U256::from(1));
+        assert!(!too_wide.fits_in_bits(160));
+        assert_eq!(address(too_wide), Address::ZERO);
+
+        assert!(SolidityType::Uint(U256::ZERO).is_zero());
+        assert!(SolidityType::Enum(U8::from(255)).fits_in_bits(8));
+        assert!(!SolidityType::Enum(U8::from(255)).fits_in_bits(7));
+    }
+
+    #[test]
+    fn test_enum_casts() {
+        let value = SolidityType::Enum(U8::from(3));
+        assert_eq!(uint(value.clone()), U256::from(3));
+        assert_eq!(uint_sized(value.clone(), 8), U256::from(3));
+        assert_eq!(address(value), address(U256::from(3)));
+    }
+
+    #[test]
+    fn test_address_casts() {
+        let token = Address::from_slice(&[7; 20]);
+        let raw = SolidityType::ByteArray(Bytes::copy_from_slice(token.as_slice()));
+        assert_eq!(address(raw), token);
+        let topic = SolidityType::ByteArray(Bytes::copy_from_slice(token.into_word().as_slice()));
+        assert_eq!(address(topic), token);
+
+        for len in [1, 0, 21] {
+            let short = SolidityType::ByteArray(Bytes::copy_from_slice(&vec![1; len]));
+            assert!(matches!(address(short), SolidityType::Null));
+        }
+
+        assert_eq!(address(true), Address::with_last_byte(1));
+        assert_eq!(address(false), ZERO_ADDRESS);
+    }
+
+    #[test]
+    fn test_bytes_from_strings() {
+        let string = |val: &str| SolidityType::String(val.to_string());
+        assert!(
+            matches!(bytes(string("0xdeadbeef")), SolidityType::ByteArray(val) if val[..] == [0xde, 0xad, 0xbe, 0xef])
+        );
+        assert!(
+            matches!(bytes(string("hello")), SolidityType::ByteArray(val) if val[..] == *b"hello")
+        );
+        assert!(matches!(bytes(string("0xabc")), SolidityType::Null));
+        assert!(matches!(bytes(string("0xzz")), SolidityType::Null));
+
+        assert!(
+            matches!(bytes_utf8(string("0xabc")), SolidityType::ByteArray(val) if val[..] == *b"0xabc")
+        );
+        assert!(
+            matches!(bytes_utf8(string("hello")), SolidityType::ByteArray(val) if val[..] == *b"hello")
+        );
+        assert!(
+            matches!(bytes_utf8(U256::from(1)), SolidityType::ByteArray(val) if val.len() == 32)
+        );
+    }
+
+    #[test]
+    fn test_boolean_casts() {
+        let string = |val: &str| SolidityType::String(val.to_string());
+        let cases = [
+            (SolidityType::from(true), Some(true)),
+            (SolidityType::from(false), Some(false)),
+            (SolidityType::Uint(U256::from(0)), Some(false)),
+            (SolidityType::Uint(U256::from(7)), Some(true)),
+            (SolidityType::Int(I256::try_from(-1).unwrap()), Some(true)),
+            (SolidityType::Enum(U8::from(0)), Some(false)),
+            (string("true"), Some(true)),
+            (string("false"), Some(false)),
+            (string("1"), Some(true)),
+            (string("0"), Some(false)),
+            (string("yes"), None),
+            (SolidityType::Null, Some(false)),
+            (SolidityType::Address(ZERO_ADDRESS), None),
+            (SolidityType::List(vec![]), None),
+        ];
+
+        for (value, expected) in cases {
+            let cast = boolean(value.clone());
+            match expected {
+                Some(expected) => assert_eq!(cast, SolidityType::from(expected), "{value:?}"),
+                None => assert!(matches!(cast, SolidityType::Null), "{value:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_enum_of_casts() {
+        let string = |val: &str| SolidityType::String(val.to_string());
+        let cases = [
+            (SolidityType::Uint(U256::from(2)), Some(2)),
+            (SolidityType::Uint(U256::from(3)), None),
+            (SolidityType::Uint(U256::from(256)), None),
+            (SolidityType::Enum(U8::from(1)), Some(1)),
+            (SolidityType::Enum(U8::from(5)), None),
+            (SolidityType::from(true), Some(1)),
+            (SolidityType::Int(I256::try_from(-1).unwrap()), None),
+            (string("0"), Some(0)),
+            (string("buy"), None),
+            (SolidityType::Null, None),
+        ];
+
+        for (value, expected) in cases {
+            let cast = enum_of(value.clone(), 2);
+            match expected {
+                Some(expected) => {
+                    assert_eq!(cast, SolidityType::Enum(U8::from(expected)), "{value:?}")
+                }
+                None => assert!(matches!(cast, SolidityType::Null), "{value:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixed_bytes() {
+        let selector = fixed_bytes("0xa9059cbb".to_string(), 4);
+        assert!(matches!(&selector, SolidityType::FixedBytes(4, val) if val.len() == 4));
+        assert!(
+            matches!(string(selector.clone()), SolidityType::String(val) if val == "0xa9059cbb")
+        );
+        assert!(matches!(bytes(selector.clone()), SolidityType::ByteArray(val) if val.len() == 4));
+        assert_eq!(uint(selector), U256::from(0xa9059cbbu64));
+
+        // a bytes4 left aligned in an abi word
+        let mut word = [0u8; 32];
+        word[..4].copy_from_slice(&[0xa9, 0x05, 0x9c, 0xbb]);
+        assert!(matches!(
+            fixed_bytes(B256::from(word), 4),
+            SolidityType::FixedBytes(4, val) if val[..] == [0xa9, 0x05, 0x9c, 0xbb]
+        ));
+        assert!(matches!(
+            fixed_bytes(B256::from(word), 2),
+            SolidityType::Null
+        ));
+        assert!(matches!(
+            fixed_bytes("0xa9059c".to_string(), 4),
+            SolidityType::Null
+        ));
+    }
+
+    #[test]
+    fn test_int_casts() {
+        let minus_five = I256::try_from(-5i64).unwrap();
+
+        assert_eq!(int(U256::from(5)), I256::try_from(5i64).unwrap());
+        assert_eq!(int("-5".to_string()), minus_five);
+        assert_eq!(int(true), I256::ONE);
+        assert_eq!(
+            int(SolidityType::FixedArray(B256::from(minus_five.into_raw()))),
+            minus_five
+        );
+        assert!(matches!(int(U256::MAX), SolidityType::Null));
+        assert!(matches!(int(Address::ZERO), SolidityType::Null));
+
+        // a negative int has no uint or address
+        assert!(matches!(uint(minus_five), SolidityType::Null));
+        assert!(matches!(address(minus_five), SolidityType::Null));
+        assert_eq!(uint(I256::try_from(5i64).unwrap()), U256::from(5));
+        assert!(matches!(string(minus_five), SolidityType::String(val) if val == "-5"));
+        assert!(
+            matches!(bytes(minus_five), SolidityType::ByteArray(val) if val[..] == minus_five.to_be_bytes::<32>())
+        );
+    }
+
+    #[test]
+    fn test_bytes32_casts() {
+        // numbers are left padded, so they round trip through uint
+        let word = bytes32(U256::from(3000));
+        assert!(
+            matches!(&word, SolidityType::FixedArray(val) if val[30..] == [0x0b, 0xb8] && val[..30].iter().all(|b| *b == 0))
+        );
+        assert_eq!(uint(word), U256::from(3000));
+        assert_eq!(uint(bytes32(true)), U256::from(1));
+        let minus_one = bytes32(I256::try_from(-1).unwrap());
+        assert_eq!(int(minus_one), I256::try_from(-1).unwrap());
+
+        // an address is left padded like a topic
+        let token = Address::from_slice(&[7; 20]);
+        let topic = bytes32(token);
+        assert!(matches!(&topic, SolidityType::FixedArray(val) if *val == token.into_word()));
+        assert_eq!(address(bytes(topic)), token);
+
+        // bytes and hex strings are right padded like a bytesN
+        let selector = SolidityType::ByteArray(Bytes::copy_from_slice(&[0xa9, 0x05, 0x9c, 0xbb]));
+        let word = bytes32(selector);
+        assert!(
+            matches!(bytes(word.clone()), SolidityType::ByteArray(val) if val.len() == 32 && val[..4] == [0xa9, 0x05, 0x9c, 0xbb])
+        );
+        assert_eq!(
+            fixed_bytes(word.clone(), 4),
+            fixed_bytes("0xa9059cbb".to_string(), 4)
+        );
+        assert_eq!(bytes32("0xa9059cbb".to_string()), word);
+
+        let hash = b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+        assert_eq!(bytes32(format!("{hash}")), SolidityType::FixedArray(hash));
+        assert_eq!(
+            bytes32(bytes(SolidityType::FixedArray(hash))),
+            SolidityType::FixedArray(hash)
+        );
+
+        let too_long = SolidityType::ByteArray(Bytes::copy_from_slice(&[1; 33]));
+        assert!(matches!(bytes32(too_long), SolidityType::Null));
+        assert!(matches!(
+            bytes32("Transfer".to_string()),
+            SolidityType::Null
+        ));
+        assert!(matches!(bytes32(SolidityType::Null), SolidityType::Null));
+    }
+
+    #[test]
+    fn test_keccak() {
+        let transfer = keccak("Transfer(address,address,uint256)".to_string());
+        assert!(matches!(
+            transfer,
+            SolidityType::FixedArray(hash) if hash == b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef")
+        ));
+
+        // a uint hashes as its 32 byte word, same as keccak256(abi.encode(value))
+        assert!(matches!(
+            keccak(U256::from(1)),
+            SolidityType::FixedArray(hash) if hash == keccak256(U256::from(1).to_be_bytes::<32>())
+        ));
+        assert!(matches!(
+            keccak(SolidityType::List(vec![])),
+            SolidityType::Null
+        ));
+    }
 }
@@ -0,0 +1,87 @@
+use alloy_primitives::B256;
+use alloy_sol_types::SolEvent;
+use substreams_alloy_helpers::prelude::*;
+use substreams_alloy_helpers::{event_store, loose_sol};
+use substreams_ethereum::pb::eth::v2::{
+    Block, Log, TransactionReceipt, TransactionTrace, TransactionTraceStatus,
+};
+
+loose_sol! {
+    event Transfer(address indexed from, address indexed to, uint256 value);
+}
+
+loose_sol! {
+    event Approval(address indexed owner, address indexed spender, uint256 value);
+}
+
+const POOL: Address = Address::new([5; 20]);
+
+fn log(address: Address, topic0: B256, from: u8, to: u8, value: u64) -> Log {
+    Log {
+        address: address.to_vec(),
+        topics: vec![
+            topic0.to_vec(),
+            Address::new([from; 20]).into_word().to_vec(),
+            Address::new([to; 20]).into_word().to_vec(),
+        ],
+        data: U256::from(value).to_be_bytes::<32>().to_vec(),
+        ..Default::default()
+    }
+}
+
+fn fixture_block() -> Block {
+    let logs = vec![
+        log(POOL, Transfer::SIGNATURE_HASH, 1, 2, 100),
+        // same event from another contract, filtered out by the addresses
+        log(Address::new([9; 20]), Transfer::SIGNATURE_HASH, 1, 3, 200),
+        log(POOL, Approval::SIGNATURE_HASH, 1, 4, 300),
+    ];
+
+    Block {
+        number: 42,
+        transaction_traces: vec![TransactionTrace {
+            from: vec![1; 20],
+            to: POOL.to_vec(),
+            status: TransactionTraceStatus::Succeeded as i32,
+            receipt: Some(TransactionReceipt {
+                logs,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_event_store() {
+    let blk = fixture_block();
+    let store = MockStore::default();
+
+    let writes = event_store! {
+        block: blk,
+        store: store,
+        event: Transfer, addresses: [POOL], key: |e| e.get("to"), value: |e| e;
+        event: Approval, addresses: [], key: |e| e.get("spender"), value: |e| e.get("value");
+    };
+    assert_eq!(writes, 2);
+
+    let key = |byte: u8| SolidityType::Address(Address::new([byte; 20])).to_string();
+    assert_eq!(store.generic_get(key(2)).get("value"), U256::from(100));
+    assert_eq!(store.generic_get(key(4)), U256::from(300));
+    assert!(!store.contains_key(&key(3)));
+}
+
+#[test]
+fn test_event_store_empty_block() {
+    let blk = Block::default();
+    let store = MockStore::default();
+
+    let writes = event_store! {
+        block: blk,
+        store: store,
+        event: Transfer, addresses: [POOL], key: |e| e.get("to"), value: |e| e
+    };
+    assert_eq!(writes, 0);
+    assert!(store.writes().is_empty());
+}
@@ -0,0 +1,64 @@
+use prost_wkt_types::Struct;
+use substreams::pb::substreams::store_delta::Operation;
+use substreams::store::{DeltaProto, Deltas, StoreGetProto, StoreNew};
+use substreams_alloy_helpers::format_inputs;
+use substreams_alloy_helpers::prelude::*;
+
+fn stored(value: SolidityType) -> Struct {
+    value.to_stored_struct().unwrap()
+}
+
+/// A module with one input of each kind, returning its inputs as they were formatted.
+fn map_inputs(
+    prior: Struct,
+    params: String,
+    pools: StoreGetProto<Struct>,
+    transfers: Deltas<DeltaProto<Struct>>,
+    config: Option<Struct>,
+) -> (
+    SolidityType,
+    SolidityType,
+    LocalVar,
+    SolidityType,
+    SolidityType,
+) {
+    format_inputs!(prior, params: string, pools: store, transfers: deltas, config: optional);
+    (prior, params, pools, transfers, config)
+}
+
+#[test]
+fn test_format_inputs() {
+    let transfers = Deltas {
+        deltas: vec![DeltaProto {
+            operation: Operation::Create,
+            ordinal: 0,
+            key: "transfer:1".to_string(),
+            old_value: Struct::default(),
+            new_value: stored(SolidityType::Uint(U256::from(5))),
+        }],
+    };
+
+    let (prior, params, pools, transfers, config) = map_inputs(
+        stored(SolidityType::Uint(U256::from(500))),
+        "factory=0x1f98".to_string(),
+        StoreGetProto::new(0),
+        transfers,
+        None,
+    );
+
+    assert_eq!(prior, U256::from(500));
+    assert!(matches!(params, SolidityType::String(params) if params == "factory=0x1f98"));
+    assert!(matches!(pools, LocalVar::StoreGet(_)));
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers.get("0").get("new_value"), U256::from(5));
+    assert!(matches!(config, SolidityType::Null));
+
+    let (_, _, _, _, config) = map_inputs(
+        Struct::default(),
+        String::new(),
+        StoreGetProto::new(0),
+        Deltas { deltas: vec![] },
+        Some(stored(SolidityType::from(true))),
+    );
+    assert_eq!(config, SolidityType::from(true));
+}
@@ -0,0 +1,6 @@
+#[test]
+fn test_locals_expansion() {
+    // the unsupported input case is a compile_fail doctest on `locals!`, so it doesn't depend on rustc's wording
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/locals_pass.rs");
+}
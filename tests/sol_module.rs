@@ -0,0 +1,56 @@
+use substreams::store::Deltas;
+use substreams_alloy_helpers::prelude::*;
+use substreams_alloy_helpers::{map_literal, sol_module};
+use substreams_ethereum::pb::eth::v2::Block;
+
+sol_module! {
+    fn map_minimal(blk: block, pools: store, transfers: deltas, prior: map, params: params) -> output {
+        output.insert("number", SolidityType::Uint(U256::from(blk.number)));
+        output.insert("pool", pools.generic_get(params.clone()));
+        output.insert("transfers", transfers);
+        output.insert("prior", prior);
+        output.insert("params", params);
+    }
+}
+
+sol_module! {
+    fn map_summary(blk: block, transfers: deltas, prior: map, params: params) -> output {
+        output.insert("number", SolidityType::Uint(U256::from(blk.number)));
+        output.insert("transfers", SolidityType::Uint(U256::from(transfers.len())));
+        output.insert("prior", prior);
+        output.insert("params", params);
+    }
+}
+
+sol_module! {
+    fn map_empty(blk: block) -> output {
+        let _ = blk;
+    }
+}
+
+#[test]
+fn test_sol_module_handler() {
+    let blk = Block {
+        number: 17,
+        ..Default::default()
+    };
+    let prior = map_literal!("count"; SolidityType::Uint(U256::from(2)))
+        .to_proto_struct()
+        .unwrap();
+    let output = map_summary::handler(blk, Deltas { deltas: vec![] }, prior, "0xabc".to_string())
+        .expect("Expected an output");
+
+    let output = SolidityType::from(output);
+    assert_eq!(output.get("number"), U256::from(17));
+    assert_eq!(output.get("transfers"), U256::from(0));
+    assert_eq!(output.get("prior").get("count"), U256::from(2));
+    assert_eq!(
+        output.get("params"),
+        SolidityType::String("0xabc".to_string())
+    );
+}
+
+#[test]
+fn test_sol_module_empty_output() {
+    assert!(map_empty::handler(Block::default()).is_none());
+}
@@ -0,0 +1,39 @@
+#![allow(deprecated)]
+
+use serde_json::json;
+use std::collections::HashMap;
+use substreams_alloy_helpers::{prelude::*, to_array};
+
+#[test]
+fn test_to_array_vec() {
+    let values: Option<Vec<u64>> = Some(vec![1, 2, 3]);
+    assert_eq!(to_array!(values), Some(vec![json!(1), json!(2), json!(3)]));
+
+    let missing: Option<Vec<u64>> = None;
+    assert_eq!(to_array!(missing), None);
+
+    let not_a_list: Option<HashMap<String, u64>> = Some(HashMap::from([("a".to_string(), 1)]));
+    assert_eq!(to_array!(not_a_list), None);
+}
+
+#[test]
+fn test_to_array_solidity_type() {
+    let uints = vec![
+        SolidityType::Uint(U256::from(1)),
+        SolidityType::Uint(U256::from(2)),
+    ];
+    let expected = Some(vec![
+        json!({"type": "uint", "value": "0x1"}),
+        json!({"type": "uint", "value": "0x2"}),
+    ]);
+
+    assert_eq!(to_array!(Some(SolidityType::List(uints.clone()))), expected);
+    assert_eq!(
+        to_array!(Some(SolidityType::Tuple(uints.clone()))),
+        expected
+    );
+    assert_eq!(to_array!(Some(SolidityType::SizedList(2, uints))), expected);
+
+    assert_eq!(to_array!(Some(SolidityType::Uint(U256::from(1)))), None);
+    assert_eq!(to_array!(Some(SolidityType::Null)), None);
+}
@@ -0,0 +1,16 @@
+use prost_wkt_types::Struct;
+use substreams_alloy_helpers::locals;
+use substreams_alloy_helpers::prelude::*;
+
+fn main() {
+    let amount = SolidityType::Uint(U256::from(1));
+    let params = String::from("0x00");
+    let prior = Struct::default();
+
+    let scope = locals![amount, params, prior];
+    assert_eq!(scope.names(), ["amount", "params", "prior"]);
+    assert!(matches!(scope.var_at(2), LocalVar::SolidityType(_)));
+
+    let empty = locals![];
+    assert!(empty.is_empty());
+}
@@ -0,0 +1,36 @@
+use prost_wkt_types::Struct;
+use substreams_alloy_helpers::prelude::*;
+use substreams_alloy_helpers::with_map;
+
+fn map_pool(fee: u64) -> Option<Struct> {
+    with_map!(map,
+        map.insert("fee", SolidityType::Uint(U256::from(fee)));
+        map.insert("token0", SolidityType::Null);
+    )
+}
+
+fn map_params(raw: &str) -> Result<Option<Struct>, substreams::errors::Error> {
+    Ok(with_map!(map,
+        let params = parse_params(raw);
+        map.insert("factory", get_address_param(&params, "factory")?.into());
+    ))
+}
+
+#[test]
+fn test_with_map() {
+    let output = SolidityType::from(map_pool(500).unwrap());
+    assert_eq!(output.get("fee"), U256::from(500));
+    assert_eq!(output.len(), 1);
+
+    let empty: Option<Struct> = with_map!(map, map.insert("missing", SolidityType::Null););
+    assert!(empty.is_none());
+}
+
+#[test]
+fn test_with_map_result() {
+    let output = map_params("factory=0x1f98431c8ad98523631ae4a59f267346ea31f984").unwrap();
+    assert!(output.is_some());
+
+    let err = map_params("start=1").unwrap_err();
+    assert!(err.to_string().contains("factory"), "{err}");
+}